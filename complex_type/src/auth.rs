@@ -0,0 +1,750 @@
+// 密码和登录会话
+// 不依赖任何外部库：
+//   - sha256 / hmac_sha256 / pbkdf2_sha256 按 FIPS 180-4、RFC 2104、RFC 8018 实现；
+//   - PasswordHash 是加盐、多轮迭代的 PBKDF2-HMAC-SHA256，保存成
+//     pbkdf2-sha256$迭代次数$盐$哈希 的字符串，迭代次数跟着哈希走，以后调高也不影响已有的密码；
+//   - 盐和会话令牌从 /dev/urandom 取，取不到就返回错误，不会退化成别人能猜出来的字节；
+//   - SessionStore 保存登录会话，令牌只以哈希的形式保存，过期、登出、按用户批量登出；
+//     validate 时对照注册表，账号停用或者改过密码之后，之前发出的会话一律作废。
+// 失败次数和锁定由 registry.rs 的 sign_in 负责，记在 Account 上，重启之后依然有效。
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+use crate::registry::{unix_seconds, Clock, RegistryError, SystemClock, UserId, UserRegistry};
+
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+const H0: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+fn compress(state: &mut [u32; 8], block: &[u8]) {
+    let mut w = [0u32; 64];
+    for (i, word) in block.chunks_exact(4).enumerate() {
+        w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+    }
+    for i in 16..64 {
+        let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+        let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+        w[i] = w[i - 16]
+            .wrapping_add(s0)
+            .wrapping_add(w[i - 7])
+            .wrapping_add(s1);
+    }
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for i in 0..64 {
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = h
+            .wrapping_add(s1)
+            .wrapping_add(ch)
+            .wrapping_add(K[i])
+            .wrapping_add(w[i]);
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0.wrapping_add(maj);
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+    for (word, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *word = word.wrapping_add(value);
+    }
+}
+
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    let mut state = H0;
+    let mut blocks = data.chunks_exact(64);
+    for block in blocks.by_ref() {
+        compress(&mut state, block);
+    }
+    // 补位：0x80，若干个 0，最后 8 字节是消息的比特数
+    let rest = blocks.remainder();
+    let mut tail = [0u8; 128];
+    tail[..rest.len()].copy_from_slice(rest);
+    tail[rest.len()] = 0x80;
+    let tail_len = if rest.len() < 56 { 64 } else { 128 };
+    tail[tail_len - 8..tail_len].copy_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+    for block in tail[..tail_len].chunks_exact(64) {
+        compress(&mut state, block);
+    }
+    let mut digest = [0u8; 32];
+    for (bytes, word) in digest.chunks_exact_mut(4).zip(state) {
+        bytes.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+pub fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    let mut block = [0u8; 64];
+    if key.len() > 64 {
+        block[..32].copy_from_slice(&sha256(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let mut inner: Vec<u8> = block.iter().map(|b| b ^ 0x36).collect();
+    inner.extend_from_slice(message);
+    let mut outer: Vec<u8> = block.iter().map(|b| b ^ 0x5c).collect();
+    outer.extend_from_slice(&sha256(&inner));
+    sha256(&outer)
+}
+
+// 输出长度任意，按 32 字节一块依次计算
+pub fn pbkdf2_sha256(password: &[u8], salt: &[u8], iterations: u32, output: &mut [u8]) {
+    for (index, chunk) in output.chunks_mut(32).enumerate() {
+        let mut message = salt.to_vec();
+        message.extend_from_slice(&(index as u32 + 1).to_be_bytes());
+        let mut u = hmac_sha256(password, &message);
+        let mut block = u;
+        for _ in 1..iterations {
+            u = hmac_sha256(password, &u);
+            block.iter_mut().zip(u).for_each(|(b, u)| *b ^= u);
+        }
+        chunk.copy_from_slice(&block[..chunk.len()]);
+    }
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub fn from_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+// 比较时间只和长度有关，不会因为前面几个字节相同就更快返回
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+// 盐和令牌必须不可预测，取不到随机数时宁可失败
+pub fn random_bytes(buf: &mut [u8]) -> io::Result<()> {
+    File::open("/dev/urandom")?.read_exact(buf)
+}
+
+pub const HASH_ALGORITHM: &str = "pbkdf2-sha256";
+// OWASP 对 PBKDF2-HMAC-SHA256 的建议值
+pub const DEFAULT_ITERATIONS: u32 = 600_000;
+pub const SALT_LEN: usize = 16;
+pub const PASSWORD_MIN: usize = 8;
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct PasswordHash {
+    iterations: u32,
+    salt: Vec<u8>,
+    hash: [u8; 32],
+}
+
+impl PasswordHash {
+    pub fn new(password: &str, iterations: u32) -> io::Result<Self> {
+        let mut salt = vec![0u8; SALT_LEN];
+        random_bytes(&mut salt)?;
+        Ok(PasswordHash::with_salt(password, salt, iterations))
+    }
+
+    pub fn with_salt(password: &str, salt: Vec<u8>, iterations: u32) -> Self {
+        let mut hash = [0u8; 32];
+        pbkdf2_sha256(password.as_bytes(), &salt, iterations.max(1), &mut hash);
+        PasswordHash {
+            iterations: iterations.max(1),
+            salt,
+            hash,
+        }
+    }
+
+    pub fn verify(&self, password: &str) -> bool {
+        let mut hash = [0u8; 32];
+        pbkdf2_sha256(password.as_bytes(), &self.salt, self.iterations, &mut hash);
+        constant_time_eq(&hash, &self.hash)
+    }
+
+    pub fn iterations(&self) -> u32 {
+        self.iterations
+    }
+
+    // 每次设置密码都会换新的盐，指纹随之改变，会话靠它发现密码改过了
+    fn fingerprint(&self) -> [u8; 32] {
+        sha256(self.to_string().as_bytes())
+    }
+}
+
+// 不把盐和哈希打进日志
+impl fmt::Debug for PasswordHash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "PasswordHash({}, {} rounds)",
+            HASH_ALGORITHM, self.iterations
+        )
+    }
+}
+
+impl fmt::Display for PasswordHash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}${}${}${}",
+            HASH_ALGORITHM,
+            self.iterations,
+            to_hex(&self.salt),
+            to_hex(&self.hash)
+        )
+    }
+}
+
+impl FromStr for PasswordHash {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = text.split('$').collect();
+        let (iterations, salt, hash) = match parts[..] {
+            [HASH_ALGORITHM, iterations, salt, hash] => (iterations, salt, hash),
+            _ => return Err(format!("not a {} hash", HASH_ALGORITHM)),
+        };
+        let iterations = iterations
+            .parse()
+            .ok()
+            .filter(|n| *n > 0)
+            .ok_or_else(|| format!("bad iteration count {:?}", iterations))?;
+        let salt = from_hex(salt).ok_or("bad salt")?;
+        let hash = from_hex(hash)
+            .and_then(|hash| <[u8; 32]>::try_from(hash).ok())
+            .ok_or("bad hash")?;
+        Ok(PasswordHash {
+            iterations,
+            salt,
+            hash,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PasswordError {
+    TooShort { min: usize, found: usize },
+    SameAsUsername,
+}
+
+impl fmt::Display for PasswordError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PasswordError::TooShort { min, found } => write!(
+                f,
+                "must be at least {} characters long, found {}",
+                min, found
+            ),
+            PasswordError::SameAsUsername => f.write_str("must not be the same as the username"),
+        }
+    }
+}
+
+pub fn validate_password(username: &str, password: &str) -> Result<(), PasswordError> {
+    let found = password.chars().count();
+    if found < PASSWORD_MIN {
+        return Err(PasswordError::TooShort {
+            min: PASSWORD_MIN,
+            found,
+        });
+    }
+    if password.eq_ignore_ascii_case(username) {
+        return Err(PasswordError::SameAsUsername);
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionError {
+    UnknownToken,
+    Expired { expired_at: SystemTime },
+    // 账号被删除、停用，或者发出会话之后改过密码
+    Revoked(UserId),
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SessionError::UnknownToken => f.write_str("unknown session token"),
+            SessionError::Expired { expired_at } => {
+                write!(f, "session expired at {}", unix_seconds(*expired_at))
+            }
+            SessionError::Revoked(user) => write!(f, "session of user {} was revoked", user),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+// 令牌只在登录时交给调用方一次
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    pub token: String,
+    pub user: UserId,
+    pub created_at: SystemTime,
+    pub expires_at: SystemTime,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SessionEntry {
+    user: UserId,
+    expires_at: SystemTime,
+    // 发会话时账号密码的指纹，没有密码时为 None
+    credential: Option<[u8; 32]>,
+}
+
+fn credential<R: Clock>(registry: &UserRegistry<R>, user: UserId) -> Option<[u8; 32]> {
+    let account = registry.get(user).ok()?;
+    account
+        .user
+        .password
+        .as_ref()
+        .map(PasswordHash::fingerprint)
+}
+
+pub struct SessionStore<C: Clock = SystemClock> {
+    clock: C,
+    ttl: Duration,
+    // 键是令牌的 sha256，内存被人看到也拿不到能用的令牌
+    sessions: HashMap<[u8; 32], SessionEntry>,
+}
+
+impl SessionStore<SystemClock> {
+    pub fn new(ttl: Duration) -> Self {
+        SessionStore::with_clock(ttl, SystemClock)
+    }
+}
+
+impl<C: Clock> SessionStore<C> {
+    pub fn with_clock(ttl: Duration, clock: C) -> Self {
+        SessionStore {
+            clock,
+            ttl,
+            sessions: HashMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    // 给 registry 里的 user 发一个会话，账号之后停用或改密码，这个会话就失效
+    pub fn issue<R: Clock>(
+        &mut self,
+        registry: &UserRegistry<R>,
+        user: UserId,
+    ) -> io::Result<Session> {
+        let token = new_token()?;
+        Ok(self.insert(token, user, credential(registry, user)))
+    }
+
+    fn insert(&mut self, token: String, user: UserId, credential: Option<[u8; 32]>) -> Session {
+        let created_at = self.clock.now();
+        let expires_at = created_at + self.ttl;
+        self.sessions.insert(
+            sha256(token.as_bytes()),
+            SessionEntry {
+                user,
+                expires_at,
+                credential,
+            },
+        );
+        Session {
+            token,
+            user,
+            created_at,
+            expires_at,
+        }
+    }
+
+    // 校验密码、记一次登录，再发一个会话；令牌先生成好，取不到随机数时不会白记一次登录
+    pub fn login<R: Clock>(
+        &mut self,
+        registry: &mut UserRegistry<R>,
+        username: &str,
+        password: &str,
+    ) -> Result<Session, RegistryError> {
+        let token = new_token().map_err(|err| RegistryError::RandomUnavailable(err.to_string()))?;
+        let id = registry.sign_in(username, password)?.id;
+        Ok(self.insert(token, id, credential(registry, id)))
+    }
+
+    // 过期的、已经作废的会话在这里顺手删掉
+    pub fn validate<R: Clock>(
+        &mut self,
+        registry: &UserRegistry<R>,
+        token: &str,
+    ) -> Result<UserId, SessionError> {
+        let key = sha256(token.as_bytes());
+        let entry = *self.sessions.get(&key).ok_or(SessionError::UnknownToken)?;
+        if self.clock.now() >= entry.expires_at {
+            self.sessions.remove(&key);
+            return Err(SessionError::Expired {
+                expired_at: entry.expires_at,
+            });
+        }
+        let active = registry
+            .get(entry.user)
+            .is_ok_and(|account| account.is_active());
+        if !active || credential(registry, entry.user) != entry.credential {
+            self.sessions.remove(&key);
+            return Err(SessionError::Revoked(entry.user));
+        }
+        Ok(entry.user)
+    }
+
+    pub fn logout(&mut self, token: &str) -> Result<UserId, SessionError> {
+        self.sessions
+            .remove(&sha256(token.as_bytes()))
+            .map(|entry| entry.user)
+            .ok_or(SessionError::UnknownToken)
+    }
+
+    // 主动作废这个用户的所有会话（比如“在所有设备上退出”），返回作废了几个；
+    // 停用账号、改密码不需要调它，旧会话在 validate 时就会被拒绝
+    pub fn logout_all(&mut self, user: UserId) -> usize {
+        let before = self.sessions.len();
+        self.sessions.retain(|_, entry| entry.user != user);
+        before - self.sessions.len()
+    }
+
+    pub fn purge_expired(&mut self) -> usize {
+        let now = self.clock.now();
+        let before = self.sessions.len();
+        self.sessions.retain(|_, entry| now < entry.expires_at);
+        before - self.sessions.len()
+    }
+}
+
+fn new_token() -> io::Result<String> {
+    let mut token = [0u8; 32];
+    random_bytes(&mut token)?;
+    Ok(to_hex(&token))
+}
+
+fn start() -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000)
+}
+
+// 手动时钟上的注册表和会话，julianlee 的密码是 hunter22，会话一小时过期
+fn sample_sessions() -> (
+    crate::registry::ManualClock,
+    UserRegistry<crate::registry::ManualClock>,
+    SessionStore<crate::registry::ManualClock>,
+    UserId,
+) {
+    let clock = crate::registry::ManualClock::new(start());
+    let mut users = UserRegistry::with_clock(clock.clone());
+    // 演示不需要那么多轮，默认的迭代次数是 DEFAULT_ITERATIONS
+    users.set_hash_iterations(1_000);
+    let sessions = SessionStore::with_clock(Duration::from_secs(3600), clock.clone());
+    let julian = users.create("julianlee", "julianlee107@163.com").unwrap();
+    users.set_password(julian, "hunter22").unwrap();
+    (clock, users, sessions, julian)
+}
+
+pub(crate) fn auth() {
+    println!("sha256(\"abc\") = {}", to_hex(&sha256(b"abc")));
+    let hash = PasswordHash::new("correct horse", 1_000).unwrap();
+    println!(
+        "{:?} 存成 {}，{} 轮，验证: {}",
+        hash,
+        hash,
+        hash.iterations(),
+        hash.verify("correct horse")
+    );
+    if let Err(err) = validate_password("julianlee", "short") {
+        println!("short: {}", err);
+    }
+
+    let (clock, mut users, mut sessions, julian) = sample_sessions();
+    let session = sessions.login(&mut users, "julianlee", "hunter22").unwrap();
+    println!(
+        "会话 {}… 属于 {:?}，{} 过期",
+        &session.token[..8],
+        sessions.validate(&users, &session.token),
+        unix_seconds(session.expires_at)
+    );
+    if let Err(err) = sessions.login(&mut users, "julianlee", "hunter23") {
+        println!("{}", err);
+    }
+
+    // 改了密码，之前的会话作废
+    users.set_password(julian, "tr0ub4dor&3").unwrap();
+    if let Err(err) = sessions.validate(&users, &session.token) {
+        println!("{}", err);
+    }
+
+    // 登出一个、全部登出，剩下的到期后清掉
+    let phone = sessions.issue(&users, julian).unwrap();
+    sessions.issue(&users, julian).unwrap();
+    println!("登出: {:?}", sessions.logout(&phone.token));
+    println!("全部登出: {} 个", sessions.logout_all(julian));
+    sessions.issue(&users, julian).unwrap();
+    clock.advance(Duration::from_secs(3600));
+    println!(
+        "清掉 {} 个过期会话，剩 {} 个，空: {}",
+        sessions.purge_expired(),
+        sessions.len(),
+        sessions.is_empty()
+    );
+
+    // 默认使用系统时间
+    let mut sessions = SessionStore::new(Duration::from_secs(60));
+    let session = sessions.issue(&users, julian).unwrap();
+    println!("系统时钟: {:?}", sessions.validate(&users, &session.token));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::{LOCKOUT_DURATION, MAX_FAILED_LOGINS};
+
+    // 已知答案：FIPS 180-2 附录和 RFC 4231、RFC 7914 的测试向量
+    #[test]
+    fn known_answers() {
+        assert_eq!(
+            to_hex(&sha256(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            to_hex(&sha256(b"")),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            to_hex(&sha256(&[b'a'; 1000])),
+            "41edece42d63e8d9bf515a9ba6932e1c20cbc9f5a5d134645adb5db1b9737ea3"
+        );
+        assert_eq!(
+            to_hex(&hmac_sha256(&[0x0b; 20], b"Hi There")),
+            "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7"
+        );
+        let mut key = [0u8; 64];
+        pbkdf2_sha256(b"passwd", b"salt", 1, &mut key);
+        assert_eq!(
+            to_hex(&key),
+            "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc\
+             49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783"
+        );
+        let mut key = [0u8; 32];
+        pbkdf2_sha256(b"password", b"salt", 4096, &mut key);
+        assert_eq!(
+            to_hex(&key),
+            "c5e478d59288c841aa530db6845c4c8d962893a001ce4e11a4963873aa98134a"
+        );
+        assert_eq!(from_hex("00ff10"), Some(vec![0, 255, 16]));
+        assert_eq!(from_hex("0"), None);
+    }
+
+    #[test]
+    fn random_bytes_are_not_repeated() {
+        let (mut a, mut b) = ([0u8; 32], [0u8; 32]);
+        random_bytes(&mut a).unwrap();
+        random_bytes(&mut b).unwrap();
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn hashes_are_salted_and_round_trip() {
+        // 同一个密码，盐不同哈希就不同；字符串形式可以原样读回来
+        let hash = PasswordHash::new("correct horse", 1_000).unwrap();
+        let other = PasswordHash::new("correct horse", 1_000).unwrap();
+        assert_ne!(hash, other);
+        assert!(hash.verify("correct horse") && other.verify("correct horse"));
+        assert!(!hash.verify("correct horsE"));
+        let stored = hash.to_string();
+        assert!(stored.starts_with("pbkdf2-sha256$1000$"));
+        assert_eq!(hash.iterations(), 1_000);
+        assert_eq!(stored.parse::<PasswordHash>(), Ok(hash.clone()));
+        assert!(format!("{:?}", hash).ends_with("1000 rounds)"));
+        assert!("md5$1$00$00".parse::<PasswordHash>().is_err());
+        assert!("pbkdf2-sha256$0$00$00".parse::<PasswordHash>().is_err());
+    }
+
+    #[test]
+    fn weak_passwords_are_rejected() {
+        assert_eq!(
+            validate_password("julianlee", "short"),
+            Err(PasswordError::TooShort { min: 8, found: 5 })
+        );
+        assert_eq!(
+            validate_password("julianlee", "JulianLee"),
+            Err(PasswordError::SameAsUsername)
+        );
+        let (_, mut users, _, julian) = sample_sessions();
+        assert_eq!(
+            users.set_password(julian, "julianlee"),
+            Err(RegistryError::WeakPassword(PasswordError::SameAsUsername))
+        );
+    }
+
+    #[test]
+    fn login_needs_a_password() {
+        let (_, mut users, mut sessions, _) = sample_sessions();
+        users.create("alice", "alice@example.com").unwrap();
+        assert_eq!(
+            sessions.login(&mut users, "alice", "hunter22"),
+            Err(RegistryError::NoPassword("alice".to_string()))
+        );
+        let session = sessions.login(&mut users, "julianlee", "hunter22").unwrap();
+        assert_eq!(session.expires_at, start() + Duration::from_secs(3600));
+        assert_eq!(sessions.validate(&users, &session.token), Ok(session.user));
+        assert_eq!(
+            sessions.validate(&users, "forged"),
+            Err(SessionError::UnknownToken)
+        );
+    }
+
+    #[test]
+    fn repeated_failures_lock_the_account() {
+        let (clock, mut users, mut sessions, julian) = sample_sessions();
+        sessions.login(&mut users, "julianlee", "hunter22").unwrap();
+        for remaining in (1..MAX_FAILED_LOGINS).rev() {
+            assert_eq!(
+                sessions.login(&mut users, "julianlee", "hunter23"),
+                Err(RegistryError::WrongPassword {
+                    username: "julianlee".to_string(),
+                    remaining,
+                })
+            );
+        }
+        assert_eq!(
+            sessions.login(&mut users, "julianlee", "hunter23"),
+            Err(RegistryError::LockedOut {
+                username: "julianlee".to_string(),
+                until: start() + LOCKOUT_DURATION,
+            })
+        );
+        // 锁定期间密码对了也不行
+        assert!(matches!(
+            sessions.login(&mut users, "julianlee", "hunter22"),
+            Err(RegistryError::LockedOut { .. })
+        ));
+        assert_eq!(users.get(julian).unwrap().sign_in_count(), 1);
+        clock.advance(LOCKOUT_DURATION);
+        sessions.login(&mut users, "julianlee", "hunter22").unwrap();
+        let account = users.get(julian).unwrap();
+        assert_eq!((account.sign_in_count(), account.failed_logins), (2, 0));
+        assert_eq!(account.locked_until, None);
+        assert_eq!(sessions.len(), 2);
+    }
+
+    #[test]
+    fn sessions_expire_and_log_out() {
+        let (clock, mut users, mut sessions, julian) = sample_sessions();
+        let first = sessions.login(&mut users, "julianlee", "hunter22").unwrap();
+        clock.advance(Duration::from_secs(1800));
+        let second = sessions.login(&mut users, "julianlee", "hunter22").unwrap();
+        clock.advance(Duration::from_secs(1800));
+        assert_eq!(
+            sessions.validate(&users, &first.token),
+            Err(SessionError::Expired {
+                expired_at: start() + Duration::from_secs(3600)
+            })
+        );
+        assert_eq!(
+            sessions.validate(&users, &first.token),
+            Err(SessionError::UnknownToken)
+        );
+        assert_eq!(sessions.logout(&second.token), Ok(julian));
+        assert_eq!(
+            sessions.logout(&second.token),
+            Err(SessionError::UnknownToken)
+        );
+        sessions.login(&mut users, "julianlee", "hunter22").unwrap();
+        clock.advance(Duration::from_secs(3600));
+        assert_eq!(sessions.purge_expired(), 1);
+        assert!(sessions.is_empty());
+    }
+
+    #[test]
+    fn changing_the_password_revokes_sessions() {
+        let (_, mut users, mut sessions, julian) = sample_sessions();
+        let a = sessions.login(&mut users, "julianlee", "hunter22").unwrap();
+        let b = sessions.issue(&users, julian).unwrap();
+        // 换成同一个密码也会换盐，同样作废
+        users.set_password(julian, "hunter22").unwrap();
+        assert_eq!(
+            sessions.validate(&users, &a.token),
+            Err(SessionError::Revoked(julian))
+        );
+        assert_eq!(
+            sessions.validate(&users, &b.token),
+            Err(SessionError::Revoked(julian))
+        );
+        assert!(sessions.is_empty());
+        let c = sessions.login(&mut users, "julianlee", "hunter22").unwrap();
+        assert_eq!(sessions.validate(&users, &c.token), Ok(julian));
+    }
+
+    #[test]
+    fn deactivating_the_account_revokes_sessions() {
+        let (_, mut users, mut sessions, julian) = sample_sessions();
+        let session = sessions.login(&mut users, "julianlee", "hunter22").unwrap();
+        users.deactivate(julian).unwrap();
+        assert_eq!(
+            sessions.validate(&users, &session.token),
+            Err(SessionError::Revoked(julian))
+        );
+        // 账号被删掉也一样
+        let alice = users.create("alice", "alice@example.com").unwrap();
+        let session = sessions.issue(&users, alice).unwrap();
+        users.remove(alice);
+        assert_eq!(
+            sessions.validate(&users, &session.token),
+            Err(SessionError::Revoked(alice))
+        );
+        assert_eq!(
+            SessionError::Revoked(alice).to_string(),
+            "session of user #2 was revoked"
+        );
+    }
+
+    #[test]
+    fn logout_all_drops_every_session_of_the_user() {
+        let (_, mut users, mut sessions, julian) = sample_sessions();
+        let a = sessions.login(&mut users, "julianlee", "hunter22").unwrap();
+        sessions.login(&mut users, "julianlee", "hunter22").unwrap();
+        let alice = users.create("alice", "alice@example.com").unwrap();
+        sessions.issue(&users, alice).unwrap();
+        assert_eq!(sessions.logout_all(julian), 2);
+        assert_eq!(
+            sessions.validate(&users, &a.token),
+            Err(SessionError::UnknownToken)
+        );
+        assert_eq!(sessions.len(), 1);
+    }
+
+    #[test]
+    fn default_store_uses_the_system_clock() {
+        let (_, users, _, julian) = sample_sessions();
+        let mut sessions = SessionStore::new(Duration::from_secs(60));
+        let session = sessions.issue(&users, julian).unwrap();
+        assert_eq!(sessions.validate(&users, &session.token), Ok(julian));
+    }
+}
@@ -0,0 +1,662 @@
+// 扑克牌
+// main.rs 里有三种牌的写法：PokerSuit、PorkerCard { suit, value: u8 } 和 PokerCard::Clubs(String)，
+// 前两种的点数和后一种的字符串都可能是非法值。这里统一成 Card { suit, rank }：
+//   - 花色沿用 PokerSuit，点数是 Rank 枚举，两个字段都只能取合法值，构造出来的牌一定有效；
+//   - 字符串解析："AS"、"10H"、"th"、"Q♦" 都可以，PokerCard 也能转换过来，非法时返回 ParseCardError；
+//   - Display 用 Unicode 花色符号（A♠），{:#} 输出 ASCII 写法（AS），可以原样解析回来；
+//   - Deck：一副 52 张，可以加大小王，可以多副混在一起；按种子洗牌，种子相同结果就相同；发牌、分手牌。
+use std::fmt;
+use std::str::FromStr;
+
+use crate::PokerCard;
+
+// 花色的先后没有大小之分，比较牌的时候只看点数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PokerSuit {
+    Clubs,
+    Spades,
+    Diamonds,
+    Hearts,
+}
+
+impl PokerSuit {
+    pub const ALL: [PokerSuit; 4] = [
+        PokerSuit::Clubs,
+        PokerSuit::Diamonds,
+        PokerSuit::Hearts,
+        PokerSuit::Spades,
+    ];
+
+    pub fn symbol(self) -> char {
+        match self {
+            PokerSuit::Clubs => '♣',
+            PokerSuit::Spades => '♠',
+            PokerSuit::Diamonds => '♦',
+            PokerSuit::Hearts => '♥',
+        }
+    }
+
+    pub fn letter(self) -> char {
+        match self {
+            PokerSuit::Clubs => 'C',
+            PokerSuit::Spades => 'S',
+            PokerSuit::Diamonds => 'D',
+            PokerSuit::Hearts => 'H',
+        }
+    }
+
+    pub fn is_red(self) -> bool {
+        matches!(self, PokerSuit::Diamonds | PokerSuit::Hearts)
+    }
+
+    // 字母不区分大小写，也认 Unicode 符号（包括空心的 ♤♡♢♧）
+    pub fn from_char(c: char) -> Option<PokerSuit> {
+        match c.to_ascii_uppercase() {
+            'C' | '♣' | '♧' => Some(PokerSuit::Clubs),
+            'S' | '♠' | '♤' => Some(PokerSuit::Spades),
+            'D' | '♦' | '♢' => Some(PokerSuit::Diamonds),
+            'H' | '♥' | '♡' => Some(PokerSuit::Hearts),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for PokerSuit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
+}
+
+// 判别值就是点数，A 算最大
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Rank {
+    Two = 2,
+    Three,
+    Four,
+    Five,
+    Six,
+    Seven,
+    Eight,
+    Nine,
+    Ten,
+    Jack,
+    Queen,
+    King,
+    Ace,
+}
+
+impl Rank {
+    pub const ALL: [Rank; 13] = [
+        Rank::Two,
+        Rank::Three,
+        Rank::Four,
+        Rank::Five,
+        Rank::Six,
+        Rank::Seven,
+        Rank::Eight,
+        Rank::Nine,
+        Rank::Ten,
+        Rank::Jack,
+        Rank::Queen,
+        Rank::King,
+        Rank::Ace,
+    ];
+
+    pub fn value(self) -> u8 {
+        self as u8
+    }
+
+    // 2..=14，A 也可以写成 1
+    pub fn from_value(value: u8) -> Option<Rank> {
+        match value {
+            1 => Some(Rank::Ace),
+            2..=14 => Some(Rank::ALL[value as usize - 2]),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Rank::Two => "2",
+            Rank::Three => "3",
+            Rank::Four => "4",
+            Rank::Five => "5",
+            Rank::Six => "6",
+            Rank::Seven => "7",
+            Rank::Eight => "8",
+            Rank::Nine => "9",
+            Rank::Ten => "10",
+            Rank::Jack => "J",
+            Rank::Queen => "Q",
+            Rank::King => "K",
+            Rank::Ace => "A",
+        }
+    }
+}
+
+impl fmt::Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Rank {
+    type Err = ParseCardError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let rank = match text.to_ascii_uppercase().as_str() {
+            "A" => Rank::Ace,
+            "K" => Rank::King,
+            "Q" => Rank::Queen,
+            "J" => Rank::Jack,
+            "T" => Rank::Ten,
+            // 只认 "2" 到 "10" 原样的写法，"+5"、"07" 这种 parse::<u8> 能读的也不算
+            digits => match (2..=10u8).find(|value| value.to_string() == digits) {
+                Some(value) => Rank::from_value(value).unwrap(),
+                None => return Err(ParseCardError::BadRank(text.to_string())),
+            },
+        };
+        Ok(rank)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseCardError {
+    Empty,
+    BadRank(String),
+    BadSuit(String),
+}
+
+impl fmt::Display for ParseCardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseCardError::Empty => f.write_str("empty card"),
+            ParseCardError::BadRank(rank) => write!(f, "unknown rank {:?}", rank),
+            ParseCardError::BadSuit(suit) => write!(f, "unknown suit {:?}", suit),
+        }
+    }
+}
+
+impl std::error::Error for ParseCardError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Card {
+    pub suit: PokerSuit,
+    pub rank: Rank,
+}
+
+impl Card {
+    pub fn new(rank: Rank, suit: PokerSuit) -> Self {
+        Card { suit, rank }
+    }
+}
+
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "{}{}", self.rank, self.suit.letter())
+        } else {
+            write!(f, "{}{}", self.rank, self.suit.symbol())
+        }
+    }
+}
+
+// 最后一个字符是花色，前面是点数
+impl FromStr for Card {
+    type Err = ParseCardError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        let suit_char = text.chars().last().ok_or(ParseCardError::Empty)?;
+        let rank = &text[..text.len() - suit_char.len_utf8()];
+        let suit = PokerSuit::from_char(suit_char)
+            .ok_or_else(|| ParseCardError::BadSuit(suit_char.to_string()))?;
+        if rank.is_empty() {
+            return Err(ParseCardError::BadRank(String::new()));
+        }
+        Ok(Card::new(rank.parse()?, suit))
+    }
+}
+
+impl TryFrom<&PokerCard> for Card {
+    type Error = ParseCardError;
+
+    fn try_from(card: &PokerCard) -> Result<Self, Self::Error> {
+        let (suit, rank) = match card {
+            PokerCard::Clubs(rank) => (PokerSuit::Clubs, rank),
+            PokerCard::Spades(rank) => (PokerSuit::Spades, rank),
+            PokerCard::Diamonds(rank) => (PokerSuit::Diamonds, rank),
+            PokerCard::Hearts(rank) => (PokerSuit::Hearts, rank),
+        };
+        Ok(Card::new(rank.parse()?, suit))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Joker {
+    Black,
+    Red,
+}
+
+// 牌堆里的一张：普通的牌或者王
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DeckCard {
+    Card(Card),
+    Joker(Joker),
+}
+
+impl DeckCard {
+    pub fn card(self) -> Option<Card> {
+        match self {
+            DeckCard::Card(card) => Some(card),
+            DeckCard::Joker(_) => None,
+        }
+    }
+}
+
+impl From<Card> for DeckCard {
+    fn from(card: Card) -> Self {
+        DeckCard::Card(card)
+    }
+}
+
+// 王显示成扑克牌字符 🃏（小王）、🃟（大王），ASCII 写法是 BJ、RJ
+impl fmt::Display for DeckCard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self, f.alternate()) {
+            (DeckCard::Card(card), _) => card.fmt(f),
+            (DeckCard::Joker(Joker::Black), false) => f.write_str("🃏"),
+            (DeckCard::Joker(Joker::Red), false) => f.write_str("🃟"),
+            (DeckCard::Joker(Joker::Black), true) => f.write_str("BJ"),
+            (DeckCard::Joker(Joker::Red), true) => f.write_str("RJ"),
+        }
+    }
+}
+
+impl FromStr for DeckCard {
+    type Err = ParseCardError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.trim().to_ascii_uppercase().as_str() {
+            "BJ" | "🃏" => Ok(DeckCard::Joker(Joker::Black)),
+            "RJ" | "🃟" => Ok(DeckCard::Joker(Joker::Red)),
+            _ => text.parse().map(DeckCard::Card),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeckError {
+    NoDecks,
+    TooManyJokers(usize),
+    NotEnoughCards { requested: usize, remaining: usize },
+}
+
+impl fmt::Display for DeckError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DeckError::NoDecks => f.write_str("a deck needs at least one pack of cards"),
+            DeckError::TooManyJokers(jokers) => {
+                write!(f, "a pack has at most 2 jokers, asked for {}", jokers)
+            }
+            DeckError::NotEnoughCards {
+                requested,
+                remaining,
+            } => write!(
+                f,
+                "cannot deal {} cards, only {} left",
+                requested, remaining
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DeckError {}
+
+// SplitMix64：够洗牌用，种子相同序列就相同，不能用在需要保密的地方
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    // [0, bound)，拒绝采样去掉取模带来的偏差；bound 为 0 时没有可选的值，直接 panic
+    pub fn below(&mut self, bound: u64) -> u64 {
+        assert!(bound > 0, "Rng::below needs a positive bound");
+        let zone = u64::MAX - u64::MAX % bound;
+        loop {
+            let value = self.next_u64();
+            if value < zone {
+                return value % bound;
+            }
+        }
+    }
+}
+
+// 牌堆顶在 Vec 的末尾，发牌就是 pop
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deck {
+    cards: Vec<DeckCard>,
+}
+
+impl Deck {
+    // 一副 52 张，不含王，按花色、点数排好
+    pub fn standard() -> Self {
+        Deck::new(1, 0).unwrap()
+    }
+
+    // packs 副牌混在一起，每副带 jokers 张王（0、1 或 2，1 张时是小王）
+    pub fn new(packs: usize, jokers: usize) -> Result<Self, DeckError> {
+        if packs == 0 {
+            return Err(DeckError::NoDecks);
+        }
+        if jokers > 2 {
+            return Err(DeckError::TooManyJokers(jokers));
+        }
+        let mut cards = Vec::with_capacity(packs * (52 + jokers));
+        for _ in 0..packs {
+            for suit in PokerSuit::ALL {
+                for rank in Rank::ALL {
+                    cards.push(DeckCard::Card(Card::new(rank, suit)));
+                }
+            }
+            cards.extend(
+                [Joker::Black, Joker::Red][..jokers]
+                    .iter()
+                    .map(|joker| DeckCard::Joker(*joker)),
+            );
+        }
+        // 翻过来，第一张在堆顶
+        cards.reverse();
+        Ok(Deck { cards })
+    }
+
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+
+    // 从堆顶往下
+    pub fn iter(&self) -> impl Iterator<Item = &DeckCard> {
+        self.cards.iter().rev()
+    }
+
+    // Fisher-Yates
+    pub fn shuffle(&mut self, seed: u64) {
+        let mut rng = Rng::new(seed);
+        for i in (1..self.cards.len()).rev() {
+            let j = rng.below(i as u64 + 1) as usize;
+            self.cards.swap(i, j);
+        }
+    }
+
+    pub fn draw(&mut self) -> Option<DeckCard> {
+        self.cards.pop()
+    }
+
+    // 不够时一张都不发
+    pub fn deal(&mut self, count: usize) -> Result<Vec<DeckCard>, DeckError> {
+        if count > self.cards.len() {
+            return Err(DeckError::NotEnoughCards {
+                requested: count,
+                remaining: self.cards.len(),
+            });
+        }
+        let split = self.cards.len() - count;
+        let mut dealt = self.cards.split_off(split);
+        dealt.reverse();
+        Ok(dealt)
+    }
+
+    // 像真人发牌一样一人一张轮流发
+    pub fn deal_hands(
+        &mut self,
+        players: usize,
+        cards_each: usize,
+    ) -> Result<Vec<Vec<DeckCard>>, DeckError> {
+        let dealt = self.deal(players * cards_each)?;
+        let mut hands = vec![Vec::with_capacity(cards_each); players];
+        for (i, card) in dealt.into_iter().enumerate() {
+            hands[i % players].push(card);
+        }
+        Ok(hands)
+    }
+}
+
+pub(crate) fn card() {
+    // 解析和显示：显示用花色符号，{:#} 用 ASCII 写法
+    for text in ["AS", "10H", "th", " Q♦ ", "K♡"] {
+        let card: Card = text.parse().unwrap();
+        println!(
+            "{:?} -> {} / {:#}，点数 {}，红色: {}",
+            text,
+            card,
+            card,
+            card.rank.value(),
+            card.suit.is_red()
+        );
+    }
+    for text in ["1S", "+5H", "07C", "AX"] {
+        if let Err(err) = text.parse::<Card>() {
+            println!("{:?}: {}", text, err);
+        }
+    }
+    println!(
+        "老写法: {:?}",
+        Card::try_from(&PokerCard::Spades(String::from("10")))
+    );
+
+    // 洗牌、发牌
+    let mut deck = Deck::new(1, 2).unwrap();
+    deck.shuffle(42);
+    println!("{} 张牌，洗完堆顶是 {:?}", deck.len(), deck.draw());
+    let hands = deck.deal_hands(2, 5).unwrap();
+    for hand in &hands {
+        let hand: Vec<String> = hand.iter().map(DeckCard::to_string).collect();
+        println!("{}", hand.join(" "));
+    }
+    println!(
+        "再发 3 张 {:?}，还剩 {}，空: {}",
+        deck.deal(3).map(|cards| cards.len()),
+        deck.len(),
+        deck.is_empty()
+    );
+    if let Err(err) = Deck::new(1, 3) {
+        println!("{}", err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{HashMap, HashSet};
+
+    #[test]
+    fn cards_parse_and_display() {
+        let ace: Card = "AS".parse().unwrap();
+        assert_eq!(ace, Card::new(Rank::Ace, PokerSuit::Spades));
+        assert_eq!(ace.to_string(), "A♠");
+        assert_eq!(format!("{:#}", ace), "AS");
+        for (text, expected) in [
+            ("10H", "10♥"),
+            ("th", "10♥"),
+            ("2c", "2♣"),
+            (" Q♦ ", "Q♦"),
+            ("K♡", "K♥"),
+        ] {
+            assert_eq!(text.parse::<Card>().unwrap().to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn bad_cards_are_rejected() {
+        for (text, err) in [
+            ("", ParseCardError::Empty),
+            ("A", ParseCardError::BadSuit("A".to_string())),
+            ("S", ParseCardError::BadRank(String::new())),
+            ("1S", ParseCardError::BadRank("1".to_string())),
+            ("11H", ParseCardError::BadRank("11".to_string())),
+            ("AX", ParseCardError::BadSuit("X".to_string())),
+        ] {
+            assert_eq!(text.parse::<Card>(), Err(err), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn numeric_ranks_must_be_written_exactly() {
+        for text in ["+5", "05", "010", "+10", " 7", "7 "] {
+            assert_eq!(
+                text.parse::<Rank>(),
+                Err(ParseCardError::BadRank(text.to_string())),
+                "{:?}",
+                text
+            );
+        }
+        for value in 2..=10u8 {
+            assert_eq!(
+                value.to_string().parse::<Rank>(),
+                Ok(Rank::from_value(value).unwrap())
+            );
+        }
+        assert_eq!(
+            "+5H".parse::<Card>(),
+            Err(ParseCardError::BadRank("+5".to_string()))
+        );
+    }
+
+    #[test]
+    fn ranks_and_suits() {
+        assert_eq!(Rank::from_value(1), Some(Rank::Ace));
+        assert_eq!(Rank::from_value(15), None);
+        assert_eq!(Rank::Ten.value(), 10);
+        assert!(Rank::Ace > Rank::King && Rank::Two < Rank::Three);
+        assert!(PokerSuit::Hearts.is_red() && !PokerSuit::Clubs.is_red());
+    }
+
+    #[test]
+    fn old_poker_cards_convert() {
+        assert_eq!(
+            Card::try_from(&PokerCard::Spades(String::from("10"))),
+            Ok(Card::new(Rank::Ten, PokerSuit::Spades))
+        );
+        assert_eq!(
+            Card::try_from(&PokerCard::Clubs(String::from("Z"))),
+            Err(ParseCardError::BadRank("Z".to_string()))
+        );
+    }
+
+    #[test]
+    fn standard_deck_round_trips() {
+        // 52 张各不相同，两种写法都能解析回来
+        let deck = Deck::standard();
+        assert_eq!(deck.len(), 52);
+        let unique: HashSet<_> = deck.iter().collect();
+        assert_eq!(unique.len(), 52);
+        for card in deck.iter() {
+            assert_eq!(format!("{:#}", card).parse::<DeckCard>(), Ok(*card));
+            assert_eq!(card.to_string().parse::<DeckCard>(), Ok(*card));
+        }
+        assert_eq!(deck.iter().next().unwrap().to_string(), "2♣");
+    }
+
+    #[test]
+    fn jokers_and_multiple_packs() {
+        let deck = Deck::new(2, 2).unwrap();
+        assert_eq!(deck.len(), 108);
+        let jokers = deck.iter().filter(|card| card.card().is_none()).count();
+        assert_eq!(jokers, 4);
+        let red = DeckCard::Joker(Joker::Red);
+        assert_eq!(
+            (red.to_string(), format!("{:#}", red)),
+            ("🃟".to_string(), "RJ".to_string())
+        );
+        assert_eq!("bj".parse(), Ok(DeckCard::Joker(Joker::Black)));
+        assert_eq!(Deck::new(0, 0), Err(DeckError::NoDecks));
+        assert_eq!(Deck::new(1, 3), Err(DeckError::TooManyJokers(3)));
+    }
+
+    #[test]
+    fn shuffle_is_seeded_and_keeps_the_cards() {
+        let mut a = Deck::standard();
+        let mut b = Deck::standard();
+        a.shuffle(42);
+        b.shuffle(42);
+        assert_eq!(a, b);
+        b.shuffle(7);
+        assert_ne!(a, b);
+        assert_ne!(a, Deck::standard());
+        let mut sorted: Vec<String> = a.iter().map(|card| format!("{:#}", card)).collect();
+        let mut original: Vec<String> = Deck::standard()
+            .iter()
+            .map(|card| format!("{:#}", card))
+            .collect();
+        sorted.sort();
+        original.sort();
+        assert_eq!(sorted, original);
+    }
+
+    #[test]
+    fn shuffle_is_roughly_uniform() {
+        // 大量洗牌时每张牌出现在堆顶的次数应该差不多
+        let mut top = HashMap::new();
+        for seed in 0..5200 {
+            let mut deck = Deck::standard();
+            deck.shuffle(seed);
+            *top.entry(deck.draw().unwrap()).or_insert(0) += 1;
+        }
+        assert_eq!(top.len(), 52);
+        assert!(top.values().all(|count| (50..=150).contains(count)));
+    }
+
+    #[test]
+    fn dealing_takes_from_the_top() {
+        let mut deck = Deck::standard();
+        let first = deck.iter().take(3).copied().collect::<Vec<_>>();
+        assert_eq!(deck.deal(3).unwrap(), first);
+        let hands = deck.deal_hands(4, 5).unwrap();
+        assert_eq!(hands.len(), 4);
+        assert!(hands.iter().all(|hand| hand.len() == 5));
+        assert_eq!(hands[0][0].to_string(), "5♣");
+        assert_eq!(hands[1][0].to_string(), "6♣");
+        assert_eq!(deck.len(), 29);
+        // 不够发时一张都不动
+        assert_eq!(
+            deck.deal(30),
+            Err(DeckError::NotEnoughCards {
+                requested: 30,
+                remaining: 29
+            })
+        );
+        assert_eq!(deck.len(), 29);
+        let rest = deck.deal(29).unwrap();
+        assert_eq!(rest.last().unwrap().to_string(), "A♠");
+        assert!(deck.is_empty() && deck.draw().is_none());
+    }
+
+    #[test]
+    fn below_stays_in_range() {
+        let mut rng = Rng::new(1);
+        assert!((0..1000).all(|_| rng.below(3) < 3));
+        assert_eq!(rng.below(1), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "positive bound")]
+    fn below_zero_panics() {
+        Rng::new(1).below(0);
+    }
+}
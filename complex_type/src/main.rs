@@ -1,6 +1,37 @@
+mod auth;
+mod card;
+mod poker;
+mod registry;
+mod storage;
+
 use std::net::TcpStream;
 
+use card::PokerSuit;
+
 fn main() {
+    // cargo run -- compact <目录> 压缩用户数据目录，见 storage.rs
+    if std::env::args().nth(1).as_deref() == Some("compact") {
+        let args: Vec<String> = std::env::args().skip(2).collect();
+        storage::compact_main(&args);
+        return;
+    }
+    // cargo run -- inspect <目录> 打开用户数据目录，输出恢复情况和每个用户，见 storage.rs
+    if std::env::args().nth(1).as_deref() == Some("inspect") {
+        let args: Vec<String> = std::env::args().skip(2).collect();
+        storage::inspect_main(&args);
+        return;
+    }
+    // cargo run --release -- poker 枚举所有 5 张、7 张的组合，对照各牌型的数量，见 poker.rs
+    if std::env::args().nth(1).as_deref() == Some("poker") {
+        poker::poker_main();
+        return;
+    }
+    // tests/storage_crash.rs 用来模拟进程被杀的子进程入口，见 storage.rs
+    if std::env::args().nth(1).as_deref() == Some(storage::CRASH_COMMAND) {
+        let args: Vec<String> = std::env::args().skip(2).collect();
+        storage::crash_main(&args);
+        return;
+    }
     //    字符串
     {
         // 字符串字面量是切片
@@ -73,6 +104,7 @@ fn main() {
             active: true,
             sign_in_count: 10,
             username: String::from("julianlee"),
+            password: None,
         };
         // 通过 . 操作符即可访问结构体实例内部的字段值，也可以修改它们
         println!("{}", user1.active);
@@ -172,6 +204,16 @@ fn main() {
         // [u8; 3]和[u8; 4]是不同的类型，数组的长度也是类型的一部分
         // 在实际开发中，使用最多的是数组切片[T]，我们往往通过引用的方式去使用&[T]，因为后者有固定的类型大小
     }
+    // 用户注册表：创建、查找、修改、停用和登录，见 registry.rs
+    registry::registry();
+    // 密码和登录会话，见 auth.rs
+    auth::auth();
+    // 用户数据持久化：日志、快照和崩溃恢复，见 storage.rs
+    storage::storage();
+    // 扑克牌和牌堆：解析、显示、洗牌、发牌，见 card.rs
+    card::card();
+    // 牌型判断和比牌，见 poker.rs
+    poker::poker();
 }
 
 fn greet(name: String) {
@@ -187,11 +229,14 @@ fn return_multiple_value(s: String) -> (String, usize) {
     (s, len)
 }
 
+#[derive(Debug, Clone, PartialEq)]
 struct User {
     active: bool,
     username: String,
     email: String,
     sign_in_count: u64,
+    // 加盐、多轮迭代后的密码哈希，没设置密码时为 None，见 auth.rs
+    password: Option<auth::PasswordHash>,
 }
 // 当函数参数和结构体字段同名时，可以直接使用缩略的方式进行初始化，跟 TypeScript 中一模一样。
 fn build_user_like_typescript(
@@ -205,6 +250,7 @@ fn build_user_like_typescript(
         username,
         email,
         sign_in_count,
+        password: None,
     };
 }
 fn build_user_in_a_dum_way(
@@ -218,6 +264,7 @@ fn build_user_in_a_dum_way(
         username: username,
         sign_in_count: sign_in_count,
         email: email,
+        password: None,
     }
 }
 // Rust 默认不会为我们实现 Debug，为了实现，有两种方式可以选择：
@@ -227,20 +274,13 @@ fn build_user_in_a_dum_way(
 // 还有一个简单的输出 debug 信息的方法，那就是使用 dbg! 宏，它会拿走表达式的所有权，
 // 然后打印出相应的文件名、行号等 debug 信息，当然还有我们需要的表达式的求值结果。除此之外，它最终还会把表达式值的所有权返回！
 // dbg! 输出到标准错误输出 stderr，而 println! 输出到标准输出 stdout
-#[derive(Debug)]
-enum PokerSuit {
-    Clubs,
-    Spades,
-    Diamonds,
-    Hearts,
-}
+// PokerSuit 的定义（同样是 #[derive(Debug)]）见 card.rs
 
-struct PorkerCard {
-    suit: PokerSuit,
-    value: u8,
-}
+// 用 u8 表示点数的 PorkerCard { suit, value } 可以装下 0、200 这样的非法点数，
+// 已经和下面的写法一起统一成 card.rs 里的 Card { suit, rank }
 
 // 更好的方式
+// 点数用字符串同样可能非法，可以用 Card::try_from(&card) 转成校验过的 Card
 enum PokerCard {
     Clubs(String),
     Spades(String),
@@ -0,0 +1,472 @@
+// 德州扑克牌型
+// 在 card.rs 的 Card 之上判断 5 到 7 张牌能组成的最大牌型，并比较两手牌的大小。
+// 不去枚举 7 选 5 的 21 种组合，而是一次扫描：
+//   - 每个花色一个点数位图，某个花色够 5 张就是同花，再看这个花色里有没有顺子（同花顺）；
+//   - 所有点数的位图找顺子，A 同时当 1 用，A2345 是最小的顺子（wheel）；
+//   - 数每个点数的张数，按四条、葫芦、三条、两对、一对、高牌依次判断。
+// 结果打包成一个 u32 的 HandValue：高位是牌型，下面每 4 位一个比较用的点数，直接比较大小就是比牌，
+// 踢脚（kicker）和 wheel 都包含在里面，值相等就是平局。
+// cargo run --release -- poker 把 52 张牌里所有 5 张、7 张的组合都算一遍，和已知的各牌型数量对照，
+// 对不上时以非零状态退出；同样的检查也是一个默认忽略的测试：cargo test --release -- --ignored。
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fmt;
+use std::time::Instant;
+
+use crate::card::{Card, Deck, ParseCardError, Rank};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Category {
+    HighCard,
+    OnePair,
+    TwoPair,
+    ThreeOfAKind,
+    Straight,
+    Flush,
+    FullHouse,
+    FourOfAKind,
+    StraightFlush,
+    RoyalFlush,
+}
+
+impl Category {
+    pub const ALL: [Category; 10] = [
+        Category::HighCard,
+        Category::OnePair,
+        Category::TwoPair,
+        Category::ThreeOfAKind,
+        Category::Straight,
+        Category::Flush,
+        Category::FullHouse,
+        Category::FourOfAKind,
+        Category::StraightFlush,
+        Category::RoyalFlush,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Category::HighCard => "high card",
+            Category::OnePair => "one pair",
+            Category::TwoPair => "two pair",
+            Category::ThreeOfAKind => "three of a kind",
+            Category::Straight => "straight",
+            Category::Flush => "flush",
+            Category::FullHouse => "full house",
+            Category::FourOfAKind => "four of a kind",
+            Category::StraightFlush => "straight flush",
+            Category::RoyalFlush => "royal flush",
+        }
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+// 牌型 << 20 | 第一比较点数 << 16 | ... | 第五比较点数
+// 比较点数按重要性排列，例如葫芦是 [三条的点数, 对子的点数]，顺子只有最大的那张（wheel 是 5）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HandValue(u32);
+
+impl HandValue {
+    pub fn category(self) -> Category {
+        Category::ALL[(self.0 >> 20) as usize]
+    }
+
+    pub fn ranks(self) -> Vec<Rank> {
+        (0..5)
+            .map(|i| ((self.0 >> (16 - 4 * i)) & 0xf) as u8)
+            .take_while(|value| *value != 0)
+            .map(|value| Rank::from_value(value).unwrap())
+            .collect()
+    }
+}
+
+impl fmt::Display for HandValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ranks: Vec<&str> = self.ranks().into_iter().map(Rank::as_str).collect();
+        write!(f, "{} ({})", self.category(), ranks.join(" "))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HandError {
+    WrongSize(usize),
+    Duplicate(Card),
+    Parse(ParseCardError),
+}
+
+impl fmt::Display for HandError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HandError::WrongSize(found) => {
+                write!(f, "a hand needs 5 to 7 cards, found {}", found)
+            }
+            HandError::Duplicate(card) => write!(f, "{} appears more than once", card),
+            HandError::Parse(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for HandError {}
+
+impl From<ParseCardError> for HandError {
+    fn from(err: ParseCardError) -> Self {
+        HandError::Parse(err)
+    }
+}
+
+// 空格分隔，例如 "AS KS QS JS 10S"
+pub fn parse_hand(text: &str) -> Result<Vec<Card>, HandError> {
+    Ok(text
+        .split_whitespace()
+        .map(str::parse)
+        .collect::<Result<_, _>>()?)
+}
+
+pub fn evaluate(cards: &[Card]) -> Result<HandValue, HandError> {
+    if !(5..=7).contains(&cards.len()) {
+        return Err(HandError::WrongSize(cards.len()));
+    }
+    let mut seen = HashSet::new();
+    if let Some(card) = cards.iter().find(|card| !seen.insert(**card)) {
+        return Err(HandError::Duplicate(*card));
+    }
+    Ok(evaluate_unchecked(cards))
+}
+
+// Greater 表示 a 大
+pub fn compare(a: &[Card], b: &[Card]) -> Result<Ordering, HandError> {
+    Ok(evaluate(a)?.cmp(&evaluate(b)?))
+}
+
+// 顺子最大的那张，位图里第 r 位表示有点数 r 的牌
+fn straight_high(mask: u16) -> Option<u8> {
+    // A 同时当 1
+    let mask = mask | ((mask >> 14) & 1) << 1;
+    // 第 r 位为 1 表示 r 到 r+4 都有
+    let runs = mask & mask >> 1 & mask >> 2 & mask >> 3 & mask >> 4;
+    if runs == 0 {
+        None
+    } else {
+        Some(highest(runs) + 4)
+    }
+}
+
+// 位图里最大的点数，位图不能为空
+fn highest(mask: u16) -> u8 {
+    15 - mask.leading_zeros() as u8
+}
+
+fn bit(rank: u8) -> u16 {
+    1 << rank
+}
+
+// 先放 fixed 里的点数，再从 rest 里从大到小取 take 个做踢脚
+fn pack(category: Category, fixed: &[u8], mut rest: u16, take: usize) -> HandValue {
+    let mut value = (category as u32) << 20;
+    let mut shift = 16;
+    for rank in fixed {
+        value |= (*rank as u32) << shift;
+        shift -= 4;
+    }
+    for _ in 0..take {
+        let rank = highest(rest);
+        rest &= !bit(rank);
+        value |= (rank as u32) << shift;
+        shift -= 4;
+    }
+    HandValue(value)
+}
+
+// 调用方保证 5 到 7 张且没有重复；整个过程不分配内存，枚举上亿手牌也够快
+pub fn evaluate_unchecked(cards: &[Card]) -> HandValue {
+    let mut suits = [0u16; 4];
+    let mut counts = [0u8; 15];
+    for card in cards {
+        let rank = card.rank.value();
+        suits[card.suit as usize] |= bit(rank);
+        counts[rank as usize] += 1;
+    }
+
+    // 7 张牌里最多只能有一个花色够 5 张，同花（顺）一定比四条、葫芦以外的牌型大，
+    // 而有同花的时候不可能同时有四条或葫芦（7 张里 5 张同花，剩下 2 张凑不出）
+    if let Some(&flush) = suits.iter().find(|mask| mask.count_ones() >= 5) {
+        return match straight_high(flush) {
+            Some(14) => pack(Category::RoyalFlush, &[14], 0, 0),
+            Some(high) => pack(Category::StraightFlush, &[high], 0, 0),
+            None => pack(Category::Flush, &[], flush, 5),
+        };
+    }
+
+    // by_count[n] 是正好有 n 张的点数位图
+    let mut by_count = [0u16; 5];
+    for rank in 2..=14u8 {
+        by_count[counts[rank as usize] as usize] |= bit(rank);
+    }
+    let all = by_count[1] | by_count[2] | by_count[3] | by_count[4];
+
+    if by_count[4] != 0 {
+        let quads = highest(by_count[4]);
+        return pack(Category::FourOfAKind, &[quads], all & !bit(quads), 1);
+    }
+    if by_count[3] != 0 {
+        let three = highest(by_count[3]);
+        // 两个三条时小的那个当对子用
+        let pair_mask = (by_count[3] & !bit(three)) | by_count[2];
+        if pair_mask != 0 {
+            return pack(Category::FullHouse, &[three, highest(pair_mask)], 0, 0);
+        }
+    }
+    if let Some(high) = straight_high(all) {
+        return pack(Category::Straight, &[high], 0, 0);
+    }
+    if by_count[3] != 0 {
+        let three = highest(by_count[3]);
+        return pack(Category::ThreeOfAKind, &[three], all & !bit(three), 2);
+    }
+    if by_count[2].count_ones() >= 2 {
+        let first = highest(by_count[2]);
+        let second = highest(by_count[2] & !bit(first));
+        // 三个对子时第三个对子可以当踢脚
+        let rest = all & !bit(first) & !bit(second);
+        return pack(Category::TwoPair, &[first, second], rest, 1);
+    }
+    if by_count[2] != 0 {
+        let pair = highest(by_count[2]);
+        return pack(Category::OnePair, &[pair], all & !bit(pair), 3);
+    }
+    pack(Category::HighCard, &[], all, 5)
+}
+
+// 52 张里取 size 张的所有组合，按牌型计数
+pub fn count_all(size: usize) -> [u64; 10] {
+    let deck: Vec<Card> = Deck::standard()
+        .iter()
+        .filter_map(|card| card.card())
+        .collect();
+    let mut counts = [0u64; 10];
+    let mut index: Vec<usize> = (0..size).collect();
+    let mut hand: Vec<Card> = index.iter().map(|&i| deck[i]).collect();
+    loop {
+        counts[evaluate_unchecked(&hand).category() as usize] += 1;
+        // 找到最右边还能往后挪的位置，它后面的依次紧挨着
+        let mut i = size;
+        while i > 0 && index[i - 1] == deck.len() - size + i - 1 {
+            i -= 1;
+        }
+        if i == 0 {
+            return counts;
+        }
+        index[i - 1] += 1;
+        for j in i..size {
+            index[j] = index[j - 1] + 1;
+        }
+        for j in i - 1..size {
+            hand[j] = deck[index[j]];
+        }
+    }
+}
+
+// 已知的各牌型组合数，顺序同 Category::ALL
+pub const FIVE_CARD_COUNTS: [u64; 10] = [
+    1_302_540, 1_098_240, 123_552, 54_912, 10_200, 5_108, 3_744, 624, 36, 4,
+];
+pub const SEVEN_CARD_COUNTS: [u64; 10] = [
+    23_294_460, 58_627_800, 31_433_400, 6_461_620, 6_180_020, 4_047_644, 3_473_184, 224_848,
+    37_260, 4_324,
+];
+
+// cargo run --release -- poker
+pub(crate) fn poker_main() {
+    if cfg!(debug_assertions) {
+        println!("warning: debug build, run with --release to enumerate 7-card hands quickly");
+    }
+    let mut mismatches = 0;
+    for (size, expected) in [(5, FIVE_CARD_COUNTS), (7, SEVEN_CARD_COUNTS)] {
+        let start = Instant::now();
+        let counts = count_all(size);
+        let elapsed = start.elapsed();
+        let total: u64 = counts.iter().sum();
+        println!(
+            "{}-card hands: {} in {:.2?} ({:.0} hands/s)",
+            size,
+            total,
+            elapsed,
+            total as f64 / elapsed.as_secs_f64()
+        );
+        for (category, (count, expected)) in Category::ALL.iter().zip(counts.iter().zip(expected)) {
+            let mark = if *count == expected {
+                "ok"
+            } else {
+                mismatches += 1;
+                "MISMATCH"
+            };
+            println!("  {:<16}{:>12}  {}", category.name(), count, mark);
+        }
+    }
+    if mismatches > 0 {
+        eprintln!(
+            "error: {} categories do not match the known counts",
+            mismatches
+        );
+        std::process::exit(1);
+    }
+}
+
+pub(crate) fn poker() {
+    // 两个人共用公共牌，各自取最好的 5 张
+    let board = "KH 9C 7D 4S 2H";
+    for (name, hole) in [("alice", "KC QS"), ("bob", "KD JS"), ("carol", "9S 9D")] {
+        let hand = parse_hand(&format!("{} {}", board, hole)).unwrap();
+        let value = evaluate(&hand).unwrap();
+        println!("{:<6}{}  {}，比牌用 {:?}", name, hole, value, value.ranks());
+    }
+    let alice = parse_hand(&format!("{} KC QS", board)).unwrap();
+    let bob = parse_hand(&format!("{} KD JS", board)).unwrap();
+    println!("alice 对 bob: {:?}", compare(&alice, &bob));
+
+    // wheel：A 当 1，是最小的顺子
+    let wheel = evaluate(&parse_hand("AS 2D 3H 4C 5S").unwrap()).unwrap();
+    println!("{} 是 {:?}", wheel, wheel.category());
+    for text in ["AS KS QS JS", "AS KS QS JS AS", "AS KS 1S"] {
+        if let Err(err) = parse_hand(text).and_then(|hand| evaluate(&hand)) {
+            println!("{:?}: {}", text, err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(text: &str) -> HandValue {
+        evaluate(&parse_hand(text).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn every_category() {
+        for (text, category) in [
+            ("AS KS QS JS 10S", Category::RoyalFlush),
+            ("9H 8H 7H 6H 5H", Category::StraightFlush),
+            ("7C 7D 7H 7S 2C", Category::FourOfAKind),
+            ("KC KD KH 2S 2C", Category::FullHouse),
+            ("AD 9D 7D 4D 2D", Category::Flush),
+            ("10C 9D 8H 7S 6C", Category::Straight),
+            ("QC QD QH 9S 2C", Category::ThreeOfAKind),
+            ("JC JD 4H 4S AC", Category::TwoPair),
+            ("10C 10D 8H 4S 2C", Category::OnePair),
+            ("AC JD 8H 4S 2C", Category::HighCard),
+        ] {
+            assert_eq!(value(text).category(), category, "{}", text);
+        }
+    }
+
+    #[test]
+    fn wheel_is_the_lowest_straight() {
+        // A 当 1，是最小的顺子和同花顺
+        let wheel = value("AS 2D 3H 4C 5S");
+        assert_eq!(wheel.category(), Category::Straight);
+        assert_eq!(wheel.ranks(), [Rank::Five]);
+        assert!(wheel < value("2S 3D 4H 5C 6S"));
+        assert!(value("AH 2H 3H 4H 5H") < value("2C 3C 4C 5C 6C"));
+        // 不能绕过 A
+        assert_eq!(value("QS KD AH 2C 3S").category(), Category::HighCard);
+    }
+
+    #[test]
+    fn kickers_break_ties() {
+        assert!(value("AC AD KH 7S 3C") > value("AH AS QH JS 10C"));
+        assert!(value("AC AD KH 7S 4C") > value("AH AS KD 7C 3S"));
+        assert!(value("8C 8D 3H 3S KC") > value("8H 8S 3C 3D QS"));
+        assert!(value("KC KD KH 2S 2C") > value("QC QD QH AS AC"));
+        assert!(value("7C 7D 7H 7S AC") > value("7C 7D 7H 7S KC"));
+        assert!(value("AD 9D 7D 4D 3D") > value("AC 9C 7C 4C 2C"));
+        // 花色不分大小，完全一样就是平局
+        assert_eq!(
+            compare(
+                &parse_hand("AC KD 8H 4S 2C").unwrap(),
+                &parse_hand("AD KH 8S 4C 2D").unwrap()
+            ),
+            Ok(Ordering::Equal)
+        );
+    }
+
+    #[test]
+    fn seven_cards_use_the_best_five() {
+        assert_eq!(
+            value("AS KS 2H 2D 2C KD 9S").to_string(),
+            "full house (2 K)"
+        );
+        // 两个三条：小的当对子
+        assert_eq!(
+            value("9S 9D 9H 4C 4S 4D AH").ranks(),
+            [Rank::Nine, Rank::Four]
+        );
+        // 三个对子：第三个对子比单张大时当踢脚
+        assert_eq!(
+            value("QS QD 8H 8C 6S 6D 2H").ranks(),
+            [Rank::Queen, Rank::Eight, Rank::Six]
+        );
+        // 四条的踢脚可以来自对子
+        assert_eq!(
+            value("5S 5D 5H 5C KS KD 2H").ranks(),
+            [Rank::Five, Rank::King]
+        );
+        // 同花和顺子同时存在但不是同花顺，同花更大
+        assert_eq!(value("4H 5H 6H 7H 8C 9H").category(), Category::Flush);
+        assert_eq!(
+            value("4H 5H 6H 7H 8C JH 8H").category(),
+            Category::StraightFlush
+        );
+        assert_eq!(value("AH 2C 3H 4H 5H 9D 6S").ranks(), [Rank::Six]);
+    }
+
+    #[test]
+    fn shared_board_splits_equal_hands() {
+        let board = "KH 9C 7D 4S 2H";
+        let alice = value(&format!("{} KC QS", board));
+        let bob = value(&format!("{} KD JS", board));
+        assert!(alice > bob);
+        assert_eq!(alice.to_string(), "one pair (K Q 9 7)");
+        // 两个人的对子都比公共牌小，一样大则平分
+        assert_eq!(
+            value(&format!("{} 3C 3D", board)),
+            value(&format!("{} 3H 3S", board))
+        );
+    }
+
+    #[test]
+    fn bad_hands_are_rejected() {
+        assert_eq!(
+            evaluate(&parse_hand("AS KS QS JS").unwrap()),
+            Err(HandError::WrongSize(4))
+        );
+        assert_eq!(
+            evaluate(&parse_hand("AS KS QS JS AS").unwrap()),
+            Err(HandError::Duplicate("AS".parse().unwrap()))
+        );
+        assert_eq!(
+            parse_hand("AS KS 1S"),
+            Err(HandError::Parse(ParseCardError::BadRank("1".to_string())))
+        );
+    }
+
+    #[test]
+    fn five_card_counts() {
+        let counts = count_all(5);
+        assert_eq!(counts, FIVE_CARD_COUNTS);
+        assert_eq!(counts.iter().sum::<u64>(), 2_598_960);
+    }
+
+    // 1.3 亿手牌，debug 构建太慢：cargo test --release -- --ignored
+    #[test]
+    #[ignore]
+    fn seven_card_counts() {
+        let counts = count_all(7);
+        assert_eq!(counts, SEVEN_CARD_COUNTS);
+        assert_eq!(counts.iter().sum::<u64>(), 133_784_560);
+    }
+}
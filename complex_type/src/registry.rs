@@ -0,0 +1,842 @@
+// 用户注册表
+// main.rs 里的 User 只是一个值，这里把它管起来：
+//   - create 创建用户，用户名和邮箱都必须唯一（邮箱不区分大小写），邮箱要通过格式校验；
+//   - 按 id、用户名、邮箱查找；
+//   - update 修改用户名或邮箱，同样要满足唯一性和格式要求；
+//   - deactivate 停用用户，停用后不能再登录，但用户名和邮箱仍然被占用；
+//   - set_password 设置密码（见 auth.rs），sign_in 校验密码，成功时 sign_in_count 加一并记录登录时间，
+//     连续输错 MAX_FAILED_LOGINS 次锁定 LOCKOUT_DURATION。
+// 每一种失败都对应 RegistryError 的一个成员，调用方可以按成员分别处理。
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::rc::Rc;
+use std::time::{Duration, SystemTime};
+
+use crate::auth::{self, PasswordError, PasswordHash};
+use crate::User;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UserId(pub u64);
+
+impl fmt::Display for UserId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+// 时间来源，演示里换成手动拨动的时钟
+pub trait Clock {
+    fn now(&self) -> SystemTime;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+// 克隆出来的时钟共享同一个时间
+#[derive(Clone)]
+pub struct ManualClock {
+    now: Rc<Cell<SystemTime>>,
+}
+
+impl ManualClock {
+    pub fn new(start: SystemTime) -> Self {
+        ManualClock {
+            now: Rc::new(Cell::new(start)),
+        }
+    }
+    pub fn advance(&self, by: Duration) {
+        self.now.set(self.now.get() + by);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> SystemTime {
+        self.now.get()
+    }
+}
+
+// 错误信息里的时间用 UNIX 秒数表示
+pub fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+// 邮箱格式错误的具体原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EmailError {
+    Empty,
+    MissingAt,
+    MultipleAt,
+    EmptyLocalPart,
+    BadLocalPart,
+    BadDomain,
+    BadTopLevelDomain,
+}
+
+impl fmt::Display for EmailError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match self {
+            EmailError::Empty => "email is empty",
+            EmailError::MissingAt => "missing '@'",
+            EmailError::MultipleAt => "more than one '@'",
+            EmailError::EmptyLocalPart => "nothing before '@'",
+            EmailError::BadLocalPart => "invalid character or dot placement before '@'",
+            EmailError::BadDomain => "invalid domain",
+            EmailError::BadTopLevelDomain => "top-level domain must be at least two letters",
+        };
+        f.write_str(reason)
+    }
+}
+
+// 用户名格式错误的具体原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UsernameError {
+    Length {
+        min: usize,
+        max: usize,
+        found: usize,
+    },
+    BadCharacter(char),
+    BadStart,
+}
+
+impl fmt::Display for UsernameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UsernameError::Length { min, max, found } => write!(
+                f,
+                "must be {} to {} characters long, found {}",
+                min, max, found
+            ),
+            UsernameError::BadCharacter(c) => write!(f, "character {:?} is not allowed", c),
+            UsernameError::BadStart => f.write_str("must start with a letter"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistryError {
+    InvalidUsername {
+        username: String,
+        reason: UsernameError,
+    },
+    InvalidEmail {
+        email: String,
+        reason: EmailError,
+    },
+    UsernameTaken(String),
+    EmailTaken(String),
+    NotFound(UserId),
+    UnknownUsername(String),
+    UnknownEmail(String),
+    Inactive(String),
+    AlreadyInactive(String),
+    WeakPassword(PasswordError),
+    NoPassword(String),
+    WrongPassword {
+        username: String,
+        remaining: u32,
+    },
+    LockedOut {
+        username: String,
+        until: SystemTime,
+    },
+    // 取不到随机数（生成盐或会话令牌时），内容是底层的 I/O 错误
+    RandomUnavailable(String),
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RegistryError::InvalidUsername { username, reason } => {
+                write!(f, "invalid username {:?}: {}", username, reason)
+            }
+            RegistryError::InvalidEmail { email, reason } => {
+                write!(f, "invalid email {:?}: {}", email, reason)
+            }
+            RegistryError::UsernameTaken(username) => {
+                write!(f, "username {:?} is already taken", username)
+            }
+            RegistryError::EmailTaken(email) => write!(f, "email {:?} is already in use", email),
+            RegistryError::NotFound(id) => write!(f, "no user with id {}", id),
+            RegistryError::UnknownUsername(username) => {
+                write!(f, "no user named {:?}", username)
+            }
+            RegistryError::UnknownEmail(email) => write!(f, "no user with email {:?}", email),
+            RegistryError::Inactive(username) => {
+                write!(f, "user {:?} is deactivated", username)
+            }
+            RegistryError::AlreadyInactive(username) => {
+                write!(f, "user {:?} is already deactivated", username)
+            }
+            RegistryError::WeakPassword(reason) => write!(f, "weak password: {}", reason),
+            RegistryError::NoPassword(username) => {
+                write!(f, "user {:?} has no password set", username)
+            }
+            RegistryError::WrongPassword {
+                username,
+                remaining,
+            } => write!(
+                f,
+                "wrong password for {:?}, {} attempts left",
+                username, remaining
+            ),
+            RegistryError::LockedOut { username, until } => write!(
+                f,
+                "user {:?} is locked out until {}",
+                username,
+                unix_seconds(*until)
+            ),
+            RegistryError::RandomUnavailable(err) => {
+                write!(f, "cannot read random bytes: {}", err)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
+pub const MAX_FAILED_LOGINS: u32 = 5;
+pub const LOCKOUT_DURATION: Duration = Duration::from_secs(15 * 60);
+
+pub const USERNAME_MIN: usize = 3;
+pub const USERNAME_MAX: usize = 32;
+
+// 用户名：字母开头，只能包含字母、数字、下划线和短横线
+pub fn validate_username(username: &str) -> Result<(), UsernameError> {
+    let found = username.chars().count();
+    if !(USERNAME_MIN..=USERNAME_MAX).contains(&found) {
+        return Err(UsernameError::Length {
+            min: USERNAME_MIN,
+            max: USERNAME_MAX,
+            found,
+        });
+    }
+    if let Some(c) = username
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || *c == '_' || *c == '-'))
+    {
+        return Err(UsernameError::BadCharacter(c));
+    }
+    if !username.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return Err(UsernameError::BadStart);
+    }
+    Ok(())
+}
+
+// 只做常见的语法检查，不追求覆盖 RFC 5322 的全部写法：
+//   本地部分由字母、数字和 .!#$%&'*+/=?^_`{|}~- 组成，点不能在开头、结尾或者连续出现；
+//   域名至少两段，每段由字母、数字、短横线组成且短横线不在两端，最后一段是至少两个字母。
+pub fn validate_email(email: &str) -> Result<(), EmailError> {
+    if email.is_empty() {
+        return Err(EmailError::Empty);
+    }
+    let (local, domain) = match email.split_once('@') {
+        Some(parts) => parts,
+        None => return Err(EmailError::MissingAt),
+    };
+    if domain.contains('@') {
+        return Err(EmailError::MultipleAt);
+    }
+    if local.is_empty() {
+        return Err(EmailError::EmptyLocalPart);
+    }
+    let local_char = |c: char| c.is_ascii_alphanumeric() || "!#$%&'*+/=?^_`{|}~-.".contains(c);
+    if !local.chars().all(local_char)
+        || local.starts_with('.')
+        || local.ends_with('.')
+        || local.contains("..")
+    {
+        return Err(EmailError::BadLocalPart);
+    }
+    let labels: Vec<&str> = domain.split('.').collect();
+    let label_ok = |label: &&str| {
+        !label.is_empty()
+            && label.len() <= 63
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+    };
+    if labels.len() < 2 || !labels.iter().all(label_ok) {
+        return Err(EmailError::BadDomain);
+    }
+    let tld = labels[labels.len() - 1];
+    if tld.len() < 2 || !tld.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(EmailError::BadTopLevelDomain);
+    }
+    Ok(())
+}
+
+// 唯一性按小写后的邮箱判断，保存的仍是用户输入的原样
+fn email_key(email: &str) -> String {
+    email.to_ascii_lowercase()
+}
+
+// 注册表里的一条记录：User 本身加上注册表维护的时间信息
+#[derive(Debug, Clone, PartialEq)]
+pub struct Account {
+    pub id: UserId,
+    pub user: User,
+    pub created_at: SystemTime,
+    pub updated_at: SystemTime,
+    pub last_sign_in: Option<SystemTime>,
+    pub deactivated_at: Option<SystemTime>,
+    // 连续输错密码的次数，登录成功或锁定到期后清零
+    pub failed_logins: u32,
+    pub locked_until: Option<SystemTime>,
+}
+
+impl Account {
+    pub fn username(&self) -> &str {
+        &self.user.username
+    }
+    pub fn email(&self) -> &str {
+        &self.user.email
+    }
+    pub fn is_active(&self) -> bool {
+        self.user.active
+    }
+    pub fn sign_in_count(&self) -> u64 {
+        self.user.sign_in_count
+    }
+    pub fn has_password(&self) -> bool {
+        self.user.password.is_some()
+    }
+}
+
+// update 的参数，没有设置的字段保持不变
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UserUpdate {
+    pub username: Option<String>,
+    pub email: Option<String>,
+}
+
+impl UserUpdate {
+    pub fn username(mut self, username: &str) -> Self {
+        self.username = Some(username.to_string());
+        self
+    }
+    pub fn email(mut self, email: &str) -> Self {
+        self.email = Some(email.to_string());
+        self
+    }
+}
+
+pub struct UserRegistry<C: Clock = SystemClock> {
+    clock: C,
+    next_id: u64,
+    accounts: BTreeMap<UserId, Account>,
+    by_username: HashMap<String, UserId>,
+    by_email: HashMap<String, UserId>,
+    // 新设置的密码用多少轮迭代
+    hash_iterations: u32,
+}
+
+impl UserRegistry<SystemClock> {
+    pub fn new() -> Self {
+        UserRegistry::with_clock(SystemClock)
+    }
+}
+
+impl Default for UserRegistry<SystemClock> {
+    fn default() -> Self {
+        UserRegistry::new()
+    }
+}
+
+impl<C: Clock> UserRegistry<C> {
+    pub fn with_clock(clock: C) -> Self {
+        UserRegistry {
+            clock,
+            next_id: 1,
+            accounts: BTreeMap::new(),
+            by_username: HashMap::new(),
+            by_email: HashMap::new(),
+            hash_iterations: auth::DEFAULT_ITERATIONS,
+        }
+    }
+
+    // 只影响之后设置的密码，已有的哈希按各自的迭代次数校验
+    pub fn set_hash_iterations(&mut self, iterations: u32) {
+        self.hash_iterations = iterations;
+    }
+
+    pub fn len(&self) -> usize {
+        self.accounts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    // 按 id 顺序遍历，也就是创建顺序
+    pub fn accounts(&self) -> impl Iterator<Item = &Account> {
+        self.accounts.values()
+    }
+
+    fn check_username(&self, username: &str, owner: Option<UserId>) -> Result<(), RegistryError> {
+        validate_username(username).map_err(|reason| RegistryError::InvalidUsername {
+            username: username.to_string(),
+            reason,
+        })?;
+        match self.by_username.get(username) {
+            Some(id) if Some(*id) != owner => {
+                Err(RegistryError::UsernameTaken(username.to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn check_email(&self, email: &str, owner: Option<UserId>) -> Result<(), RegistryError> {
+        validate_email(email).map_err(|reason| RegistryError::InvalidEmail {
+            email: email.to_string(),
+            reason,
+        })?;
+        match self.by_email.get(&email_key(email)) {
+            Some(id) if Some(*id) != owner => Err(RegistryError::EmailTaken(email.to_string())),
+            _ => Ok(()),
+        }
+    }
+
+    // 新用户是启用状态，登录次数为 0
+    pub fn create(&mut self, username: &str, email: &str) -> Result<UserId, RegistryError> {
+        self.check_username(username, None)?;
+        self.check_email(email, None)?;
+        let id = UserId(self.next_id);
+        self.next_id += 1;
+        let now = self.clock.now();
+        let user =
+            crate::build_user_like_typescript(email.to_string(), username.to_string(), 0, true);
+        self.by_username.insert(username.to_string(), id);
+        self.by_email.insert(email_key(email), id);
+        self.accounts.insert(
+            id,
+            Account {
+                id,
+                user,
+                created_at: now,
+                updated_at: now,
+                last_sign_in: None,
+                deactivated_at: None,
+                failed_logins: 0,
+                locked_until: None,
+            },
+        );
+        Ok(id)
+    }
+
+    pub fn get(&self, id: UserId) -> Result<&Account, RegistryError> {
+        self.accounts.get(&id).ok_or(RegistryError::NotFound(id))
+    }
+
+    pub fn find_by_username(&self, username: &str) -> Result<&Account, RegistryError> {
+        self.by_username
+            .get(username)
+            .and_then(|id| self.accounts.get(id))
+            .ok_or_else(|| RegistryError::UnknownUsername(username.to_string()))
+    }
+
+    pub fn find_by_email(&self, email: &str) -> Result<&Account, RegistryError> {
+        self.by_email
+            .get(&email_key(email))
+            .and_then(|id| self.accounts.get(id))
+            .ok_or_else(|| RegistryError::UnknownEmail(email.to_string()))
+    }
+
+    // 先把所有字段都校验完再修改，失败时注册表保持原样
+    pub fn update(&mut self, id: UserId, update: UserUpdate) -> Result<&Account, RegistryError> {
+        let account = self.get(id)?;
+        if !account.is_active() {
+            return Err(RegistryError::Inactive(account.username().to_string()));
+        }
+        if let Some(username) = &update.username {
+            self.check_username(username, Some(id))?;
+        }
+        if let Some(email) = &update.email {
+            self.check_email(email, Some(id))?;
+        }
+        let now = self.clock.now();
+        let account = self.accounts.get_mut(&id).unwrap();
+        if let Some(username) = update.username {
+            self.by_username.remove(&account.user.username);
+            self.by_username.insert(username.clone(), id);
+            account.user.username = username;
+        }
+        if let Some(email) = update.email {
+            self.by_email.remove(&email_key(&account.user.email));
+            self.by_email.insert(email_key(&email), id);
+            account.user.email = email;
+        }
+        account.updated_at = now;
+        Ok(account)
+    }
+
+    // 原样放回一条记录（从磁盘恢复、撤销写失败的修改时用），同 id 的旧记录被替换，不做校验也不动时间
+    pub fn restore(&mut self, account: Account) {
+        self.remove(account.id);
+        let id = account.id;
+        self.next_id = self.next_id.max(id.0 + 1);
+        self.by_username.insert(account.user.username.clone(), id);
+        self.by_email.insert(email_key(&account.user.email), id);
+        self.accounts.insert(id, account);
+    }
+
+    // 彻底删除一条记录，只用于撤销；正常的业务流程用 deactivate
+    pub fn remove(&mut self, id: UserId) -> Option<Account> {
+        let account = self.accounts.remove(&id)?;
+        self.by_username.remove(&account.user.username);
+        self.by_email.remove(&email_key(&account.user.email));
+        Some(account)
+    }
+
+    // 停用不删除记录，用户名和邮箱不会被别人拿走
+    pub fn deactivate(&mut self, id: UserId) -> Result<&Account, RegistryError> {
+        let now = self.clock.now();
+        let account = self
+            .accounts
+            .get_mut(&id)
+            .ok_or(RegistryError::NotFound(id))?;
+        if !account.user.active {
+            return Err(RegistryError::AlreadyInactive(
+                account.user.username.clone(),
+            ));
+        }
+        account.user.active = false;
+        account.deactivated_at = Some(now);
+        account.updated_at = now;
+        Ok(account)
+    }
+
+    // 设置或更换密码，顺带解除锁定
+    pub fn set_password(&mut self, id: UserId, password: &str) -> Result<&Account, RegistryError> {
+        let account = self.get(id)?;
+        if !account.is_active() {
+            return Err(RegistryError::Inactive(account.username().to_string()));
+        }
+        auth::validate_password(account.username(), password)
+            .map_err(RegistryError::WeakPassword)?;
+        let hash = PasswordHash::new(password, self.hash_iterations)
+            .map_err(|err| RegistryError::RandomUnavailable(err.to_string()))?;
+        let now = self.clock.now();
+        let account = self.accounts.get_mut(&id).unwrap();
+        account.user.password = Some(hash);
+        account.failed_logins = 0;
+        account.locked_until = None;
+        account.updated_at = now;
+        Ok(account)
+    }
+
+    // 失败时也可能改了记录（失败次数、锁定时间），持久化的调用方要把它写下来
+    pub fn sign_in(&mut self, username: &str, password: &str) -> Result<&Account, RegistryError> {
+        let id = self.find_by_username(username)?.id;
+        let now = self.clock.now();
+        let account = self.accounts.get_mut(&id).unwrap();
+        if !account.user.active {
+            return Err(RegistryError::Inactive(username.to_string()));
+        }
+        match account.locked_until {
+            Some(until) if now < until => {
+                return Err(RegistryError::LockedOut {
+                    username: username.to_string(),
+                    until,
+                })
+            }
+            Some(_) => {
+                account.locked_until = None;
+                account.failed_logins = 0;
+            }
+            None => {}
+        }
+        let hash = match &account.user.password {
+            Some(hash) => hash,
+            None => return Err(RegistryError::NoPassword(username.to_string())),
+        };
+        if !hash.verify(password) {
+            account.failed_logins += 1;
+            if account.failed_logins >= MAX_FAILED_LOGINS {
+                let until = now + LOCKOUT_DURATION;
+                account.locked_until = Some(until);
+                return Err(RegistryError::LockedOut {
+                    username: username.to_string(),
+                    until,
+                });
+            }
+            return Err(RegistryError::WrongPassword {
+                username: username.to_string(),
+                remaining: MAX_FAILED_LOGINS - account.failed_logins,
+            });
+        }
+        account.failed_logins = 0;
+        account.user.sign_in_count += 1;
+        account.last_sign_in = Some(now);
+        Ok(account)
+    }
+}
+
+fn start() -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000)
+}
+
+// 手动时钟上的注册表，里面有 julianlee 和 alice 两个用户
+fn sample_registry() -> (ManualClock, UserRegistry<ManualClock>, UserId, UserId) {
+    let clock = ManualClock::new(start());
+    let mut users = UserRegistry::with_clock(clock.clone());
+    // 演示不需要那么多轮，默认的迭代次数见 auth.rs
+    users.set_hash_iterations(1_000);
+    let julian = users.create("julianlee", "julianlee107@163.com").unwrap();
+    let alice = users.create("alice", "Alice@Example.com").unwrap();
+    (clock, users, julian, alice)
+}
+
+pub(crate) fn registry() {
+    let (clock, mut users, julian, alice) = sample_registry();
+    println!("{} 个用户，alice 是 {}", users.len(), alice);
+
+    // 唯一性：用户名区分大小写，邮箱不区分；格式不对的直接拒绝
+    for (username, email) in [
+        ("julianlee", "other@163.com"),
+        ("alice2", "alice@example.COM"),
+        ("bob smith", "bob@example.com"),
+        ("bob", "bob@localhost"),
+    ] {
+        if let Err(error) = users.create(username, email) {
+            println!("create {} <{}>: {}", username, email, error);
+        }
+    }
+
+    // 登录：成功时计数加一并记下时间，输错密码会提示还剩几次
+    users.set_password(julian, "hunter22").unwrap();
+    clock.advance(Duration::from_secs(60));
+    if let Err(error) = users.sign_in("julianlee", "hunter2") {
+        println!("{}", error);
+    }
+    if let Ok(account) = users.sign_in("julianlee", "hunter22") {
+        println!(
+            "{} 登录 {} 次，最近一次在 {:?}",
+            account.username(),
+            account.sign_in_count(),
+            account.last_sign_in.map(unix_seconds)
+        );
+    }
+
+    // 修改用户名和邮箱，旧的用户名被释放
+    if let Ok(account) = users.update(
+        julian,
+        UserUpdate::default()
+            .username("julian")
+            .email("JulianLee107@163.com"),
+    ) {
+        println!("改名之后: {} <{}>", account.username(), account.email());
+    }
+    println!(
+        "按邮箱找 alice: {:?}，julianlee 还在吗: {}",
+        users
+            .find_by_email("ALICE@example.com")
+            .map(|account| account.id),
+        users.find_by_username("julianlee").is_ok()
+    );
+
+    // 停用之后不能登录，名字仍然占着
+    clock.advance(Duration::from_secs(60));
+    if let Ok(account) = users.deactivate(alice) {
+        println!(
+            "alice 停用于 {:?}，active: {}",
+            account.deactivated_at.map(unix_seconds),
+            account.is_active()
+        );
+    }
+    println!("{}", users.sign_in("alice", "hunter22").unwrap_err());
+    let names: Vec<&str> = users.accounts().map(Account::username).collect();
+    println!("{:?}，空: {}", names, users.is_empty());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn create_records_the_time() {
+        let (_, users, julian, _) = sample_registry();
+        assert_eq!(users.len(), 2);
+        let account = users.get(julian).unwrap();
+        assert!(account.is_active());
+        assert_eq!(account.sign_in_count(), 0);
+        assert_eq!(account.created_at, start());
+        assert_eq!(account.last_sign_in, None);
+        assert!(UserRegistry::with_clock(ManualClock::new(start())).is_empty());
+    }
+
+    #[test]
+    fn usernames_and_emails_are_unique() {
+        let (_, mut users, _, alice) = sample_registry();
+        // 用户名区分大小写，邮箱不区分
+        assert_eq!(
+            users.create("julianlee", "other@163.com"),
+            Err(RegistryError::UsernameTaken("julianlee".to_string()))
+        );
+        assert_eq!(
+            users.create("alice2", "alice@example.COM"),
+            Err(RegistryError::EmailTaken("alice@example.COM".to_string()))
+        );
+        assert_eq!(users.find_by_email("ALICE@example.com").unwrap().id, alice);
+        assert_eq!(users.len(), 2);
+    }
+
+    #[test]
+    fn email_format_is_checked() {
+        for (email, reason) in [
+            ("", EmailError::Empty),
+            ("julian.163.com", EmailError::MissingAt),
+            ("a@b@c.com", EmailError::MultipleAt),
+            ("@163.com", EmailError::EmptyLocalPart),
+            (".julian@163.com", EmailError::BadLocalPart),
+            ("jul..ian@163.com", EmailError::BadLocalPart),
+            ("jul ian@163.com", EmailError::BadLocalPart),
+            ("julian@localhost", EmailError::BadDomain),
+            ("julian@-163.com", EmailError::BadDomain),
+            ("julian@163..com", EmailError::BadDomain),
+            ("julian@163.c", EmailError::BadTopLevelDomain),
+            ("julian@163.c0m", EmailError::BadTopLevelDomain),
+        ] {
+            assert_eq!(validate_email(email), Err(reason), "{}", email);
+        }
+        assert!(validate_email("first.last+tag@mail.example-site.org").is_ok());
+    }
+
+    #[test]
+    fn username_format_is_checked() {
+        assert_eq!(
+            validate_username("ab"),
+            Err(UsernameError::Length {
+                min: 3,
+                max: 32,
+                found: 2
+            })
+        );
+        assert_eq!(validate_username("9lives"), Err(UsernameError::BadStart));
+        let (_, mut users, _, _) = sample_registry();
+        let err = users.create("bob smith", "bob@example.com").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid username \"bob smith\": character ' ' is not allowed"
+        );
+        assert_eq!(users.len(), 2);
+    }
+
+    #[test]
+    fn sign_in_counts_only_successes() {
+        let (clock, mut users, julian, _) = sample_registry();
+        users.set_password(julian, "hunter22").unwrap();
+        clock.advance(Duration::from_secs(60));
+        users.sign_in("julianlee", "hunter22").unwrap();
+        assert!(matches!(
+            users.sign_in("julianlee", "hunter2"),
+            Err(RegistryError::WrongPassword { remaining: 4, .. })
+        ));
+        clock.advance(Duration::from_secs(60));
+        let account = users.sign_in("julianlee", "hunter22").unwrap();
+        assert_eq!(account.sign_in_count(), 2);
+        assert_eq!(
+            account.last_sign_in,
+            Some(start() + Duration::from_secs(120))
+        );
+        assert_eq!(account.updated_at, start());
+        assert_eq!(account.failed_logins, 0);
+        assert_eq!(
+            users.sign_in("nobody", "hunter22").unwrap_err(),
+            RegistryError::UnknownUsername("nobody".to_string())
+        );
+    }
+
+    #[test]
+    fn failed_update_changes_nothing() {
+        let (clock, mut users, julian, _) = sample_registry();
+        clock.advance(Duration::from_secs(120));
+        assert_eq!(
+            users.update(
+                julian,
+                UserUpdate::default()
+                    .username("julian")
+                    .email("ALICE@example.com")
+            ),
+            Err(RegistryError::EmailTaken("ALICE@example.com".to_string()))
+        );
+        assert_eq!(users.find_by_username("julianlee").unwrap().id, julian);
+        assert!(users.find_by_username("julian").is_err());
+        // 改成自己原来的邮箱（只是大小写不同）不算冲突
+        let account = users
+            .update(
+                julian,
+                UserUpdate::default()
+                    .username("julian")
+                    .email("JulianLee107@163.com"),
+            )
+            .unwrap();
+        assert_eq!(account.username(), "julian");
+        assert_eq!(account.email(), "JulianLee107@163.com");
+        assert_eq!(account.updated_at, start() + Duration::from_secs(120));
+        // 旧的用户名被释放
+        assert!(users.find_by_username("julianlee").is_err());
+        users.create("julianlee", "julianlee@example.com").unwrap();
+        assert_eq!(
+            users.update(UserId(99), UserUpdate::default()),
+            Err(RegistryError::NotFound(UserId(99)))
+        );
+    }
+
+    #[test]
+    fn deactivated_users_keep_their_names() {
+        let (clock, mut users, julian, alice) = sample_registry();
+        clock.advance(Duration::from_secs(180));
+        let account = users.deactivate(alice).unwrap();
+        assert!(!account.is_active());
+        assert_eq!(
+            account.deactivated_at,
+            Some(start() + Duration::from_secs(180))
+        );
+        assert_eq!(
+            users.deactivate(alice),
+            Err(RegistryError::AlreadyInactive("alice".to_string()))
+        );
+        assert_eq!(
+            users.sign_in("alice", "hunter22"),
+            Err(RegistryError::Inactive("alice".to_string()))
+        );
+        assert_eq!(
+            users.update(alice, UserUpdate::default().username("alicia")),
+            Err(RegistryError::Inactive("alice".to_string()))
+        );
+        assert_eq!(
+            users.create("alice", "new@example.com"),
+            Err(RegistryError::UsernameTaken("alice".to_string()))
+        );
+        assert!(users.get(julian).unwrap().is_active());
+        let names: Vec<&str> = users.accounts().map(Account::username).collect();
+        assert_eq!(names, ["julianlee", "alice"]);
+    }
+
+    #[test]
+    fn default_registry_uses_the_system_clock() {
+        let mut users = UserRegistry::new();
+        users.set_hash_iterations(1_000);
+        let id = users.create("julianlee", "julianlee107@163.com").unwrap();
+        users.set_password(id, "hunter22").unwrap();
+        assert!(users
+            .sign_in("julianlee", "hunter22")
+            .unwrap()
+            .last_sign_in
+            .is_some());
+        assert!(users.get(id).unwrap().created_at <= SystemTime::now());
+        assert_eq!(
+            RegistryError::NotFound(UserId(99)).to_string(),
+            "no user with id #99"
+        );
+    }
+}
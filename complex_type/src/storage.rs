@@ -0,0 +1,963 @@
+// 用户数据持久化
+// 把 registry.rs 的 UserRegistry 保存到一个本地目录，目录里有两个文件：
+//   snapshot  某一时刻全部用户的快照，第一行记录快照对应的日志序号
+//   wal.log   快照之后的每一次修改，只追加不改写（write-ahead log）
+// 每次修改先改内存，再把改动后的整条记录追加到日志并 fsync，成功后才返回给调用方；
+// 追加失败时把内存里的修改撤销，内存和磁盘始终一致。
+// 日志里的每一行都是修改后的完整记录（put），重放同一行多少次结果都一样。
+// 日志积累到 snapshot_every 条时自动做一次快照：
+//   1. 写 snapshot.tmp 并 fsync；
+//   2. rename 成 snapshot（rename 是原子的，snapshot 要么是旧的要么是新的）；
+//   3. 清空 wal.log。
+// 任何一步之间进程被杀掉，重新打开时都能恢复：
+//   - 残留的 snapshot.tmp 直接删掉；
+//   - 日志里序号不大于快照序号的记录已经在快照里了，跳过；
+//   - 日志最后一行没写完（没有换行或者校验和不对）说明是写到一半被杀，截掉；
+//     中间的行校验失败则说明文件真的坏了，报 Corrupt，不去猜。
+// cargo run -- compact <目录> 手动做一次压缩（快照 + 清空日志），cargo run -- inspect <目录> 查看恢复情况；
+// 在各个位置真的杀掉进程的测试见 tests/storage_crash.rs。
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::auth::PasswordHash;
+use crate::registry::{
+    Account, Clock, RegistryError, SystemClock, UserId, UserRegistry, UserUpdate,
+};
+use crate::User;
+
+const SNAPSHOT: &str = "snapshot";
+const SNAPSHOT_TMP: &str = "snapshot.tmp";
+const WAL: &str = "wal.log";
+const SNAPSHOT_HEADER: &str = "users-snapshot";
+const VERSION: &str = "1";
+
+#[derive(Debug)]
+pub enum StorageError {
+    Io(io::Error),
+    Corrupt {
+        file: PathBuf,
+        line: usize,
+        reason: String,
+    },
+    Registry(RegistryError),
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StorageError::Io(err) => write!(f, "i/o error: {}", err),
+            StorageError::Corrupt { file, line, reason } => {
+                write!(f, "{}:{}: corrupt record: {}", file.display(), line, reason)
+            }
+            StorageError::Registry(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StorageError::Io(err) => Some(err),
+            StorageError::Registry(err) => Some(err),
+            StorageError::Corrupt { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for StorageError {
+    fn from(err: io::Error) -> Self {
+        StorageError::Io(err)
+    }
+}
+
+impl From<RegistryError> for StorageError {
+    fn from(err: RegistryError) -> Self {
+        StorageError::Registry(err)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StoreConfig {
+    // 日志攒够多少条自动做快照，None 表示只在 compact 时做
+    pub snapshot_every: Option<usize>,
+}
+
+impl Default for StoreConfig {
+    fn default() -> Self {
+        StoreConfig {
+            snapshot_every: Some(100),
+        }
+    }
+}
+
+// 打开时恢复了什么
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Recovery {
+    // 从快照读到的记录数
+    pub snapshot_records: usize,
+    // 从日志重放的记录数
+    pub replayed: usize,
+    // 日志里已经包含在快照中、被跳过的记录数
+    pub skipped: usize,
+    // 写到一半被截掉的字节数
+    pub torn_bytes: usize,
+    // 删掉了没来得及 rename 的 snapshot.tmp
+    pub stale_snapshot: bool,
+}
+
+// 故障注入：走到指定位置时直接 abort 进程，模拟被 kill
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrashPoint {
+    // 日志写了半行
+    TornAppend,
+    // 日志已经追加并 fsync，还没开始做快照
+    AfterAppend,
+    // snapshot.tmp 写完，还没 rename
+    BeforeRename,
+    // 快照已经 rename，日志还没清空
+    BeforeTruncate,
+}
+
+impl CrashPoint {
+    pub const ALL: [CrashPoint; 4] = [
+        CrashPoint::TornAppend,
+        CrashPoint::AfterAppend,
+        CrashPoint::BeforeRename,
+        CrashPoint::BeforeTruncate,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CrashPoint::TornAppend => "torn-append",
+            CrashPoint::AfterAppend => "after-append",
+            CrashPoint::BeforeRename => "before-rename",
+            CrashPoint::BeforeTruncate => "before-truncate",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<CrashPoint> {
+        CrashPoint::ALL
+            .into_iter()
+            .find(|point| point.name() == name)
+    }
+}
+
+pub struct Store<C: Clock = SystemClock> {
+    dir: PathBuf,
+    config: StoreConfig,
+    registry: UserRegistry<C>,
+    wal: File,
+    // 最后一条记录的序号，快照里记的也是它
+    seq: u64,
+    // 日志里现在有多少条记录
+    wal_records: usize,
+    recovery: Recovery,
+    // 最近一次自动快照失败的原因，下一次成功时清掉
+    snapshot_error: Option<StorageError>,
+    crash_at: Option<CrashPoint>,
+}
+
+impl Store<SystemClock> {
+    pub fn open(dir: impl AsRef<Path>, config: StoreConfig) -> Result<Self, StorageError> {
+        Store::open_with_clock(dir, config, SystemClock)
+    }
+}
+
+impl<C: Clock> Store<C> {
+    pub fn open_with_clock(
+        dir: impl AsRef<Path>,
+        config: StoreConfig,
+        clock: C,
+    ) -> Result<Self, StorageError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        let mut recovery = Recovery::default();
+        let mut registry = UserRegistry::with_clock(clock);
+
+        let tmp = dir.join(SNAPSHOT_TMP);
+        if tmp.exists() {
+            fs::remove_file(&tmp)?;
+            recovery.stale_snapshot = true;
+        }
+
+        let mut seq = 0;
+        let snapshot = dir.join(SNAPSHOT);
+        if snapshot.exists() {
+            let (snapshot_seq, accounts) = read_snapshot(&snapshot)?;
+            seq = snapshot_seq;
+            recovery.snapshot_records = accounts.len();
+            accounts
+                .into_iter()
+                .for_each(|account| registry.restore(account));
+        }
+
+        let wal_path = dir.join(WAL);
+        let mut wal_records = 0;
+        if wal_path.exists() {
+            let log = read_wal(&wal_path)?;
+            for (record_seq, account) in log.records {
+                wal_records += 1;
+                if record_seq <= seq {
+                    recovery.skipped += 1;
+                    continue;
+                }
+                seq = record_seq;
+                recovery.replayed += 1;
+                registry.restore(account);
+            }
+            if log.torn_bytes > 0 {
+                let file = OpenOptions::new().write(true).open(&wal_path)?;
+                file.set_len(log.valid_len as u64)?;
+                file.sync_all()?;
+                recovery.torn_bytes = log.torn_bytes;
+            }
+        }
+        let wal = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&wal_path)?;
+        sync_dir(&dir)?;
+        Ok(Store {
+            dir,
+            config,
+            registry,
+            wal,
+            seq,
+            wal_records,
+            recovery,
+            snapshot_error: None,
+            crash_at: None,
+        })
+    }
+
+    pub fn registry(&self) -> &UserRegistry<C> {
+        &self.registry
+    }
+
+    pub fn recovery(&self) -> Recovery {
+        self.recovery
+    }
+
+    pub fn wal_records(&self) -> usize {
+        self.wal_records
+    }
+
+    pub fn snapshot_error(&self) -> Option<&StorageError> {
+        self.snapshot_error.as_ref()
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn crash_at(&mut self, point: CrashPoint) {
+        self.crash_at = Some(point);
+    }
+
+    fn crash_if(&self, point: CrashPoint) {
+        if self.crash_at == Some(point) {
+            std::process::abort();
+        }
+    }
+
+    pub fn create(&mut self, username: &str, email: &str) -> Result<UserId, StorageError> {
+        let id = self.registry.create(username, email)?;
+        self.persist(id, None)?;
+        Ok(id)
+    }
+
+    pub fn update(&mut self, id: UserId, update: UserUpdate) -> Result<&Account, StorageError> {
+        let before = self.registry.get(id)?.clone();
+        self.registry.update(id, update)?;
+        self.persist(id, Some(before))
+    }
+
+    pub fn deactivate(&mut self, id: UserId) -> Result<&Account, StorageError> {
+        let before = self.registry.get(id)?.clone();
+        self.registry.deactivate(id)?;
+        self.persist(id, Some(before))
+    }
+
+    pub fn set_password(&mut self, id: UserId, password: &str) -> Result<&Account, StorageError> {
+        let before = self.registry.get(id)?.clone();
+        self.registry.set_password(id, password)?;
+        self.persist(id, Some(before))
+    }
+
+    pub fn set_hash_iterations(&mut self, iterations: u32) {
+        self.registry.set_hash_iterations(iterations);
+    }
+
+    // 密码错误同样要落盘：失败次数和锁定时间变了，重启之后不能清零
+    pub fn sign_in(&mut self, username: &str, password: &str) -> Result<&Account, StorageError> {
+        let before = self.registry.find_by_username(username)?.clone();
+        let id = before.id;
+        let result = self.registry.sign_in(username, password).map(|_| ());
+        if *self.registry.get(id)? != before {
+            self.persist(id, Some(before))?;
+        }
+        result?;
+        Ok(self.registry.get(id)?)
+    }
+
+    // 把修改后的记录写进日志；写失败时恢复成 before（None 表示这是新建的记录）
+    fn persist(&mut self, id: UserId, before: Option<Account>) -> Result<&Account, StorageError> {
+        let account = self.registry.get(id)?.clone();
+        if let Err(err) = self.append(&account) {
+            match before {
+                Some(before) => self.registry.restore(before),
+                None => {
+                    self.registry.remove(id);
+                }
+            }
+            return Err(err.into());
+        }
+        self.crash_if(CrashPoint::AfterAppend);
+        // 记录已经写进日志并 fsync，这次修改已经生效；快照只是为了缩短日志，失败了也不算这次写入失败。
+        // wal_records 没有清零，下一次写入时会再试
+        if let Some(every) = self.config.snapshot_every {
+            if self.wal_records >= every {
+                self.snapshot_error = self.compact().err();
+            }
+        }
+        Ok(self.registry.get(id)?)
+    }
+
+    fn append(&mut self, account: &Account) -> io::Result<()> {
+        let line = encode_record(self.seq + 1, account);
+        // 只写进去一部分就失败时要截回原来的长度，否则下一条会接在半行后面，变成中间坏掉的一行
+        let len = self.wal.metadata()?.len();
+        if let Err(err) = self.write_line(&line) {
+            self.wal.set_len(len)?;
+            return Err(err);
+        }
+        self.seq += 1;
+        self.wal_records += 1;
+        Ok(())
+    }
+
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        if self.crash_at == Some(CrashPoint::TornAppend) {
+            self.wal.write_all(&line.as_bytes()[..line.len() / 2])?;
+            self.wal.sync_data()?;
+            self.crash_if(CrashPoint::TornAppend);
+        }
+        self.wal.write_all(line.as_bytes())?;
+        self.wal.sync_data()
+    }
+
+    // 做一次快照并清空日志，返回清掉的日志条数
+    pub fn compact(&mut self) -> Result<usize, StorageError> {
+        let tmp = self.dir.join(SNAPSHOT_TMP);
+        let mut file = File::create(&tmp)?;
+        let mut contents = format!(
+            "{}\t{}\t{}\t{}\n",
+            SNAPSHOT_HEADER,
+            VERSION,
+            self.seq,
+            self.registry.len()
+        );
+        for account in self.registry.accounts() {
+            contents.push_str(&encode_record(self.seq, account));
+        }
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()?;
+        self.crash_if(CrashPoint::BeforeRename);
+        fs::rename(&tmp, self.dir.join(SNAPSHOT))?;
+        sync_dir(&self.dir)?;
+        self.crash_if(CrashPoint::BeforeTruncate);
+        self.wal.set_len(0)?;
+        self.wal.sync_all()?;
+        Ok(std::mem::replace(&mut self.wal_records, 0))
+    }
+}
+
+// rename 之后要 fsync 目录，新的目录项才算落盘
+fn sync_dir(dir: &Path) -> io::Result<()> {
+    if cfg!(unix) {
+        File::open(dir)?.sync_all()
+    } else {
+        Ok(())
+    }
+}
+
+// FNV-1a，只用来发现写坏的行，不防篡改
+fn checksum(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+// 字段之间用 \t 分隔，记录之间用 \n 分隔，字段里的这两个字符和 \ 要转义
+fn escape(field: &str) -> String {
+    let mut escaped = String::with_capacity(field.len());
+    for c in field.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape(field: &str) -> Result<String, String> {
+    let mut plain = String::with_capacity(field.len());
+    let mut chars = field.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            plain.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('\\') => plain.push('\\'),
+            Some('t') => plain.push('\t'),
+            Some('n') => plain.push('\n'),
+            other => return Err(format!("bad escape {:?}", other)),
+        }
+    }
+    Ok(plain)
+}
+
+// 时间存成 UNIX 纪元以来的纳秒数，没有值时存 -
+fn encode_time(time: Option<SystemTime>) -> String {
+    match time {
+        Some(time) => time
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos()
+            .to_string(),
+        None => "-".to_string(),
+    }
+}
+
+fn decode_time(field: &str) -> Result<Option<SystemTime>, String> {
+    if field == "-" {
+        return Ok(None);
+    }
+    let nanos: u128 = field
+        .parse()
+        .map_err(|_| format!("bad timestamp {:?}", field))?;
+    let secs = (nanos / 1_000_000_000) as u64;
+    let nanos = (nanos % 1_000_000_000) as u32;
+    Ok(Some(SystemTime::UNIX_EPOCH + Duration::new(secs, nanos)))
+}
+
+// 校验和 \t 序号 \t put \t id \t 用户名 \t 邮箱 \t 是否启用 \t 登录次数 \t 创建 \t 修改 \t 最近登录 \t 停用
+//   \t 密码哈希 \t 连续失败次数 \t 锁定到期
+// 没有密码时哈希存 -；最后三个字段是后来加的，只有前 11 个字段的旧记录按没有密码读
+fn encode_record(seq: u64, account: &Account) -> String {
+    let body = [
+        seq.to_string(),
+        "put".to_string(),
+        account.id.0.to_string(),
+        escape(&account.user.username),
+        escape(&account.user.email),
+        (account.user.active as u8).to_string(),
+        account.user.sign_in_count.to_string(),
+        encode_time(Some(account.created_at)),
+        encode_time(Some(account.updated_at)),
+        encode_time(account.last_sign_in),
+        encode_time(account.deactivated_at),
+        match &account.user.password {
+            Some(hash) => hash.to_string(),
+            None => "-".to_string(),
+        },
+        account.failed_logins.to_string(),
+        encode_time(account.locked_until),
+    ]
+    .join("\t");
+    format!("{:016x}\t{}\n", checksum(&body), body)
+}
+
+// line 不含结尾的换行
+fn decode_record(line: &str) -> Result<(u64, Account), String> {
+    let (sum, body) = line.split_once('\t').ok_or("missing checksum")?;
+    if u64::from_str_radix(sum, 16) != Ok(checksum(body)) {
+        return Err("checksum mismatch".to_string());
+    }
+    let fields: Vec<&str> = body.split('\t').collect();
+    if fields.len() != 11 && fields.len() != 14 {
+        return Err(format!("expected 11 or 14 fields, found {}", fields.len()));
+    }
+    if fields[1] != "put" {
+        return Err(format!("unknown operation {:?}", fields[1]));
+    }
+    let number = |index: usize| {
+        fields[index]
+            .parse::<u64>()
+            .map_err(|_| format!("bad number {:?}", fields[index]))
+    };
+    let active = match fields[5] {
+        "0" => false,
+        "1" => true,
+        other => return Err(format!("bad flag {:?}", other)),
+    };
+    let user = User {
+        active,
+        username: unescape(fields[3])?,
+        email: unescape(fields[4])?,
+        sign_in_count: number(6)?,
+        password: match fields.get(11) {
+            None | Some(&"-") => None,
+            Some(hash) => Some(hash.parse::<PasswordHash>()?),
+        },
+    };
+    let required =
+        |index: usize| decode_time(fields[index])?.ok_or_else(|| "missing timestamp".to_string());
+    let account = Account {
+        id: UserId(number(2)?),
+        user,
+        created_at: required(7)?,
+        updated_at: required(8)?,
+        last_sign_in: decode_time(fields[9])?,
+        deactivated_at: decode_time(fields[10])?,
+        failed_logins: match fields.get(12) {
+            Some(count) => count
+                .parse()
+                .map_err(|_| format!("bad number {:?}", count))?,
+            None => 0,
+        },
+        locked_until: match fields.get(13) {
+            Some(time) => decode_time(time)?,
+            None => None,
+        },
+    };
+    Ok((number(0)?, account))
+}
+
+// 快照是 rename 过来的，不会只写一半，任何问题都算损坏
+fn read_snapshot(path: &Path) -> Result<(u64, Vec<Account>), StorageError> {
+    let text = fs::read_to_string(path)?;
+    let corrupt = |line: usize, reason: String| StorageError::Corrupt {
+        file: path.to_path_buf(),
+        line,
+        reason,
+    };
+    let mut lines = text.lines();
+    let header: Vec<&str> = lines.next().unwrap_or_default().split('\t').collect();
+    let (seq, count) = match header[..] {
+        [SNAPSHOT_HEADER, VERSION, seq, count] => match (seq.parse(), count.parse()) {
+            (Ok(seq), Ok(count)) => (seq, count),
+            _ => return Err(corrupt(1, "bad header".to_string())),
+        },
+        _ => return Err(corrupt(1, "bad header".to_string())),
+    };
+    let mut accounts = Vec::with_capacity(count);
+    for (index, line) in lines.enumerate() {
+        let (_, account) = decode_record(line).map_err(|reason| corrupt(index + 2, reason))?;
+        accounts.push(account);
+    }
+    if accounts.len() != count {
+        return Err(corrupt(
+            accounts.len() + 2,
+            format!("expected {} records, found {}", count, accounts.len()),
+        ));
+    }
+    Ok((seq, accounts))
+}
+
+struct Wal {
+    records: Vec<(u64, Account)>,
+    // 完整、有效的部分有多长
+    valid_len: usize,
+    torn_bytes: usize,
+}
+
+fn read_wal(path: &Path) -> Result<Wal, StorageError> {
+    let bytes = fs::read(path)?;
+    let mut records = Vec::new();
+    let mut valid_len = 0;
+    let mut line_number = 0;
+    while valid_len < bytes.len() {
+        line_number += 1;
+        let rest = &bytes[valid_len..];
+        let end = match rest.iter().position(|&b| b == b'\n') {
+            Some(end) => end,
+            // 最后一行没有换行，写到一半
+            None => break,
+        };
+        let decoded = std::str::from_utf8(&rest[..end])
+            .map_err(|err| err.to_string())
+            .and_then(decode_record);
+        match decoded {
+            Ok(record) => records.push(record),
+            // 最后一行校验失败同样当作没写完
+            Err(_) if valid_len + end + 1 == bytes.len() => break,
+            Err(reason) => {
+                return Err(StorageError::Corrupt {
+                    file: path.to_path_buf(),
+                    line: line_number,
+                    reason,
+                })
+            }
+        }
+        valid_len += end + 1;
+    }
+    Ok(Wal {
+        records,
+        valid_len,
+        torn_bytes: bytes.len() - valid_len,
+    })
+}
+
+// cargo run -- compact <目录>
+pub(crate) fn compact_main(args: &[String]) {
+    let dir = match args.first() {
+        Some(dir) => dir,
+        None => {
+            eprintln!("usage: compact <data-dir>");
+            return;
+        }
+    };
+    let result = Store::open(dir, StoreConfig::default())
+        .and_then(|mut store| Ok((store.recovery(), store.compact()?, store.registry().len())));
+    match result {
+        Ok((recovery, removed, users)) => println!(
+            "{}: {} users, {} log records compacted ({} replayed, {} torn bytes dropped)",
+            dir, users, removed, recovery.replayed, recovery.torn_bytes
+        ),
+        Err(err) => eprintln!("{}: {}", dir, err),
+    }
+}
+
+// cargo run -- inspect <目录>
+// 打开目录（和正常打开一样会截掉写了一半的日志、删掉残留的 snapshot.tmp），输出恢复情况和每个用户
+pub(crate) fn inspect_main(args: &[String]) {
+    let dir = match args.first() {
+        Some(dir) => dir,
+        None => {
+            eprintln!("usage: inspect <data-dir>");
+            return;
+        }
+    };
+    let store = match Store::open(dir, StoreConfig::default()) {
+        Ok(store) => store,
+        Err(err) => {
+            eprintln!("{}: {}", dir, err);
+            std::process::exit(1);
+        }
+    };
+    let recovery = store.recovery();
+    println!(
+        "snapshot_records={} replayed={} skipped={} torn_bytes={} stale_snapshot={}",
+        recovery.snapshot_records,
+        recovery.replayed,
+        recovery.skipped,
+        recovery.torn_bytes,
+        recovery.stale_snapshot
+    );
+    for account in store.registry().accounts() {
+        println!(
+            "{} active={} sign_in_count={}",
+            account.username(),
+            account.is_active(),
+            account.sign_in_count()
+        );
+    }
+}
+
+// 崩溃测试（tests/storage_crash.rs）用的子进程入口：cargo run -- storage-crash <目录> <位置>
+// 先正常写几条，再在指定位置 abort
+pub(crate) const CRASH_COMMAND: &str = "storage-crash";
+
+pub(crate) fn crash_main(args: &[String]) {
+    let (dir, point) = match args {
+        [dir, point] => (
+            dir,
+            CrashPoint::from_name(point).expect("unknown crash point"),
+        ),
+        _ => panic!("usage: {} <data-dir> <crash-point>", CRASH_COMMAND),
+    };
+    // 第 5 条记录正好触发快照，日志追加和快照之间的每个位置都能走到
+    let config = StoreConfig {
+        snapshot_every: Some(5),
+    };
+    let mut store = Store::open(dir, config).unwrap();
+    store.set_hash_iterations(1_000);
+    let julian = store.create("julianlee", "julianlee107@163.com").unwrap();
+    let alice = store.create("alice", "alice@example.com").unwrap();
+    store.set_password(julian, "hunter22").unwrap();
+    store.sign_in("julianlee", "hunter22").unwrap();
+    store.crash_at(point);
+    store.deactivate(alice).unwrap();
+    unreachable!("store did not crash at {}", point.name());
+}
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("complex_type-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+pub(crate) fn storage() {
+    let dir = scratch_dir("storage");
+    let config = StoreConfig {
+        snapshot_every: Some(3),
+    };
+
+    // 写几条，第 3 条触发快照，之后的留在日志里
+    let mut store = Store::open(&dir, config).unwrap();
+    store.set_hash_iterations(1_000);
+    let julian = store.create("julianlee", "julianlee107@163.com").unwrap();
+    store.create("alice", "alice@example.com").unwrap();
+    store.set_password(julian, "hunter22").unwrap();
+    store.sign_in("julianlee", "hunter22").unwrap();
+    if let Err(err) = store.create("bob", "alice@example.com") {
+        println!("没有写进日志: {}", err);
+    }
+    println!(
+        "{} 里还有 {} 条日志，快照出错: {:?}",
+        store.dir().display(),
+        store.wal_records(),
+        store.snapshot_error()
+    );
+    drop(store);
+
+    // 重新打开：快照加上重放的日志
+    let mut store = Store::open(&dir, config).unwrap();
+    println!("{:?}", store.recovery());
+    if let Ok(account) = store.registry().get(julian) {
+        println!(
+            "{} 登录 {} 次，有密码: {}",
+            account.username(),
+            account.sign_in_count(),
+            account.has_password()
+        );
+    }
+    store
+        .update(julian, UserUpdate::default().email("julian@example.com"))
+        .unwrap();
+    println!("compact 清掉 {} 条日志", store.compact().unwrap());
+    drop(store);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: StoreConfig = StoreConfig {
+        snapshot_every: Some(3),
+    };
+    const NO_SNAPSHOT: StoreConfig = StoreConfig {
+        snapshot_every: None,
+    };
+
+    fn open(dir: &Path, config: StoreConfig) -> Store {
+        let mut store = Store::open(dir, config).unwrap();
+        store.set_hash_iterations(1_000);
+        store
+    }
+
+    #[test]
+    fn reopen_restores_everything() {
+        let dir = scratch_dir("reopen");
+        let mut store = open(&dir, CONFIG);
+        let julian = store.create("julianlee", "julianlee107@163.com").unwrap();
+        store.create("alice", "alice@example.com").unwrap();
+        assert_eq!(store.wal_records(), 2);
+        store.set_password(julian, "hunter22").unwrap();
+        // 第 3 条触发了快照
+        assert_eq!(store.wal_records(), 0);
+        // 输错密码也会写日志，失败次数重启后还在
+        for _ in 0..2 {
+            assert!(matches!(
+                store.sign_in("julianlee", "hunter2"),
+                Err(StorageError::Registry(RegistryError::WrongPassword { .. }))
+            ));
+        }
+        assert_eq!(store.wal_records(), 2);
+        drop(store);
+
+        let mut store = open(&dir, CONFIG);
+        assert_eq!(store.recovery().replayed, 2);
+        let account = store.registry().get(julian).unwrap();
+        assert!(account.has_password());
+        assert_eq!(account.failed_logins, 2);
+        let signed_in = store.sign_in("julianlee", "hunter22").unwrap().clone();
+        assert_eq!(store.wal_records(), 0);
+        store
+            .update(julian, UserUpdate::default().email("julian@example.com"))
+            .unwrap();
+        assert_eq!(store.wal_records(), 1);
+        // 校验失败的修改不进日志
+        assert!(matches!(
+            store.create("bob", "alice@example.com"),
+            Err(StorageError::Registry(RegistryError::EmailTaken(_)))
+        ));
+        assert_eq!(store.wal_records(), 1);
+        drop(store);
+
+        let store = open(&dir, CONFIG);
+        assert_eq!(
+            store.recovery(),
+            Recovery {
+                snapshot_records: 2,
+                replayed: 1,
+                ..Recovery::default()
+            }
+        );
+        let account = store.registry().get(julian).unwrap();
+        assert_eq!(account.email(), "julian@example.com");
+        assert_eq!((account.sign_in_count(), account.failed_logins), (1, 0));
+        assert_eq!(account.last_sign_in, signed_in.last_sign_in);
+        assert_eq!(account.created_at, signed_in.created_at);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn compact_empties_the_log() {
+        let dir = scratch_dir("compact");
+        let mut store = open(&dir, NO_SNAPSHOT);
+        store.create("julianlee", "julianlee107@163.com").unwrap();
+        let alice = store.create("alice", "alice@example.com").unwrap();
+        store.set_password(alice, "correct horse").unwrap();
+        for _ in 0..5 {
+            store.sign_in("alice", "correct horse").unwrap();
+        }
+        assert_eq!(store.compact().unwrap(), 8);
+        assert_eq!(fs::metadata(dir.join(WAL)).unwrap().len(), 0);
+        drop(store);
+
+        let store = open(&dir, CONFIG);
+        assert_eq!(store.recovery().snapshot_records, 2);
+        assert_eq!(store.recovery().replayed, 0);
+        assert_eq!(
+            store
+                .registry()
+                .find_by_username("alice")
+                .unwrap()
+                .sign_in_count(),
+            5
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn corrupt_middle_line_is_an_error() {
+        let dir = scratch_dir("corrupt");
+        let mut store = open(&dir, NO_SNAPSHOT);
+        store.create("alice", "alice@example.com").unwrap();
+        store.create("julianlee", "julianlee107@163.com").unwrap();
+        drop(store);
+        // 日志中间被改坏：不猜，直接报错
+        let wal = fs::read_to_string(dir.join(WAL)).unwrap();
+        fs::write(dir.join(WAL), wal.replacen("alice", "alicf", 1)).unwrap();
+        match Store::open(&dir, CONFIG) {
+            Err(StorageError::Corrupt { line, reason, .. }) => {
+                assert_eq!(line, 1);
+                assert_eq!(reason, "checksum mismatch");
+            }
+            other => panic!("expected corrupt log, got {:?}", other.err()),
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn torn_last_line_is_truncated() {
+        let dir = scratch_dir("torn");
+        let mut store = open(&dir, NO_SNAPSHOT);
+        store.create("alice", "alice@example.com").unwrap();
+        drop(store);
+        let mut wal = OpenOptions::new().append(true).open(dir.join(WAL)).unwrap();
+        wal.write_all(b"0123\t2\tput").unwrap();
+        drop(wal);
+
+        let mut store = open(&dir, NO_SNAPSHOT);
+        assert_eq!(store.recovery().torn_bytes, 10);
+        assert_eq!(store.recovery().replayed, 1);
+        // 截掉之后接着写，新的一行不会和半行拼在一起
+        store.create("julianlee", "julianlee107@163.com").unwrap();
+        drop(store);
+        let store = open(&dir, NO_SNAPSHOT);
+        assert_eq!(store.recovery().replayed, 2);
+        assert_eq!(store.recovery().torn_bytes, 0);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn failed_append_rolls_back_memory_and_log() {
+        let dir = scratch_dir("failed-append");
+        let mut store = open(&dir, NO_SNAPSHOT);
+        let alice = store.create("alice", "alice@example.com").unwrap();
+        let before = fs::read(dir.join(WAL)).unwrap();
+        // 换成只读的句柄，之后的追加都会失败
+        store.wal = File::open(dir.join(WAL)).unwrap();
+        assert!(matches!(
+            store.create("julianlee", "julianlee107@163.com"),
+            Err(StorageError::Io(_))
+        ));
+        assert!(matches!(store.deactivate(alice), Err(StorageError::Io(_))));
+        assert_eq!(store.registry().len(), 1);
+        assert!(store.registry().get(alice).unwrap().is_active());
+        assert_eq!(store.wal_records(), 1);
+        assert_eq!(fs::read(dir.join(WAL)).unwrap(), before);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn failed_snapshot_keeps_the_write() {
+        let dir = scratch_dir("failed-snapshot");
+        let mut store = open(&dir, CONFIG);
+        // snapshot.tmp 是个目录，快照写不进去
+        fs::create_dir(dir.join(SNAPSHOT_TMP)).unwrap();
+        let julian = store.create("julianlee", "julianlee107@163.com").unwrap();
+        store.create("alice", "alice@example.com").unwrap();
+        // 第 3 条该做快照了，快照失败但修改已经写进日志
+        assert!(store.deactivate(julian).is_ok());
+        assert!(!store.registry().get(julian).unwrap().is_active());
+        assert_eq!(store.wal_records(), 3);
+        assert!(matches!(store.snapshot_error(), Some(StorageError::Io(_))));
+        assert!(!dir.join(SNAPSHOT).exists());
+
+        // 下一次写入再试，成功之后日志清空
+        fs::remove_dir(dir.join(SNAPSHOT_TMP)).unwrap();
+        store.create("bob", "bob@example.com").unwrap();
+        assert_eq!(store.wal_records(), 0);
+        assert!(store.snapshot_error().is_none());
+        drop(store);
+
+        let store = open(&dir, CONFIG);
+        assert_eq!(store.recovery().snapshot_records, 3);
+        assert!(!store.registry().get(julian).unwrap().is_active());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn records_round_trip() {
+        let dir = scratch_dir("records");
+        let mut store = open(&dir, NO_SNAPSHOT);
+        let id = store.create("julianlee", "julianlee107@163.com").unwrap();
+        store.set_password(id, "hunter22").unwrap();
+        let account = store.registry().get(id).unwrap().clone();
+        let line = encode_record(7, &account);
+        assert_eq!(decode_record(line.trim_end()), Ok((7, account.clone())));
+
+        // 只有前 11 个字段的旧记录按没有密码读
+        let body: Vec<&str> = line.trim_end().split('\t').skip(1).take(11).collect();
+        let body = body.join("\t");
+        let old = format!("{:016x}\t{}", checksum(&body), body);
+        let (_, decoded) = decode_record(&old).unwrap();
+        assert!(!decoded.has_password());
+        assert_eq!(decoded.username(), "julianlee");
+
+        let body = "1\tput\t1";
+        assert_eq!(
+            decode_record(&format!("{:016x}\t{}", checksum(body), body)),
+            Err("expected 11 or 14 fields, found 3".to_string())
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn fields_are_escaped() {
+        assert_eq!(escape("a\tb\\c\n"), "a\\tb\\\\c\\n");
+        assert_eq!(unescape(&escape("a\tb\\c\n")).unwrap(), "a\tb\\c\n");
+        assert!(unescape("a\\x").is_err());
+    }
+}
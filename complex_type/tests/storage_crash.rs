@@ -0,0 +1,112 @@
+// 真的把进程杀掉：子进程在指定位置 abort，再用 inspect 重新打开目录检查恢复的结果。
+// 子进程先建 julianlee 和 alice、设置密码、登录一次（4 条日志），第 5 条停用 alice 时崩溃，
+// 第 5 条正好触发快照（见 storage.rs 的 crash_main）。
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+const EXE: &str = env!("CARGO_BIN_EXE_complex_type");
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "complex_type-crash-{}-{}",
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn run(args: &[&str]) -> Output {
+    Command::new(EXE)
+        .args(args)
+        .stderr(Stdio::null())
+        .output()
+        .unwrap()
+}
+
+// inspect 的输出：第一行是恢复情况，后面每行一个用户
+fn inspect(dir: &Path) -> (String, Vec<String>) {
+    let output = run(&["inspect", dir.to_str().unwrap()]);
+    assert!(output.status.success());
+    let text = String::from_utf8(output.stdout).unwrap();
+    let mut lines = text.lines().map(str::to_string);
+    let recovery = lines.next().unwrap();
+    (recovery, lines.collect())
+}
+
+fn users(alice_active: bool) -> Vec<String> {
+    vec![
+        "julianlee active=true sign_in_count=1".to_string(),
+        format!("alice active={} sign_in_count=0", alice_active),
+    ]
+}
+
+// 让子进程在 point 处崩溃，返回数据目录和重新打开时的恢复情况
+fn crash(point: &str, alice_active: bool) -> (PathBuf, String) {
+    let dir = scratch_dir(point);
+    let status = run(&["storage-crash", dir.to_str().unwrap(), point]).status;
+    assert!(!status.success(), "{} did not crash", point);
+    let (recovery, found) = inspect(&dir);
+    assert_eq!(found, users(alice_active), "{}", point);
+    (dir, recovery)
+}
+
+// 恢复之后可以继续写：compact 做一次快照，再打开数据不变，日志为空
+fn keeps_working(dir: PathBuf, alice_active: bool) {
+    assert!(run(&["compact", dir.to_str().unwrap()]).status.success());
+    let (recovery, found) = inspect(&dir);
+    assert_eq!(
+        recovery,
+        "snapshot_records=2 replayed=0 skipped=0 torn_bytes=0 stale_snapshot=false"
+    );
+    assert_eq!(found, users(alice_active));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+// 半行被截掉，停用没有生效，调用方也没有收到成功
+#[test]
+fn torn_append_drops_the_half_line() {
+    let (dir, recovery) = crash("torn-append", true);
+    assert!(recovery.starts_with("snapshot_records=0 replayed=4 skipped=0 torn_bytes="));
+    assert!(!recovery.contains("torn_bytes=0 "));
+    // 第一次打开已经截掉了半行
+    let (recovery, _) = inspect(&dir);
+    assert_eq!(
+        recovery,
+        "snapshot_records=0 replayed=4 skipped=0 torn_bytes=0 stale_snapshot=false"
+    );
+    keeps_working(dir, true);
+}
+
+// 日志完整，快照还没开始做，全部从日志重放
+#[test]
+fn crash_after_append_replays_the_log() {
+    let (dir, recovery) = crash("after-append", false);
+    assert_eq!(
+        recovery,
+        "snapshot_records=0 replayed=5 skipped=0 torn_bytes=0 stale_snapshot=false"
+    );
+    keeps_working(dir, false);
+}
+
+// snapshot.tmp 被丢掉，全部从日志重放
+#[test]
+fn crash_before_rename_drops_the_temporary_snapshot() {
+    let (dir, recovery) = crash("before-rename", false);
+    assert_eq!(
+        recovery,
+        "snapshot_records=0 replayed=5 skipped=0 torn_bytes=0 stale_snapshot=true"
+    );
+    keeps_working(dir, false);
+}
+
+// 快照已经包含全部记录，日志里的都跳过
+#[test]
+fn crash_before_truncate_skips_logged_records() {
+    let (dir, recovery) = crash("before-truncate", false);
+    assert_eq!(
+        recovery,
+        "snapshot_records=2 replayed=0 skipped=5 torn_bytes=0 stale_snapshot=false"
+    );
+    keeps_working(dir, false);
+}
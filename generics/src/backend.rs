@@ -0,0 +1,346 @@
+// 输出后端
+// 组件只管往 Canvas 上画字符和样式（widget.rs 里的 Style），最后怎么输出由后端决定：
+//   TextBackend  纯文本，样式全部忽略，可以选择在四周加一圈边框
+//   AnsiBackend  用 ANSI 转义序列给终端上色
+//   HtmlBackend  输出 <pre>，每段样式包一层 <span class="...">，可以选择输出完整的 HTML 页面
+// 同一棵组件树画一次，换一个后端就是另一种格式；后端可以在运行时按名字选择（from_name），
+// cargo run -- render ansi 用指定的后端输出演示界面。
+use crate::widget::{Canvas, Style};
+
+pub trait Backend {
+    fn name(&self) -> &'static str;
+    fn render(&self, canvas: &Canvas) -> String;
+}
+
+// 按名字选择后端，找不到时返回 None
+pub fn from_name(name: &str) -> Option<Box<dyn Backend>> {
+    match name {
+        "text" => Some(Box::new(TextBackend { frame: true })),
+        "ansi" => Some(Box::new(AnsiBackend { frame: true })),
+        "html" => Some(Box::new(HtmlBackend { standalone: false })),
+        "html-page" => Some(Box::new(HtmlBackend { standalone: true })),
+        _ => None,
+    }
+}
+
+pub const BACKENDS: [&str; 4] = ["text", "ansi", "html", "html-page"];
+
+// 四周的边框，文本和 ANSI 后端共用
+fn framed(canvas: &Canvas, rows: Vec<String>, edge_style: impl Fn(&str) -> String) -> String {
+    let edge = edge_style(&format!("+{}+", "-".repeat(canvas.width() as usize)));
+    let bar = edge_style("|");
+    let mut lines = vec![edge.clone()];
+    lines.extend(rows.into_iter().map(|row| format!("{}{}{}", bar, row, bar)));
+    lines.push(edge);
+    lines.join("\n")
+}
+
+pub struct TextBackend {
+    pub frame: bool,
+}
+
+impl Backend for TextBackend {
+    fn name(&self) -> &'static str {
+        "text"
+    }
+    fn render(&self, canvas: &Canvas) -> String {
+        if self.frame {
+            let rows = (0..canvas.height()).map(|y| canvas.row(y)).collect();
+            framed(canvas, rows, str::to_string)
+        } else {
+            canvas.to_string()
+        }
+    }
+}
+
+pub struct AnsiBackend {
+    pub frame: bool,
+}
+
+impl AnsiBackend {
+    // SGR 参数，Plain 不加任何转义
+    fn code(style: Style) -> Option<&'static str> {
+        match style {
+            Style::Plain => None,
+            Style::Border => Some("34"),
+            Style::Accent => Some("1;36"),
+            Style::Muted => Some("90"),
+            Style::Selected => Some("7"),
+            Style::Focus => Some("1;33"),
+        }
+    }
+    fn paint(style: Style, text: &str) -> String {
+        match AnsiBackend::code(style) {
+            Some(code) => format!("\x1b[{}m{}\x1b[0m", code, text),
+            None => text.to_string(),
+        }
+    }
+}
+
+impl Backend for AnsiBackend {
+    fn name(&self) -> &'static str {
+        "ansi"
+    }
+    // 每段结束都重置样式，一行中途被截断也不会把颜色带到下一行
+    fn render(&self, canvas: &Canvas) -> String {
+        let rows: Vec<String> = (0..canvas.height())
+            .map(|y| {
+                canvas
+                    .runs(y)
+                    .iter()
+                    .map(|(style, text)| AnsiBackend::paint(*style, text))
+                    .collect()
+            })
+            .collect();
+        if self.frame {
+            framed(canvas, rows, |text| AnsiBackend::paint(Style::Border, text))
+        } else {
+            rows.join("\n")
+        }
+    }
+}
+
+pub struct HtmlBackend {
+    // true 时输出带 <style> 的完整页面，false 时只输出 <pre> 片段，方便嵌到别的页面里
+    pub standalone: bool,
+}
+
+impl HtmlBackend {
+    fn class(style: Style) -> Option<&'static str> {
+        match style {
+            Style::Plain => None,
+            Style::Border => Some("border"),
+            Style::Accent => Some("accent"),
+            Style::Muted => Some("muted"),
+            Style::Selected => Some("selected"),
+            Style::Focus => Some("focus"),
+        }
+    }
+}
+
+const HTML_STYLE: &str =
+    "pre.screen { display: inline-block; border: 1px solid #888; padding: 2px; }
+.border { color: #36c; }
+.accent { color: #099; font-weight: bold; }
+.muted { color: #999; }
+.selected { background: #333; color: #fff; }
+.focus { color: #c90; font-weight: bold; }";
+
+pub(crate) fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl Backend for HtmlBackend {
+    fn name(&self) -> &'static str {
+        "html"
+    }
+    fn render(&self, canvas: &Canvas) -> String {
+        let rows: Vec<String> = (0..canvas.height())
+            .map(|y| {
+                canvas
+                    .runs(y)
+                    .iter()
+                    .map(|(style, text)| match HtmlBackend::class(*style) {
+                        Some(class) => {
+                            format!("<span class=\"{}\">{}</span>", class, escape_html(text))
+                        }
+                        None => escape_html(text),
+                    })
+                    .collect()
+            })
+            .collect();
+        let pre = format!("<pre class=\"screen\">{}</pre>", rows.join("\n"));
+        if self.standalone {
+            format!(
+                "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<style>\n{}\n</style>\n</head>\n<body>\n{}\n</body>\n</html>",
+                HTML_STYLE, pre
+            )
+        } else {
+            pre
+        }
+    }
+}
+
+// cargo run -- render [后端]
+pub(crate) fn render_main(name: Option<&str>) {
+    let name = name.unwrap_or("text");
+    match from_name(name) {
+        Some(backend) => demo_screen().run_with(backend.as_ref()),
+        None => {
+            eprintln!(
+                "unknown backend {:?}, expected one of: {}",
+                name,
+                BACKENDS.join(", ")
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+fn demo_screen() -> crate::widget::Screen {
+    use crate::widget::{Button, Checkbox, List, Screen, TextInput};
+    let mut screen = Screen::new(24, 4);
+    screen
+        .add(TextInput::new(0, 0, 10, "name"))
+        .add(Checkbox {
+            x: 0,
+            y: 2,
+            width: 10,
+            height: 1,
+            label: "a<b".to_string(),
+            checked: true,
+        })
+        .add(List {
+            x: 12,
+            y: 0,
+            width: 12,
+            height: 2,
+            items: vec!["tea".into(), "coffee".into()],
+            selected: Some(1),
+        })
+        .add(Button {
+            x: 12,
+            y: 3,
+            width: 12,
+            height: 1,
+            label: "OK".to_string(),
+        });
+    screen
+}
+
+pub(crate) fn backend() {
+    let mut screen = demo_screen();
+    screen.set_focus(&[3]);
+    let canvas = screen.render();
+
+    // 同一块画布：纯文本看不到样式，样式单独存在每个格子上
+    print!("{}", TextBackend { frame: false }.render(&canvas));
+    println!(
+        "(12, 1) {:?}，(15, 3) {:?}",
+        canvas.style(12, 1),
+        canvas.style(15, 3)
+    );
+    println!("{}", HtmlBackend { standalone: false }.render(&canvas));
+    println!("escape_html: {}", escape_html("a<b & \"c\""));
+    for name in BACKENDS {
+        if let Some(backend) = from_name(name) {
+            println!(
+                "{:<9} -> {} 后端，{} 字节",
+                name,
+                backend.name(),
+                backend.render(&canvas).len()
+            );
+        }
+    }
+    screen.run_with(&AnsiBackend { frame: true });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 去掉 ESC [ ... m 形式的转义序列
+    fn strip_ansi(text: &str) -> String {
+        let mut plain = String::new();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                for c in chars.by_ref() {
+                    if c == 'm' {
+                        break;
+                    }
+                }
+            } else {
+                plain.push(c);
+            }
+        }
+        plain
+    }
+
+    fn canvas() -> Canvas {
+        let mut screen = demo_screen();
+        screen.set_focus(&[3]);
+        screen.render()
+    }
+
+    #[test]
+    fn text_backend_ignores_styles() {
+        let canvas = canvas();
+        let text = TextBackend { frame: false }.render(&canvas);
+        assert_eq!(text, canvas.to_string());
+        assert_eq!(
+            text.lines().collect::<Vec<_>>(),
+            [
+                "name______    tea",
+                "            > coffee",
+                "[x] a<b",
+                "               < OK >",
+            ]
+            .iter()
+            .map(|line| format!("{:<24}", line))
+            .collect::<Vec<_>>()
+        );
+        assert_eq!(canvas.style(12, 1), Some(Style::Selected));
+        assert_eq!(canvas.style(15, 3), Some(Style::Focus));
+        assert_eq!(canvas.style(16, 3), Some(Style::Accent));
+        assert_eq!(canvas.style(24, 0), None);
+        let framed = TextBackend { frame: true }.render(&canvas);
+        assert!(framed.starts_with("+------------------------+\n|name______"));
+        assert_eq!(framed.lines().count(), 6);
+    }
+
+    #[test]
+    fn ansi_without_escapes_is_the_text_output() {
+        let canvas = canvas();
+        let ansi = AnsiBackend { frame: false }.render(&canvas);
+        assert_eq!(
+            strip_ansi(&ansi),
+            TextBackend { frame: false }.render(&canvas)
+        );
+        assert!(ansi.starts_with("\x1b[90mname______\x1b[0m"));
+        assert!(ansi.contains("\x1b[7m> coffee\x1b[0m"));
+        assert!(ansi.contains("[\x1b[1;36mx\x1b[0m] a<b"));
+        assert!(ansi.contains("\x1b[1;33m<\x1b[0m\x1b[1;36m OK \x1b[0m\x1b[1;33m>\x1b[0m"));
+        let ansi_framed = AnsiBackend { frame: true }.render(&canvas);
+        assert_eq!(
+            strip_ansi(&ansi_framed),
+            TextBackend { frame: true }.render(&canvas)
+        );
+    }
+
+    #[test]
+    fn html_escapes_text_and_maps_styles_to_classes() {
+        let canvas = canvas();
+        let html = HtmlBackend { standalone: false }.render(&canvas);
+        assert!(html.starts_with("<pre class=\"screen\"><span class=\"muted\">name______</span>"));
+        assert!(html.contains("<span class=\"selected\">&gt; coffee</span>"));
+        assert!(html.contains("] a&lt;b"));
+        assert!(!html.contains("a<b"));
+        assert_eq!(html.lines().count(), 4);
+        let page = HtmlBackend { standalone: true }.render(&canvas);
+        assert!(page.starts_with("<!DOCTYPE html>") && page.contains(&html));
+        assert!(page.contains(".selected {"));
+        assert_eq!(escape_html("\"&\""), "&quot;&amp;&quot;");
+    }
+
+    #[test]
+    fn backends_are_found_by_name() {
+        let canvas = canvas();
+        for name in BACKENDS {
+            let backend = from_name(name).unwrap();
+            assert!(name.starts_with(backend.name()));
+            assert!(!backend.render(&canvas).is_empty());
+        }
+        assert!(from_name("pdf").is_none());
+    }
+}
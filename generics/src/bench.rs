@@ -0,0 +1,412 @@
+// 静态分发 vs 动态分发 vs 枚举分发
+// widget.rs 里的注释说 Screen1<T: Draw>（泛型，静态分发）比 Screen 的 Vec<Box<dyn Draw>>（特征对象，动态分发）快，
+// 这里用数字说话。一共比较三种写法：
+//   static  Screen1<T>，编译期就知道调用哪个 draw，可以内联
+//   dyn     Vec<Box<dyn Draw>>，运行时通过 vtable 找到 draw，每个组件单独分配在堆上
+//   enum    Vec<AnyWidget>，用 match 分发，组件连续存放，也能处理不同类型的组件
+// 每种写法测两个操作：
+//   area    只调用 area() 求和，几乎没有别的开销，最能体现分发本身的代价
+//   render  把所有组件画到 80x24 的画布上，更接近真实使用
+// Screen1<T> 只能放同一种组件，所以同质（全是 Button）的场景三种都测，混合的场景只测 dyn 和 enum。
+//
+// 默认的 cargo run 只用很少的迭代次数跑一遍，检查三种写法画出来的结果完全一样；
+// 要看真实的数字请用 release 模式单独运行：cargo run --release -- bench
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use crate::event::{Event, Response};
+use crate::widget::{compose, Button, Checkbox, Draw, Label, Rect, Region, Screen, Screen1};
+
+const WIDTH: u32 = 80;
+const HEIGHT: u32 = 24;
+
+// 枚举分发：每增加一种组件都要改这里，换来的是没有 vtable、没有装箱
+pub enum AnyWidget {
+    Button(Button),
+    Label(Label),
+    Checkbox(Checkbox),
+}
+
+macro_rules! each {
+    ($self:ident, $widget:ident => $body:expr) => {
+        match $self {
+            AnyWidget::Button($widget) => $body,
+            AnyWidget::Label($widget) => $body,
+            AnyWidget::Checkbox($widget) => $body,
+        }
+    };
+}
+
+impl Draw for AnyWidget {
+    fn area(&self) -> Rect {
+        each!(self, w => w.area())
+    }
+    fn set_area(&mut self, area: Rect) {
+        each!(self, w => w.set_area(area))
+    }
+    fn draw(&self, region: &mut Region) {
+        each!(self, w => w.draw(region))
+    }
+    fn focusable(&self) -> bool {
+        each!(self, w => w.focusable())
+    }
+    fn handle(&mut self, event: &Event, visible: Rect) -> Response {
+        each!(self, w => w.handle(event, visible))
+    }
+    fn draw_focus(&self, region: &mut Region) {
+        each!(self, w => w.draw_focus(region))
+    }
+}
+
+// 第 i 个组件的位置，在画布上铺开，超出的部分会折回来重叠
+fn place(i: usize) -> (u32, u32) {
+    let i = i as u32;
+    ((i * 7) % (WIDTH - 8), (i * 3) % HEIGHT)
+}
+
+fn button(i: usize) -> Button {
+    let (x, y) = place(i);
+    Button {
+        x,
+        y,
+        width: 8,
+        height: 1,
+        label: format!("b{}", i % 100),
+    }
+}
+
+// 混合场景里按顺序轮流放三种组件
+fn mixed(i: usize) -> AnyWidget {
+    let (x, y) = place(i);
+    match i % 3 {
+        0 => AnyWidget::Button(button(i)),
+        1 => AnyWidget::Label(Label {
+            x,
+            y,
+            width: 10,
+            height: 2,
+            text: format!("label {}", i),
+        }),
+        _ => AnyWidget::Checkbox(Checkbox {
+            x,
+            y,
+            width: 10,
+            height: 1,
+            label: format!("c{}", i),
+            checked: i.is_multiple_of(2),
+        }),
+    }
+}
+
+fn boxed(widget: AnyWidget) -> Box<dyn Draw> {
+    match widget {
+        AnyWidget::Button(w) => Box::new(w),
+        AnyWidget::Label(w) => Box::new(w),
+        AnyWidget::Checkbox(w) => Box::new(w),
+    }
+}
+
+fn sum_areas<'a, T: Draw + ?Sized + 'a>(components: impl Iterator<Item = &'a T>) -> u64 {
+    components
+        .map(|c| {
+            let area = c.area();
+            (area.x + area.y + area.width) as u64
+        })
+        .sum()
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BenchConfig {
+    pub counts: &'static [usize],
+    // 每个样本至少运行这么长时间
+    pub sample_time: Duration,
+    pub samples: usize,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        BenchConfig {
+            counts: &[10, 100, 1_000, 10_000],
+            sample_time: Duration::from_millis(100),
+            samples: 7,
+        }
+    }
+}
+
+impl BenchConfig {
+    // 只检查能跑通，数字没有参考价值
+    pub fn quick() -> Self {
+        BenchConfig {
+            counts: &[10, 100],
+            sample_time: Duration::from_micros(200),
+            samples: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurement {
+    pub scenario: &'static str,
+    pub operation: &'static str,
+    pub dispatch: &'static str,
+    pub components: usize,
+    // 多个样本中每次调用耗时的中位数和最小值（纳秒）
+    pub median_ns: f64,
+    pub min_ns: f64,
+}
+
+impl Measurement {
+    pub fn ns_per_component(&self) -> f64 {
+        self.median_ns / self.components as f64
+    }
+}
+
+// 先找到一个让单个样本运行至少 sample_time 的迭代次数，再采集多个样本
+pub fn measure<R, F: FnMut() -> R>(config: &BenchConfig, mut f: F) -> (f64, f64) {
+    // 预热
+    black_box(f());
+    let mut iterations: u64 = 1;
+    loop {
+        let start = Instant::now();
+        for _ in 0..iterations {
+            black_box(f());
+        }
+        if start.elapsed() >= config.sample_time || iterations >= 1 << 30 {
+            break;
+        }
+        iterations *= 2;
+    }
+    let mut samples: Vec<f64> = (0..config.samples.max(1))
+        .map(|_| {
+            let start = Instant::now();
+            for _ in 0..iterations {
+                black_box(f());
+            }
+            start.elapsed().as_nanos() as f64 / iterations as f64
+        })
+        .collect();
+    samples.sort_by(f64::total_cmp);
+    (samples[samples.len() / 2], samples[0])
+}
+
+pub fn run(config: &BenchConfig) -> Vec<Measurement> {
+    let mut results = Vec::new();
+    let mut record =
+        |scenario, operation, dispatch, components, (median_ns, min_ns): (f64, f64)| {
+            results.push(Measurement {
+                scenario,
+                operation,
+                dispatch,
+                components,
+                median_ns,
+                min_ns,
+            })
+        };
+    for &count in config.counts {
+        let statics = Screen1 {
+            width: WIDTH,
+            height: HEIGHT,
+            components: (0..count).map(button).collect(),
+        };
+        let mut dynamic = Screen::new(WIDTH, HEIGHT);
+        dynamic.components = (0..count)
+            .map(|i| boxed(AnyWidget::Button(button(i))))
+            .collect();
+        let enums: Vec<AnyWidget> = (0..count).map(|i| AnyWidget::Button(button(i))).collect();
+
+        let area = "area";
+        record(
+            "same",
+            area,
+            "static",
+            count,
+            measure(config, || sum_areas(black_box(&statics.components).iter())),
+        );
+        record(
+            "same",
+            area,
+            "dyn",
+            count,
+            measure(config, || {
+                sum_areas(black_box(&dynamic.components).iter().map(|c| c.as_ref()))
+            }),
+        );
+        record(
+            "same",
+            area,
+            "enum",
+            count,
+            measure(config, || sum_areas(black_box(&enums).iter())),
+        );
+        let render = "render";
+        record(
+            "same",
+            render,
+            "static",
+            count,
+            measure(config, || statics.render()),
+        );
+        record(
+            "same",
+            render,
+            "dyn",
+            count,
+            measure(config, || dynamic.render()),
+        );
+        record(
+            "same",
+            render,
+            "enum",
+            count,
+            measure(config, || compose(WIDTH, HEIGHT, black_box(&enums).iter())),
+        );
+
+        let mut dynamic = Screen::new(WIDTH, HEIGHT);
+        dynamic.components = (0..count).map(|i| boxed(mixed(i))).collect();
+        let enums: Vec<AnyWidget> = (0..count).map(mixed).collect();
+        record(
+            "mixed",
+            area,
+            "dyn",
+            count,
+            measure(config, || {
+                sum_areas(black_box(&dynamic.components).iter().map(|c| c.as_ref()))
+            }),
+        );
+        record(
+            "mixed",
+            area,
+            "enum",
+            count,
+            measure(config, || sum_areas(black_box(&enums).iter())),
+        );
+        record(
+            "mixed",
+            render,
+            "dyn",
+            count,
+            measure(config, || dynamic.render()),
+        );
+        record(
+            "mixed",
+            render,
+            "enum",
+            count,
+            measure(config, || compose(WIDTH, HEIGHT, black_box(&enums).iter())),
+        );
+    }
+    results
+}
+
+// 按场景和操作分组，同一组里以第一种写法为基准给出相对耗时
+pub fn print_report(results: &[Measurement]) {
+    println!(
+        "{:<6} {:<7} {:<7} {:>6} {:>12} {:>12} {:>10} {:>8}",
+        "case", "op", "via", "n", "median", "min", "ns/item", "ratio"
+    );
+    let mut baseline = None;
+    for m in results {
+        let key = (m.scenario, m.operation, m.components);
+        let base = match baseline {
+            Some((k, base)) if k == key => base,
+            _ => {
+                baseline = Some((key, m.median_ns));
+                m.median_ns
+            }
+        };
+        println!(
+            "{:<6} {:<7} {:<7} {:>6} {:>12} {:>12} {:>10.2} {:>7.2}x",
+            m.scenario,
+            m.operation,
+            m.dispatch,
+            m.components,
+            format_ns(m.median_ns),
+            format_ns(m.min_ns),
+            m.ns_per_component(),
+            m.median_ns / base
+        );
+    }
+}
+
+fn format_ns(ns: f64) -> String {
+    if ns >= 1_000_000.0 {
+        format!("{:.2} ms", ns / 1_000_000.0)
+    } else if ns >= 1_000.0 {
+        format!("{:.2} us", ns / 1_000.0)
+    } else {
+        format!("{:.1} ns", ns)
+    }
+}
+
+// cargo run --release -- bench
+pub(crate) fn bench_main() {
+    if cfg!(debug_assertions) {
+        println!("warning: debug build, run with --release for meaningful numbers");
+    }
+    print_report(&run(&BenchConfig::default()));
+}
+
+pub(crate) fn bench() {
+    // debug 下只跑一遍最小的配置，确认能跑通；要看数字用 cargo run --release -- bench
+    let results = run(&BenchConfig::quick());
+    println!(
+        "dispatch benchmark smoke run: {} measurements, use `cargo run --release -- bench` for numbers",
+        results.len()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widget::Canvas;
+
+    #[test]
+    fn every_dispatch_renders_the_same() {
+        // 三种写法画出来的结果必须完全一样，比较速度才有意义
+        let statics = Screen1 {
+            width: WIDTH,
+            height: HEIGHT,
+            components: (0..50).map(button).collect(),
+        };
+        let mut dynamic = Screen::new(WIDTH, HEIGHT);
+        dynamic.components = (0..50)
+            .map(|i| boxed(AnyWidget::Button(button(i))))
+            .collect();
+        let enums: Vec<AnyWidget> = (0..50).map(|i| AnyWidget::Button(button(i))).collect();
+        let expected: Canvas = statics.render();
+        assert_eq!(dynamic.render(), expected);
+        assert_eq!(compose(WIDTH, HEIGHT, enums.iter()), expected);
+        assert_eq!(
+            sum_areas(statics.components.iter()),
+            sum_areas(enums.iter())
+        );
+    }
+
+    #[test]
+    fn mixed_widgets_through_enum_and_dyn() {
+        let mut dynamic = Screen::new(WIDTH, HEIGHT);
+        dynamic.components = (0..30).map(|i| boxed(mixed(i))).collect();
+        let mut enums: Vec<AnyWidget> = (0..30).map(mixed).collect();
+        assert_eq!(dynamic.render(), compose(WIDTH, HEIGHT, enums.iter()));
+        // 枚举也能正常处理事件
+        let area = enums[2].area();
+        assert_eq!(
+            enums[2].handle(&Event::Click { x: 0, y: 0 }, area),
+            Response::Toggled(false)
+        );
+        enums[1].set_area(Rect::new(1, 2, 3, 4));
+        assert_eq!(enums[1].area(), Rect::new(1, 2, 3, 4));
+        assert!(enums[0].focusable() && !enums[1].focusable());
+    }
+
+    #[test]
+    fn quick_run_measures_every_case() {
+        let config = BenchConfig::quick();
+        let results = run(&config);
+        // 每个数量：同质 3 种写法 x 2 个操作 + 混合 2 种写法 x 2 个操作
+        assert_eq!(results.len(), config.counts.len() * 10);
+        assert!(results
+            .iter()
+            .all(|m| m.min_ns > 0.0 && m.min_ns <= m.median_ns));
+    }
+}
@@ -0,0 +1,585 @@
+// 缓存
+// 以 CacheableItem::Address 为键的通用缓存。
+//   - 淘汰策略：Lru（最久没用的先走）、Lfu（用得最少的先走，次数相同时最久没用的先走）、
+//     Ttl（写入之后过一段时间自动过期，满了先淘汰最早过期的）；
+//   - 容量可以按条数，也可以按字节数（地址的字节数 + 条目自己报告的大小）；
+//   - 统计命中、未命中、淘汰、过期的次数；
+//   - is_null() 为 true 的条目表示“确认不存在”，同样会被缓存（负缓存），下次查同一个地址时不用再去加载，
+//     这种条目可以单独设置一个较短的过期时间。
+// 淘汰顺序用一个 BTreeMap 维护，插入、查询、淘汰都是 O(log n)。
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::Hash;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+//     使用特征类型和使用泛型的不同
+// 调用是使用Address比完整地写完AsRef<[u8]> + Clone + fmt::Debug + Eq + Hash要简单和可读性更强
+pub trait CacheableItem: Clone + Default + fmt::Debug {
+    type Address: AsRef<[u8]> + Clone + fmt::Debug + Eq + Hash;
+    fn is_null(&self) -> bool;
+    // 按字节计算容量时条目占多少字节，默认是类型本身的大小，有堆内存的类型应该把它也算上
+    fn size_bytes(&self) -> usize {
+        std::mem::size_of::<Self>()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Policy {
+    Lru,
+    Lfu,
+    Ttl(Duration),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Capacity {
+    Items(usize),
+    Bytes(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheConfig {
+    pub policy: Policy,
+    pub capacity: Capacity,
+    // 负缓存条目的过期时间，None 表示和普通条目一样
+    pub negative_ttl: Option<Duration>,
+}
+
+// 时间来源，测试时可以换成手动拨动的时钟
+pub trait Clock {
+    // 从某个固定起点开始经过的时间
+    fn now(&self) -> Duration;
+}
+
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        SystemClock {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+// 克隆出来的时钟共享同一个时间，拨动任何一个都会影响缓存
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+    now: Rc<Cell<Duration>>,
+}
+
+impl ManualClock {
+    pub fn advance(&self, by: Duration) {
+        self.now.set(self.now.get() + by);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        self.now.get()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CacheStats {
+    pub hits: u64,
+    // 命中了负缓存条目
+    pub negative_hits: u64,
+    pub misses: u64,
+    pub insertions: u64,
+    // 因为容量不够被淘汰
+    pub evictions: u64,
+    pub expirations: u64,
+    // 单个条目比整个缓存还大（或者容量为 0），没有放进去
+    pub rejected: u64,
+}
+
+impl CacheStats {
+    // 负缓存命中也算命中，因为同样省掉了一次加载
+    pub fn hit_rate(&self) -> Option<f64> {
+        let lookups = self.hits + self.negative_hits + self.misses;
+        if lookups == 0 {
+            None
+        } else {
+            Some((self.hits + self.negative_hits) as f64 / lookups as f64)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lookup<T> {
+    Hit(T),
+    // 缓存里记着这个地址不存在
+    Negative,
+    Miss,
+}
+
+struct Entry<T> {
+    item: T,
+    size: usize,
+    // 过期时间，None 表示不会过期
+    expires: Option<Duration>,
+    uses: u64,
+    // 在淘汰顺序里的键
+    rank: (u128, u64),
+}
+
+pub struct Cache<T: CacheableItem, C: Clock = SystemClock> {
+    config: CacheConfig,
+    clock: C,
+    entries: HashMap<T::Address, Entry<T>>,
+    // 淘汰顺序：最小的先淘汰。第二个分量是全局递增的序号，保证键唯一
+    order: BTreeMap<(u128, u64), T::Address>,
+    tick: u64,
+    bytes: usize,
+    stats: CacheStats,
+}
+
+impl<T: CacheableItem> Cache<T> {
+    pub fn new(config: CacheConfig) -> Self {
+        Cache::with_clock(config, SystemClock::default())
+    }
+}
+
+impl<T: CacheableItem, C: Clock> Cache<T, C> {
+    pub fn with_clock(config: CacheConfig, clock: C) -> Self {
+        Cache {
+            config,
+            clock,
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            tick: 0,
+            bytes: 0,
+            stats: CacheStats::default(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    // 当前占用的字节数，和容量单位无关，总是会统计
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    fn rank(&mut self, uses: u64, expires: Option<Duration>) -> (u128, u64) {
+        self.tick += 1;
+        let primary = match self.config.policy {
+            Policy::Lru => 0,
+            Policy::Lfu => uses as u128,
+            Policy::Ttl(_) => expires.map_or(u128::MAX, |at| at.as_nanos()),
+        };
+        (primary, self.tick)
+    }
+
+    fn is_expired(&self, entry: &Entry<T>) -> bool {
+        entry.expires.is_some_and(|at| self.clock.now() >= at)
+    }
+
+    fn remove_entry(&mut self, address: &T::Address) -> Option<Entry<T>> {
+        let entry = self.entries.remove(address)?;
+        self.order.remove(&entry.rank);
+        self.bytes -= entry.size;
+        Some(entry)
+    }
+
+    pub fn get(&mut self, address: &T::Address) -> Lookup<&T> {
+        let expired = match self.entries.get(address) {
+            None => {
+                self.stats.misses += 1;
+                return Lookup::Miss;
+            }
+            Some(entry) => self.is_expired(entry),
+        };
+        if expired {
+            self.remove_entry(address);
+            self.stats.expirations += 1;
+            self.stats.misses += 1;
+            return Lookup::Miss;
+        }
+        // 更新使用次数和淘汰顺序
+        let (uses, expires, old_rank) = {
+            let entry = &self.entries[address];
+            (entry.uses + 1, entry.expires, entry.rank)
+        };
+        let rank = self.rank(uses, expires);
+        self.order.remove(&old_rank);
+        self.order.insert(rank, address.clone());
+        let entry = self.entries.get_mut(address).unwrap();
+        entry.uses = uses;
+        entry.rank = rank;
+        if entry.item.is_null() {
+            self.stats.negative_hits += 1;
+            Lookup::Negative
+        } else {
+            self.stats.hits += 1;
+            Lookup::Hit(&entry.item)
+        }
+    }
+
+    // 放入一个条目，已有的同一地址会被替换。
+    // 返回 false 表示条目比整个缓存还大（或者容量为 0）放不下，这时原来的条目保持不变
+    pub fn insert(&mut self, address: T::Address, item: T) -> bool {
+        let size = address.as_ref().len() + item.size_bytes();
+        let fits = match self.config.capacity {
+            Capacity::Items(limit) => limit > 0,
+            Capacity::Bytes(limit) => size <= limit,
+        };
+        if !fits {
+            self.stats.rejected += 1;
+            return false;
+        }
+        self.remove_entry(&address);
+        let ttl = match self.config.policy {
+            Policy::Ttl(ttl) => Some(ttl),
+            _ => None,
+        };
+        let ttl = if item.is_null() {
+            self.config.negative_ttl.or(ttl)
+        } else {
+            ttl
+        };
+        let expires = ttl.map(|ttl| self.clock.now() + ttl);
+        self.make_room(size);
+        let rank = self.rank(1, expires);
+        self.order.insert(rank, address.clone());
+        self.entries.insert(
+            address,
+            Entry {
+                item,
+                size,
+                expires,
+                uses: 1,
+                rank,
+            },
+        );
+        self.bytes += size;
+        self.stats.insertions += 1;
+        true
+    }
+
+    // 先清掉过期的条目，还不够再按策略淘汰，直到能放下一个 size 字节的新条目
+    fn make_room(&mut self, size: usize) {
+        let full = |cache: &Self| match cache.config.capacity {
+            Capacity::Items(limit) => cache.entries.len() >= limit,
+            Capacity::Bytes(limit) => cache.bytes + size > limit,
+        };
+        if full(self) {
+            self.purge_expired();
+        }
+        while full(self) {
+            let Some((_, address)) = self.order.pop_first() else {
+                break;
+            };
+            let entry = self.entries.remove(&address).unwrap();
+            self.bytes -= entry.size;
+            self.stats.evictions += 1;
+        }
+    }
+
+    // 删除所有已过期的条目，返回删除的个数
+    pub fn purge_expired(&mut self) -> usize {
+        let expired: Vec<T::Address> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| self.is_expired(entry))
+            .map(|(address, _)| address.clone())
+            .collect();
+        for address in &expired {
+            self.remove_entry(address);
+        }
+        self.stats.expirations += expired.len() as u64;
+        expired.len()
+    }
+
+    pub fn remove(&mut self, address: &T::Address) -> Option<T> {
+        self.remove_entry(address).map(|entry| entry.item)
+    }
+
+    // 缓存里有就直接用，没有就调用 load 加载并放进缓存。
+    // load 返回 is_null() 的条目时同样会被缓存，结果返回 None
+    pub fn get_or_load<F>(&mut self, address: &T::Address, load: F) -> Option<T>
+    where
+        F: FnOnce(&T::Address) -> T,
+    {
+        match self.get(address) {
+            Lookup::Hit(item) => return Some(item.clone()),
+            Lookup::Negative => return None,
+            Lookup::Miss => {}
+        }
+        let item = load(address);
+        self.insert(address.clone(), item.clone());
+        if item.is_null() {
+            None
+        } else {
+            Some(item)
+        }
+    }
+}
+
+// 演示用的网页缓存：地址是 URL，默认值（空页面）表示 404
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Page {
+    status: u16,
+    body: String,
+}
+
+impl CacheableItem for Page {
+    type Address = String;
+    fn is_null(&self) -> bool {
+        self.status == 0
+    }
+    fn size_bytes(&self) -> usize {
+        std::mem::size_of::<Self>() + self.body.len()
+    }
+}
+
+// 地址也可以直接是字节
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Block(u64);
+
+impl CacheableItem for Block {
+    type Address = [u8; 4];
+    fn is_null(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+fn page(body: &str) -> Page {
+    Page {
+        status: 200,
+        body: body.to_string(),
+    }
+}
+
+fn url(path: &str) -> String {
+    format!("https://example.com/{}", path)
+}
+
+pub(crate) fn cache() {
+    // TTL 缓存，404 也缓存下来，但只缓存 5 秒
+    let clock = ManualClock::default();
+    let mut cache = Cache::with_clock(
+        CacheConfig {
+            policy: Policy::Ttl(Duration::from_secs(60)),
+            capacity: Capacity::Items(10),
+            negative_ttl: Some(Duration::from_secs(5)),
+        },
+        clock.clone(),
+    );
+    let load = |address: &String| {
+        println!("loading {}", address);
+        if address.ends_with("missing") {
+            Page::default()
+        } else {
+            page("fresh")
+        }
+    };
+    for path in ["home", "missing", "home", "missing"] {
+        let found = cache.get_or_load(&url(path), load);
+        println!("{} -> {:?}", path, found.map(|p| p.body));
+        clock.advance(Duration::from_secs(1));
+    }
+    println!(
+        "{} cached, {} bytes, {:?}, hit rate {:?}",
+        cache.len(),
+        cache.bytes(),
+        cache.stats(),
+        cache.stats().hit_rate()
+    );
+
+    let mut blocks: Cache<Block> = Cache::new(CacheConfig {
+        policy: Policy::Lfu,
+        capacity: Capacity::Items(1),
+        negative_ttl: None,
+    });
+    blocks.insert(*b"aaaa", Block(1));
+    blocks.insert(*b"bbbb", Block(2));
+    // 容量只有 1 条，放 b 时淘汰 a
+    println!("a after b: {:?}", blocks.get(b"aaaa"));
+    println!("remove b: {:?}", blocks.remove(b"bbbb"));
+    println!("empty: {}", blocks.is_empty());
+
+    // 按字节计算容量，比整个缓存还大的页面不放
+    let mut pages: Cache<Page> = Cache::new(CacheConfig {
+        policy: Policy::Lru,
+        capacity: Capacity::Bytes(200),
+        negative_ttl: None,
+    });
+    let inserted = pages.insert(url("big"), page(&"x".repeat(1000)));
+    println!("1000-byte page inserted: {}, {:?}", inserted, pages.stats());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lru_evicts_least_recently_used() {
+        // 容量 2 条，最近读过 a，所以淘汰的是 b
+        let mut lru: Cache<Page> = Cache::new(CacheConfig {
+            policy: Policy::Lru,
+            capacity: Capacity::Items(2),
+            negative_ttl: None,
+        });
+        lru.insert(url("a"), page("A"));
+        lru.insert(url("b"), page("B"));
+        assert!(matches!(lru.get(&url("a")), Lookup::Hit(p) if p.body == "A"));
+        lru.insert(url("c"), page("C"));
+        assert_eq!(lru.get(&url("b")), Lookup::Miss);
+        assert_eq!(lru.len(), 2);
+        assert_eq!(lru.stats().evictions, 1);
+        assert_eq!(lru.remove(&url("c")), Some(page("C")));
+    }
+
+    #[test]
+    fn lfu_evicts_least_frequently_used() {
+        // a 读了两次，b 读了一次，c 没读过，放 d 时淘汰 c
+        let mut lfu: Cache<Block> = Cache::new(CacheConfig {
+            policy: Policy::Lfu,
+            capacity: Capacity::Items(3),
+            negative_ttl: None,
+        });
+        lfu.insert(*b"aaaa", Block(1));
+        lfu.insert(*b"bbbb", Block(2));
+        lfu.insert(*b"cccc", Block(3));
+        lfu.get(b"aaaa");
+        lfu.get(b"aaaa");
+        lfu.get(b"bbbb");
+        lfu.insert(*b"dddd", Block(4));
+        assert_eq!(lfu.get(b"cccc"), Lookup::Miss);
+        assert_eq!(lfu.get(b"aaaa"), Lookup::Hit(&Block(1)));
+        // 次数相同（d 和 b 加载后各用了一次）时淘汰最久没用的
+        lfu.get(b"dddd");
+        lfu.insert(*b"eeee", Block(5));
+        assert_eq!(lfu.get(b"bbbb"), Lookup::Miss);
+        assert_eq!(lfu.len(), 3);
+    }
+
+    #[test]
+    fn ttl_and_negative_entries_expire() {
+        // 用手动时钟控制时间
+        let clock = ManualClock::default();
+        let mut ttl = Cache::with_clock(
+            CacheConfig {
+                policy: Policy::Ttl(Duration::from_secs(60)),
+                capacity: Capacity::Items(10),
+                negative_ttl: Some(Duration::from_secs(5)),
+            },
+            clock.clone(),
+        );
+        let mut loads = 0;
+        let mut load = |address: &String| {
+            loads += 1;
+            if address.ends_with("missing") {
+                Page::default()
+            } else {
+                page("fresh")
+            }
+        };
+        assert_eq!(
+            ttl.get_or_load(&url("home"), &mut load),
+            Some(page("fresh"))
+        );
+        assert_eq!(ttl.get_or_load(&url("missing"), &mut load), None);
+        clock.advance(Duration::from_secs(3));
+        // 都还在缓存里，不会再次加载
+        assert_eq!(
+            ttl.get_or_load(&url("home"), &mut load),
+            Some(page("fresh"))
+        );
+        assert_eq!(ttl.get_or_load(&url("missing"), &mut load), None);
+        assert_eq!(ttl.get(&url("missing")), Lookup::Negative);
+        // 负缓存 5 秒后过期，正常条目 60 秒后过期
+        clock.advance(Duration::from_secs(3));
+        assert_eq!(ttl.get(&url("missing")), Lookup::Miss);
+        assert!(matches!(ttl.get(&url("home")), Lookup::Hit(_)));
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(ttl.purge_expired(), 1);
+        assert!(ttl.is_empty());
+        assert_eq!(loads, 2);
+        let stats = ttl.stats();
+        assert_eq!(
+            (
+                stats.hits,
+                stats.negative_hits,
+                stats.misses,
+                stats.expirations
+            ),
+            (2, 2, 3, 2)
+        );
+        assert_eq!(stats.hit_rate(), Some(4.0 / 7.0));
+    }
+
+    #[test]
+    fn byte_capacity() {
+        let page_size = |body: &str| url("x").len() + page(body).size_bytes();
+        let mut bytes: Cache<Page> = Cache::new(CacheConfig {
+            policy: Policy::Lru,
+            capacity: Capacity::Bytes(page_size("0123456789") * 2),
+            negative_ttl: None,
+        });
+        assert!(bytes.insert(url("1"), page("0123456789")));
+        assert!(bytes.insert(url("2"), page("0123456789")));
+        assert_eq!(bytes.bytes(), page_size("0123456789") * 2);
+        // 稍大一点的条目需要淘汰两个旧条目才能放下
+        assert!(bytes.insert(url("3"), page("0123456789abc")));
+        assert_eq!(bytes.len(), 1);
+        assert_eq!(bytes.stats().evictions, 2);
+        // 比整个缓存还大的条目不放
+        assert!(!bytes.insert(url("4"), page(&"x".repeat(1000))));
+        assert_eq!(bytes.stats().rejected, 1);
+        assert_eq!(bytes.len(), 1);
+    }
+
+    #[test]
+    fn rejected_insert_keeps_the_existing_entry() {
+        let limit = url("3").len() + page("0123456789").size_bytes();
+        let mut bytes: Cache<Page> = Cache::new(CacheConfig {
+            policy: Policy::Lru,
+            capacity: Capacity::Bytes(limit),
+            negative_ttl: None,
+        });
+        assert!(bytes.insert(url("3"), page("0123456789")));
+        // 同一个地址换成放不下的大条目：被拒绝，原来的条目还在
+        assert!(!bytes.insert(url("3"), page(&"x".repeat(1000))));
+        assert_eq!(bytes.get(&url("3")), Lookup::Hit(&page("0123456789")));
+        assert_eq!(bytes.bytes(), limit);
+        let stats = bytes.stats();
+        assert_eq!((stats.rejected, stats.evictions), (1, 0));
+    }
+
+    #[test]
+    fn zero_item_capacity_stores_nothing() {
+        for policy in [
+            Policy::Lru,
+            Policy::Lfu,
+            Policy::Ttl(Duration::from_secs(1)),
+        ] {
+            let mut cache: Cache<Block> = Cache::new(CacheConfig {
+                policy,
+                capacity: Capacity::Items(0),
+                negative_ttl: None,
+            });
+            assert!(!cache.insert(*b"aaaa", Block(1)));
+            assert!(cache.is_empty());
+            assert_eq!(cache.get_or_load(b"aaaa", |_| Block(1)), Some(Block(1)));
+            assert!(cache.is_empty());
+            assert_eq!(cache.stats().rejected, 2);
+        }
+    }
+}
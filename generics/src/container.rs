@@ -0,0 +1,683 @@
+// 容器
+// 同一组容器分别用两种写法实现“容器”特征，对比泛型参数和关联类型：
+//   Container<A, B>  泛型参数，一个类型可以实现多次（比如 Grid 既能按格子看，也能按行看），
+//                    代价是使用的地方要把 A、B 都写出来，有时还要手动标注；
+//   ContainerII      关联类型，一个类型只能实现一次，但约束写起来简单得多。
+// 具体的容器有三个：
+//   BiMap     双向映射，两边都唯一，可以从任意一边查另一边；
+//   MultiMap  一个键对应多个值；
+//   Grid      二维网格，按 (x, y) 定位。
+// difference 和 difference_ii 计算两个容器的对称差大小：只在其中一个容器里出现的 (A, B) 对有几个。
+// 按集合计算：MultiMap 里重复插入的同一个 (A, B) 对只算一次，不比较出现的次数。
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::ops::{Index, IndexMut};
+
+// 使用泛型
+pub trait Container<A, B> {
+    fn container(&self, a: A, b: B) -> bool;
+    // 容器里所有的 (A, B) 对
+    fn pairs(&self) -> Vec<(A, B)>;
+}
+
+pub fn difference<A, B, C, D>(left: &C, right: &D) -> usize
+where
+    A: PartialEq + Clone,
+    B: PartialEq + Clone,
+    C: Container<A, B>,
+    D: Container<A, B>,
+{
+    let only_left = distinct(left.pairs())
+        .iter()
+        .filter(|(a, b)| !right.container(a.clone(), b.clone()))
+        .count();
+    let only_right = distinct(right.pairs())
+        .iter()
+        .filter(|(a, b)| !left.container(a.clone(), b.clone()))
+        .count();
+    only_left + only_right
+}
+
+// 去掉重复的元素，保留第一次出现的顺序。只要求 PartialEq，所以是 O(n²) 的
+fn distinct<T: PartialEq>(items: Vec<T>) -> Vec<T> {
+    let mut out: Vec<T> = Vec::with_capacity(items.len());
+    for item in items {
+        if !out.contains(&item) {
+            out.push(item);
+        }
+    }
+    out
+}
+
+// 可以看到使用了关联类型，可读性好不少
+pub trait ContainerII {
+    type A;
+    type B;
+    fn contains(&self, a: &Self::A, b: &Self::B) -> bool;
+    fn pairs(&self) -> Vec<(Self::A, Self::B)>;
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// 对比 difference：这里不需要额外的 A、B 参数，两个容器的关联类型相同即可
+pub fn difference_ii<C, D>(left: &C, right: &D) -> usize
+where
+    C: ContainerII,
+    C::A: PartialEq,
+    C::B: PartialEq,
+    D: ContainerII<A = C::A, B = C::B>,
+{
+    let only_left = distinct(left.pairs())
+        .iter()
+        .filter(|(a, b)| !right.contains(a, b))
+        .count();
+    let only_right = distinct(right.pairs())
+        .iter()
+        .filter(|(a, b)| !left.contains(a, b))
+        .count();
+    only_left + only_right
+}
+
+// 双向映射：左边的值和右边的值都是唯一的
+#[derive(Debug, Clone, Default)]
+pub struct BiMap<L, R> {
+    left: HashMap<L, R>,
+    right: HashMap<R, L>,
+}
+
+impl<L, R> BiMap<L, R>
+where
+    L: Eq + Hash + Clone,
+    R: Eq + Hash + Clone,
+{
+    pub fn new() -> Self {
+        BiMap {
+            left: HashMap::new(),
+            right: HashMap::new(),
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.left.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.left.is_empty()
+    }
+    // 插入 (l, r)，已经和 l 或 r 配对的旧条目会被移除并返回
+    pub fn insert(&mut self, l: L, r: R) -> Vec<(L, R)> {
+        let mut replaced = Vec::new();
+        if let Some(old) = self.remove_by_left(&l) {
+            replaced.push(old);
+        }
+        if let Some(old) = self.remove_by_right(&r) {
+            replaced.push(old);
+        }
+        self.left.insert(l.clone(), r.clone());
+        self.right.insert(r, l);
+        replaced
+    }
+    pub fn get_by_left(&self, l: &L) -> Option<&R> {
+        self.left.get(l)
+    }
+    pub fn get_by_right(&self, r: &R) -> Option<&L> {
+        self.right.get(r)
+    }
+    pub fn remove_by_left(&mut self, l: &L) -> Option<(L, R)> {
+        let r = self.left.remove(l)?;
+        let l = self.right.remove(&r).unwrap();
+        Some((l, r))
+    }
+    pub fn remove_by_right(&mut self, r: &R) -> Option<(L, R)> {
+        let l = self.right.remove(r)?;
+        let r = self.left.remove(&l).unwrap();
+        Some((l, r))
+    }
+    pub fn iter(&self) -> impl Iterator<Item = (&L, &R)> {
+        self.left.iter()
+    }
+}
+
+impl<L, R> Container<L, R> for BiMap<L, R>
+where
+    L: Eq + Hash + Clone,
+    R: Eq + Hash + Clone,
+{
+    fn container(&self, a: L, b: R) -> bool {
+        self.left.get(&a) == Some(&b)
+    }
+    fn pairs(&self) -> Vec<(L, R)> {
+        self.iter().map(|(l, r)| (l.clone(), r.clone())).collect()
+    }
+}
+
+impl<L, R> ContainerII for BiMap<L, R>
+where
+    L: Eq + Hash + Clone,
+    R: Eq + Hash + Clone,
+{
+    type A = L;
+    type B = R;
+    fn contains(&self, a: &L, b: &R) -> bool {
+        self.left.get(a) == Some(b)
+    }
+    fn pairs(&self) -> Vec<(L, R)> {
+        Container::pairs(self)
+    }
+    fn len(&self) -> usize {
+        self.left.len()
+    }
+}
+
+// 一个键对应多个值，同一个键下的值按插入顺序保存，允许重复
+#[derive(Debug, Clone, Default)]
+pub struct MultiMap<K, V> {
+    map: HashMap<K, Vec<V>>,
+    len: usize,
+}
+
+impl<K, V> MultiMap<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: PartialEq + Clone,
+{
+    pub fn new() -> Self {
+        MultiMap {
+            map: HashMap::new(),
+            len: 0,
+        }
+    }
+    // 值的总数
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub fn key_count(&self) -> usize {
+        self.map.len()
+    }
+    pub fn insert(&mut self, key: K, value: V) {
+        self.map.entry(key).or_default().push(value);
+        self.len += 1;
+    }
+    // 没有这个键时返回空切片
+    pub fn get(&self, key: &K) -> &[V] {
+        self.map.get(key).map_or(&[], Vec::as_slice)
+    }
+    // 删除一个匹配的值，键下没有值了就把键也删掉
+    pub fn remove(&mut self, key: &K, value: &V) -> bool {
+        let Some(values) = self.map.get_mut(key) else {
+            return false;
+        };
+        let Some(index) = values.iter().position(|v| v == value) else {
+            return false;
+        };
+        values.remove(index);
+        if values.is_empty() {
+            self.map.remove(key);
+        }
+        self.len -= 1;
+        true
+    }
+    pub fn remove_all(&mut self, key: &K) -> Vec<V> {
+        let values = self.map.remove(key).unwrap_or_default();
+        self.len -= values.len();
+        values
+    }
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.map
+            .iter()
+            .flat_map(|(k, values)| values.iter().map(move |v| (k, v)))
+    }
+}
+
+impl<K, V> Container<K, V> for MultiMap<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: PartialEq + Clone,
+{
+    fn container(&self, a: K, b: V) -> bool {
+        self.get(&a).contains(&b)
+    }
+    fn pairs(&self) -> Vec<(K, V)> {
+        self.iter().map(|(k, v)| (k.clone(), v.clone())).collect()
+    }
+}
+
+impl<K, V> ContainerII for MultiMap<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: PartialEq + Clone,
+{
+    type A = K;
+    type B = V;
+    fn contains(&self, a: &K, b: &V) -> bool {
+        self.get(a).contains(b)
+    }
+    fn pairs(&self) -> Vec<(K, V)> {
+        Container::pairs(self)
+    }
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+// 二维网格，按行存放在一个 Vec 里
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    // 每行的长度必须一样，否则返回 None
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Option<Self> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, Vec::len);
+        if rows.iter().any(|row| row.len() != width) {
+            return None;
+        }
+        Some(Grid {
+            width,
+            height,
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    pub fn height(&self) -> usize {
+        self.height
+    }
+    fn index_of(&self, x: usize, y: usize) -> Option<usize> {
+        if x < self.width && y < self.height {
+            Some(y * self.width + x)
+        } else {
+            None
+        }
+    }
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        self.index_of(x, y).map(|i| &self.cells[i])
+    }
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        self.index_of(x, y).map(|i| &mut self.cells[i])
+    }
+    // 返回原来的值，越界时返回 None 且不修改
+    pub fn set(&mut self, x: usize, y: usize, value: T) -> Option<T> {
+        self.get_mut(x, y)
+            .map(|cell| std::mem::replace(cell, value))
+    }
+    pub fn row(&self, y: usize) -> Option<&[T]> {
+        (y < self.height).then(|| &self.cells[y * self.width..(y + 1) * self.width])
+    }
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        let width = self.width;
+        self.cells
+            .iter()
+            .skip(x)
+            .step_by(width.max(1))
+            .take(if x < width { self.height } else { 0 })
+    }
+    // 上下左右四个方向上存在的邻居
+    pub fn neighbors(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        let mut result = Vec::new();
+        if self.index_of(x, y).is_none() {
+            return result;
+        }
+        if y > 0 {
+            result.push((x, y - 1));
+        }
+        if x + 1 < self.width {
+            result.push((x + 1, y));
+        }
+        if y + 1 < self.height {
+            result.push((x, y + 1));
+        }
+        if x > 0 {
+            result.push((x - 1, y));
+        }
+        result
+    }
+    // 按行遍历，同时给出坐标
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        let width = self.width;
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(i, cell)| ((i % width, i / width), cell))
+    }
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn transpose(&self) -> Grid<T> {
+        let mut cells = Vec::with_capacity(self.cells.len());
+        for x in 0..self.width {
+            cells.extend(self.column(x).cloned());
+        }
+        Grid {
+            width: self.height,
+            height: self.width,
+            cells,
+        }
+    }
+}
+
+// 越界直接 panic，和切片的下标一样；不想 panic 用 get
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        match self.index_of(x, y) {
+            Some(i) => &self.cells[i],
+            None => panic!(
+                "grid index ({}, {}) out of bounds {}x{}",
+                x, y, self.width, self.height
+            ),
+        }
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        match self.index_of(x, y) {
+            Some(i) => &mut self.cells[i],
+            None => panic!(
+                "grid index ({}, {}) out of bounds {}x{}",
+                x, y, width, height
+            ),
+        }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for y in 0..self.height {
+            for (x, cell) in self.row(y).unwrap().iter().enumerate() {
+                if x > 0 {
+                    write!(f, " ")?;
+                }
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+// 泛型参数的好处：Grid 可以实现两次 Container，一次按格子 ((x, y), 值)，一次按行 (y, 整行)
+impl<T: PartialEq + Clone> Container<(usize, usize), T> for Grid<T> {
+    fn container(&self, (x, y): (usize, usize), b: T) -> bool {
+        self.get(x, y) == Some(&b)
+    }
+    fn pairs(&self) -> Vec<((usize, usize), T)> {
+        self.iter().map(|(at, cell)| (at, cell.clone())).collect()
+    }
+}
+
+impl<T: PartialEq + Clone> Container<usize, Vec<T>> for Grid<T> {
+    fn container(&self, y: usize, row: Vec<T>) -> bool {
+        self.row(y) == Some(row.as_slice())
+    }
+    fn pairs(&self) -> Vec<(usize, Vec<T>)> {
+        (0..self.height)
+            .map(|y| (y, self.row(y).unwrap().to_vec()))
+            .collect()
+    }
+}
+
+// 关联类型只能选一种，这里选按格子
+impl<T: PartialEq + Clone> ContainerII for Grid<T> {
+    type A = (usize, usize);
+    type B = T;
+    fn contains(&self, &(x, y): &(usize, usize), b: &T) -> bool {
+        self.get(x, y) == Some(b)
+    }
+    fn pairs(&self) -> Vec<((usize, usize), T)> {
+        Container::<(usize, usize), T>::pairs(self)
+    }
+    fn len(&self) -> usize {
+        self.cells.len()
+    }
+}
+
+fn status_codes() -> BiMap<&'static str, u16> {
+    let mut codes = BiMap::new();
+    codes.insert("ok", 200);
+    codes.insert("not found", 404);
+    codes.insert("teapot", 418);
+    codes
+}
+
+fn language_tags() -> MultiMap<&'static str, &'static str> {
+    let mut tags = MultiMap::new();
+    tags.insert("rust", "lang");
+    tags.insert("rust", "systems");
+    tags.insert("rust", "lang");
+    tags.insert("go", "lang");
+    tags
+}
+
+fn small_grid() -> Grid<char> {
+    let mut grid = Grid::new(3, 2, '.');
+    grid[(0, 0)] = '#';
+    grid.set(2, 1, '@');
+    grid
+}
+
+fn letters(pairs: &[(u32, char)]) -> BiMap<u32, char> {
+    let mut map = BiMap::new();
+    for &(n, c) in pairs {
+        map.insert(n, c);
+    }
+    map
+}
+
+pub(crate) fn container() {
+    // BiMap：两边都唯一，插入时会把冲突的旧记录挤掉
+    let mut codes = status_codes();
+    println!(
+        "404 -> {:?}, 418 -> {:?}",
+        codes.get_by_left(&"not found"),
+        codes.get_by_right(&418)
+    );
+    println!("挤掉的记录: {:?}", codes.insert("ok", 418));
+    println!("删除 404: {:?}", codes.remove_by_right(&404));
+    println!(
+        "剩下 {} 条: {:?}，包含 (ok, 418): {}，空: {}",
+        codes.len(),
+        codes.iter().collect::<Vec<_>>(),
+        codes.contains(&"ok", &418),
+        codes.is_empty()
+    );
+
+    // MultiMap：一个键多个值
+    let mut tags = language_tags();
+    println!(
+        "rust: {:?}，共 {} 个值 {} 个键",
+        tags.get(&"rust"),
+        tags.len(),
+        tags.key_count()
+    );
+    tags.remove(&"rust", &"lang");
+    println!("删掉一个 lang 之后 rust: {:?}", tags.get(&"rust"));
+    println!(
+        "go 的全部值: {:?}，剩下 {} 个值，空: {}，新建的空: {}",
+        tags.remove_all(&"go"),
+        ContainerII::len(&tags),
+        tags.is_empty(),
+        ContainerII::is_empty(&MultiMap::<u8, u8>::new())
+    );
+
+    // Grid
+    let grid = small_grid();
+    print!("{}", grid);
+    println!(
+        "第 1 行 {:?}，第 2 列 {:?}，(1, 1) 的邻居 {:?}",
+        grid.row(1),
+        grid.column(2).collect::<String>(),
+        grid.neighbors(1, 1)
+    );
+    let flipped = grid.transpose();
+    println!("转置后 {}x{}:", flipped.width(), flipped.height());
+    print!("{}", flipped);
+    let walls = grid.map(|&c| c == '#');
+    println!("墙: {}", walls.iter().filter(|(_, &wall)| wall).count());
+    if let Some(numbers) = Grid::from_rows(vec![vec![1, 2], vec![3, 4]]) {
+        println!("from_rows (0, 1) = {:?}", numbers.get(0, 1));
+    }
+
+    // 对称差：两种写法结果一样，不同的容器类型之间也能比较
+    let a = letters(&[(1, 'a'), (2, 'b'), (3, 'c')]);
+    let b = letters(&[(2, 'b'), (3, 'x'), (4, 'd')]);
+    println!(
+        "difference = {}, difference_ii = {}",
+        difference(&a, &b),
+        difference_ii(&a, &b)
+    );
+    // Grid 实现了两次 Container，difference 要把 A、B 写出来
+    let mut other = grid.clone();
+    other[(1, 0)] = '#';
+    println!(
+        "按格子 {}，按行 {}，difference_ii {}",
+        difference::<(usize, usize), char, _, _>(&grid, &other),
+        difference::<usize, Vec<char>, _, _>(&grid, &other),
+        difference_ii(&grid, &other)
+    );
+    println!(
+        "(2, 1) 是 '@': {}",
+        Container::container(&grid, (2, 1), '@')
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bimap_keeps_both_sides_unique() {
+        let mut codes = status_codes();
+        assert_eq!(codes.get_by_left(&"not found"), Some(&404));
+        assert_eq!(codes.get_by_right(&418), Some(&"teapot"));
+        // 418 已经属于 teapot，"ok" 已经对应 200，两条旧记录都被替换
+        assert_eq!(codes.insert("ok", 418), vec![("ok", 200), ("teapot", 418)]);
+        assert_eq!(codes.len(), 2);
+        assert_eq!(codes.get_by_right(&200), None);
+        assert_eq!(codes.remove_by_right(&404), Some(("not found", 404)));
+        assert_eq!(codes.remove_by_left(&"not found"), None);
+        assert!(codes.contains(&"ok", &418) && !codes.is_empty());
+        assert!(BiMap::<u8, u8>::new().insert(1, 2).is_empty());
+    }
+
+    #[test]
+    fn multimap_keeps_duplicate_values() {
+        let mut tags = language_tags();
+        assert_eq!(tags.get(&"rust"), ["lang", "systems", "lang"]);
+        assert_eq!((tags.len(), tags.key_count()), (4, 2));
+        assert!(tags.remove(&"rust", &"lang"));
+        assert_eq!(tags.get(&"rust"), ["systems", "lang"]);
+        assert!(!tags.remove(&"python", &"lang"));
+        assert_eq!(tags.remove_all(&"go"), vec!["lang"]);
+        assert_eq!(tags.get(&"go"), [] as [&str; 0]);
+        assert_eq!(ContainerII::len(&tags), 2);
+        assert!(!tags.is_empty() && ContainerII::is_empty(&MultiMap::<u8, u8>::new()));
+    }
+
+    #[test]
+    fn grid_rows_columns_and_neighbors() {
+        let mut grid = small_grid();
+        assert_eq!(grid.set(3, 0, '!'), None);
+        assert_eq!(grid.to_string(), "# . .\n. . @\n");
+        assert_eq!(grid.row(1), Some(&['.', '.', '@'][..]));
+        assert_eq!(grid.row(2), None);
+        assert_eq!(grid.column(2).collect::<String>(), ".@");
+        assert_eq!(grid.column(3).count(), 0);
+        assert_eq!(grid.neighbors(0, 0), vec![(1, 0), (0, 1)]);
+        assert_eq!(grid.neighbors(1, 1), vec![(1, 0), (2, 1), (0, 1)]);
+        *grid.get_mut(1, 0).unwrap() = '+';
+        assert_eq!(grid[(1, 0)], '+');
+    }
+
+    #[test]
+    fn grid_transpose_map_and_from_rows() {
+        let grid = small_grid();
+        let flipped = grid.transpose();
+        assert_eq!((flipped.width(), flipped.height()), (2, 3));
+        assert_eq!(flipped.to_string(), "# .\n. .\n. @\n");
+        assert_eq!(flipped.transpose(), grid);
+        let walls = grid.map(|&c| c == '#');
+        assert_eq!(walls.iter().filter(|(_, &wall)| wall).count(), 1);
+        let numbers = Grid::from_rows(vec![vec![1, 2], vec![3, 4]]).unwrap();
+        assert_eq!(numbers[(1, 1)], 4);
+        assert_eq!(*numbers.get(0, 1).unwrap(), 3);
+        assert!(Grid::from_rows(vec![vec![1, 2], vec![3]]).is_none());
+    }
+
+    #[test]
+    fn both_differences_agree() {
+        let a = letters(&[(1, 'a'), (2, 'b'), (3, 'c')]);
+        let b = letters(&[(2, 'b'), (3, 'x'), (4, 'd')]);
+        // 只在 a 里：(1,a) (3,c)；只在 b 里：(3,x) (4,d)
+        assert_eq!(difference(&a, &b), 4);
+        assert_eq!(difference_ii(&a, &b), 4);
+        assert_eq!(difference_ii(&a, &a), 0);
+
+        let mut multi: MultiMap<u32, char> = MultiMap::new();
+        multi.insert(1, 'a');
+        multi.insert(1, 'z');
+        assert_eq!(difference(&a, &multi), 3);
+        assert_eq!(difference_ii(&a, &multi), 3);
+    }
+
+    #[test]
+    fn duplicate_pairs_count_once() {
+        // 重复插入不改变对称差：(1,z) 只算一次，a 里的 (1,a) 不管 multi 里有几个都不算
+        let a = letters(&[(1, 'a'), (2, 'b')]);
+        let mut multi: MultiMap<u32, char> = MultiMap::new();
+        for (key, value) in [(1, 'a'), (1, 'a'), (1, 'z'), (1, 'z'), (1, 'z')] {
+            multi.insert(key, value);
+        }
+        assert_eq!(multi.len(), 5);
+        // 只在 a 里：(2,b)；只在 multi 里：(1,z)
+        assert_eq!(difference(&a, &multi), 2);
+        assert_eq!(difference_ii(&a, &multi), 2);
+        assert_eq!(difference_ii(&multi, &a), 2);
+        let mut once = MultiMap::new();
+        once.insert(1, 'a');
+        once.insert(1, 'z');
+        assert_eq!(difference(&multi, &once), 0);
+        assert_eq!(difference_ii(&multi, &once), 0);
+    }
+
+    #[test]
+    fn grid_is_a_container_of_cells_and_rows() {
+        let grid = small_grid();
+        let mut other = grid.clone();
+        other[(1, 0)] = '#';
+        assert_eq!(difference::<(usize, usize), char, _, _>(&grid, &other), 2);
+        assert_eq!(difference::<usize, Vec<char>, _, _>(&grid, &other), 2);
+        assert_eq!(difference_ii(&grid, &other), 2);
+        assert!(Container::container(&grid, 1, vec!['.', '.', '@']));
+        assert!(Container::container(&grid, (2, 1), '@'));
+        assert!(!Container::container(&grid, (2, 1), '#'));
+    }
+}
@@ -0,0 +1,484 @@
+// 错误处理
+// main.rs 里的 enum Result<T, E> { Ok(T), Err(E) } 只有两个变体，这里把它补全成能用的版本：
+//   - Result<T, E = Error> 带 map / map_err / and_then / or_else / unwrap_or 等组合子，
+//     和标准库的 Result 可以互相转换；
+//   - Error 是库里统一的错误类型，保存最初的错误和一层层加上去的上下文（context），
+//     每一层都记下调用 context 的源码位置，合起来就是一条字符串形式的“调用栈”；
+//   - 任何实现了 std::error::Error 的错误都能通过 From 转成 Error，所以在返回 std::result::Result<T, Error>
+//     的函数里可以直接用 ? ；自定义的 Result 不能直接用 ?（Try 特征还不稳定），先 into_std() 再 ? 即可。
+// 和 anyhow 一样，Error 自己不实现 std::error::Error，否则 From<E: Error> 会和标准库的 From<T> for T 冲突。
+use std::error::Error as StdError;
+use std::fmt;
+use std::panic::Location;
+
+// 枚举中使用泛型
+// 如果函数正常运行，则最后返回一个 Ok(T)，如果函数异常运行，则返回一个 Err(E)；E 默认是本模块的 Error
+#[must_use]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Result<T, E = Error> {
+    Ok(T),
+    Err(E),
+}
+
+use self::Result::{Err, Ok};
+
+impl<T, E> Result<T, E> {
+    pub fn is_ok(&self) -> bool {
+        matches!(self, Ok(_))
+    }
+    pub fn is_err(&self) -> bool {
+        matches!(self, Err(_))
+    }
+    pub fn ok(self) -> Option<T> {
+        match self {
+            Ok(value) => Some(value),
+            Err(_) => None,
+        }
+    }
+    pub fn err(self) -> Option<E> {
+        match self {
+            Ok(_) => None,
+            Err(error) => Some(error),
+        }
+    }
+    pub fn as_ref(&self) -> Result<&T, &E> {
+        match self {
+            Ok(value) => Ok(value),
+            Err(error) => Err(error),
+        }
+    }
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> Result<U, E> {
+        match self {
+            Ok(value) => Ok(f(value)),
+            Err(error) => Err(error),
+        }
+    }
+    pub fn map_err<G, F: FnOnce(E) -> G>(self, f: F) -> Result<T, G> {
+        match self {
+            Ok(value) => Ok(value),
+            Err(error) => Err(f(error)),
+        }
+    }
+    // 成功时继续下一步，失败时直接把错误传下去
+    pub fn and_then<U, F: FnOnce(T) -> Result<U, E>>(self, f: F) -> Result<U, E> {
+        match self {
+            Ok(value) => f(value),
+            Err(error) => Err(error),
+        }
+    }
+    // 失败时换一种办法再试，错误类型可以变
+    pub fn or_else<G, F: FnOnce(E) -> Result<T, G>>(self, f: F) -> Result<T, G> {
+        match self {
+            Ok(value) => Ok(value),
+            Err(error) => f(error),
+        }
+    }
+    pub fn unwrap_or(self, default: T) -> T {
+        match self {
+            Ok(value) => value,
+            Err(_) => default,
+        }
+    }
+    pub fn unwrap_or_else<F: FnOnce(E) -> T>(self, f: F) -> T {
+        match self {
+            Ok(value) => value,
+            Err(error) => f(error),
+        }
+    }
+    pub fn into_std(self) -> std::result::Result<T, E> {
+        self.into()
+    }
+}
+
+impl<T, E: Into<Error>> Result<T, E> {
+    // 失败时给错误加一层说明，成功时什么也不做
+    #[track_caller]
+    pub fn context<C: fmt::Display>(self, context: C) -> Result<T, Error> {
+        match self {
+            Ok(value) => Ok(value),
+            Err(error) => Err(error.into().push(context.to_string(), Location::caller())),
+        }
+    }
+    // 说明需要计算（比如 format!）时用这个，只有失败时才会调用 f
+    #[track_caller]
+    pub fn with_context<C: fmt::Display, F: FnOnce() -> C>(self, f: F) -> Result<T, Error> {
+        match self {
+            Ok(value) => Ok(value),
+            Err(error) => Err(error.into().push(f().to_string(), Location::caller())),
+        }
+    }
+}
+
+impl<T, E> From<std::result::Result<T, E>> for Result<T, E> {
+    fn from(result: std::result::Result<T, E>) -> Self {
+        match result {
+            std::result::Result::Ok(value) => Ok(value),
+            std::result::Result::Err(error) => Err(error),
+        }
+    }
+}
+
+impl<T, E> From<Result<T, E>> for std::result::Result<T, E> {
+    fn from(result: Result<T, E>) -> Self {
+        match result {
+            Ok(value) => std::result::Result::Ok(value),
+            Err(error) => std::result::Result::Err(error),
+        }
+    }
+}
+
+// 一层错误信息和产生它的位置
+#[derive(Debug)]
+struct Frame {
+    message: String,
+    location: &'static Location<'static>,
+}
+
+pub struct Error {
+    // 最初的错误，用 Error::msg 创建时没有
+    source: Option<Box<dyn StdError + Send + Sync + 'static>>,
+    // 从里到外，frames[0] 是最初的错误
+    frames: Vec<Frame>,
+}
+
+impl Error {
+    #[track_caller]
+    pub fn msg<M: fmt::Display>(message: M) -> Self {
+        Error {
+            source: None,
+            frames: vec![Frame {
+                message: message.to_string(),
+                location: Location::caller(),
+            }],
+        }
+    }
+
+    #[track_caller]
+    pub fn context<C: fmt::Display>(self, context: C) -> Self {
+        self.push(context.to_string(), Location::caller())
+    }
+
+    fn push(mut self, message: String, location: &'static Location<'static>) -> Self {
+        self.frames.push(Frame { message, location });
+        self
+    }
+
+    // 从外到里的所有信息，最后一个是最初的错误。
+    // 如果最初的错误自己还有 source（比如包了一层的 io::Error），也会接着列出来
+    pub fn chain(&self) -> Vec<String> {
+        let mut messages: Vec<String> = self
+            .frames
+            .iter()
+            .rev()
+            .map(|f| f.message.clone())
+            .collect();
+        let mut source = self.source.as_deref().and_then(|e| e.source());
+        while let Some(error) = source {
+            messages.push(error.to_string());
+            source = error.source();
+        }
+        messages
+    }
+
+    // 最初的错误
+    pub fn root_cause(&self) -> String {
+        self.chain().pop().unwrap_or_default()
+    }
+
+    // 每一层是在哪里产生的，从外到里，格式是 "文件:行:列"
+    pub fn trace(&self) -> Vec<String> {
+        self.frames
+            .iter()
+            .rev()
+            .map(|f| {
+                format!(
+                    "{}:{}:{}",
+                    f.location.file(),
+                    f.location.line(),
+                    f.location.column()
+                )
+            })
+            .collect()
+    }
+
+    // 取回最初的错误，知道具体类型时可以按类型处理
+    pub fn downcast_ref<E: StdError + 'static>(&self) -> Option<&E> {
+        self.source.as_deref()?.downcast_ref()
+    }
+}
+
+// 任何标准错误都能转成 Error，这样 ? 可以直接用
+impl<E: StdError + Send + Sync + 'static> From<E> for Error {
+    #[track_caller]
+    fn from(error: E) -> Self {
+        Error {
+            frames: vec![Frame {
+                message: error.to_string(),
+                location: Location::caller(),
+            }],
+            source: Some(Box::new(error)),
+        }
+    }
+}
+
+// {} 只显示最外层，{:#} 把整条链用 ": " 连起来
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "{}", self.chain().join(": "))
+        } else {
+            write!(f, "{}", self.frames.last().unwrap().message)
+        }
+    }
+}
+
+// {:?} 显示完整的链和位置，main 返回 Err 时打印的就是这个
+impl fmt::Debug for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let chain = self.chain();
+        writeln!(f, "{}", chain[0])?;
+        if chain.len() > 1 {
+            writeln!(f, "\nCaused by:")?;
+            for (i, message) in chain[1..].iter().enumerate() {
+                writeln!(f, "    {}: {}", i, message)?;
+            }
+        }
+        writeln!(f, "\nTrace:")?;
+        for location in self.trace() {
+            writeln!(f, "    at {}", location)?;
+        }
+        std::result::Result::Ok(())
+    }
+}
+
+// 给标准库的 Result 和 Option 也加上 context，用起来和本模块的 Result 一样
+pub trait Context<T> {
+    fn context<C: fmt::Display>(self, context: C) -> std::result::Result<T, Error>;
+    fn with_context<C: fmt::Display, F: FnOnce() -> C>(self, f: F)
+        -> std::result::Result<T, Error>;
+}
+
+impl<T, E: Into<Error>> Context<T> for std::result::Result<T, E> {
+    #[track_caller]
+    fn context<C: fmt::Display>(self, context: C) -> std::result::Result<T, Error> {
+        Result::from(self).context(context).into_std()
+    }
+    #[track_caller]
+    fn with_context<C: fmt::Display, F: FnOnce() -> C>(
+        self,
+        f: F,
+    ) -> std::result::Result<T, Error> {
+        Result::from(self).with_context(f).into_std()
+    }
+}
+
+// None 没有错误信息，context 就是整条错误
+impl<T> Context<T> for Option<T> {
+    #[track_caller]
+    fn context<C: fmt::Display>(self, context: C) -> std::result::Result<T, Error> {
+        self.ok_or_else(|| Error::msg(context))
+    }
+    #[track_caller]
+    fn with_context<C: fmt::Display, F: FnOnce() -> C>(
+        self,
+        f: F,
+    ) -> std::result::Result<T, Error> {
+        self.ok_or_else(|| Error::msg(f()))
+    }
+}
+
+// 原来的 largest 直接取 list[0]、猜数字游戏用 expect 读输入，出错就 panic。
+// 改成返回错误之后，调用方可以决定是重试、用默认值还是继续往上报
+pub fn first<T: Copy>(list: &[T]) -> std::result::Result<T, Error> {
+    list.first().copied().context("list is empty")
+}
+
+pub fn parse_guess(input: &str) -> Result<u32> {
+    Result::from(input.trim().parse::<u32>())
+        .with_context(|| format!("please type a number, got {:?}", input.trim()))
+}
+
+// 读一个价格表，每行 "名字 价格"，演示 ? 和 context 一起用
+fn parse_prices(text: &str) -> std::result::Result<Vec<(String, crate::money::Money)>, Error> {
+    let mut prices = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let (name, price) = line
+            .split_once(' ')
+            .with_context(|| format!("line {}: expected \"name price\"", i + 1))?;
+        let price = crate::money::Money::parse(price, crate::money::Currency::Cny)
+            .with_context(|| format!("line {}: bad price for {}", i + 1, name))?;
+        prices.push((name.to_string(), price));
+    }
+    std::result::Result::Ok(prices)
+}
+
+fn load_prices(path: &str) -> Result<Vec<(String, crate::money::Money)>> {
+    Result::from(std::fs::read_to_string(path))
+        .context(format!("cannot open {}", path))
+        .and_then(|text| Result::from(parse_prices(&text)))
+        .context("loading price list")
+}
+
+pub(crate) fn error() {
+    // 组合子
+    let doubled: Result<i32, String> = Ok(21).map(|n| n * 2);
+    let failed: Result<i32, String> = Err("boom".to_string());
+    println!(
+        "doubled = {:?}，ok: {}，failed.map_err(len) = {:?}，failed.unwrap_or(7) = {}",
+        doubled.clone().ok(),
+        doubled.is_ok(),
+        failed.clone().map_err(|e| e.len()),
+        failed.clone().unwrap_or(7)
+    );
+    let checked = doubled.and_then(|n| {
+        if n > 40 {
+            Ok(n)
+        } else {
+            Err("small".to_string())
+        }
+    });
+    let recovered = failed.clone().or_else(|_| Ok::<i32, ()>(0));
+    println!(
+        "and_then = {:?}，or_else = {:?}，as_ref.unwrap_or_else = {}，err = {:?}，is_err: {}",
+        checked.into_std(),
+        recovered.unwrap_or(-1),
+        failed
+            .as_ref()
+            .map(|n| *n)
+            .unwrap_or_else(|e| e.len() as i32),
+        failed.clone().err(),
+        failed.is_err()
+    );
+
+    // 替代 list[0] 和 expect：错误带着上下文继续往上传
+    println!("first = {:?}", first(&[3, 1, 2]).ok());
+    if let std::result::Result::Err(error) = first::<i32>(&[]) {
+        println!("first of empty: {}", error);
+    }
+    if let Some(error) = parse_guess("forty-two").context("reading guess").err() {
+        println!("{:#}", error);
+        println!(
+            "root cause: {}，ParseIntError: {}，{} 层 trace",
+            error.root_cause(),
+            error.downcast_ref::<std::num::ParseIntError>().is_some(),
+            error.trace().len()
+        );
+    }
+
+    // 标准库的 Result 里用 ? ，不同的错误类型自动转成 Error
+    match parse_prices("咖啡 28.00\n蛋糕 abc") {
+        std::result::Result::Ok(prices) => println!("{} prices", prices.len()),
+        std::result::Result::Err(error) => println!("{:#}", error),
+    }
+    if let Some(error) = load_prices("/nonexistent/prices.txt").err() {
+        println!("{:?}", error.chain());
+    }
+    println!("{:?}", Error::msg("disk full").context("saving order"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combinators_follow_std() {
+        let doubled: Result<i32, String> = Ok(21).map(|n| n * 2);
+        assert_eq!(doubled, Ok(42));
+        let failed: Result<i32, String> = Err("boom".to_string());
+        assert_eq!(failed.clone().map(|n| n * 2), Err("boom".to_string()));
+        assert_eq!(failed.clone().map_err(|e| e.len()), Err(4));
+        assert_eq!(
+            Ok::<i32, String>(4).and_then(|n| if n > 3 {
+                Ok(n)
+            } else {
+                Err("small".to_string())
+            }),
+            Ok(4)
+        );
+        assert_eq!(failed.clone().or_else(|_| Ok::<i32, ()>(0)), Ok(0));
+        assert_eq!(failed.clone().unwrap_or(7), 7);
+        assert_eq!(
+            failed
+                .as_ref()
+                .map(|n| *n)
+                .unwrap_or_else(|e| e.len() as i32),
+            4
+        );
+        assert!(failed.is_err() && doubled.is_ok());
+        assert_eq!(doubled.ok(), Some(42));
+        assert_eq!(failed.clone().err(), Some("boom".to_string()));
+        assert_eq!(
+            failed.into_std(),
+            std::result::Result::Err("boom".to_string())
+        );
+    }
+
+    #[test]
+    fn first_of_empty_list_is_an_error() {
+        assert_eq!(first(&[3, 1, 2]).unwrap(), 3);
+        let empty = first::<i32>(&[]).unwrap_err();
+        assert_eq!(empty.to_string(), "list is empty");
+    }
+
+    #[test]
+    fn context_keeps_the_cause_and_the_trace() {
+        assert_eq!(parse_guess(" 42\n").ok(), Some(42));
+        let error = parse_guess("forty-two")
+            .context("reading guess")
+            .err()
+            .unwrap();
+        assert_eq!(error.to_string(), "reading guess");
+        assert_eq!(
+            format!("{:#}", error),
+            "reading guess: please type a number, got \"forty-two\": invalid digit found in string"
+        );
+        assert_eq!(error.root_cause(), "invalid digit found in string");
+        assert!(error.downcast_ref::<std::num::ParseIntError>().is_some());
+        assert!(error.downcast_ref::<std::io::Error>().is_none());
+        // 三层，每一层都记录了位置
+        let trace = error.trace();
+        assert_eq!(trace.len(), 3);
+        assert!(trace.iter().all(|at| at.starts_with("src/error.rs:")));
+    }
+
+    #[test]
+    fn question_mark_converts_std_errors() {
+        let prices = parse_prices("咖啡 28.00\n蛋糕 32.5").unwrap();
+        assert_eq!(prices.len(), 2);
+        let error = parse_prices("咖啡 28.00\n蛋糕 abc").unwrap_err();
+        assert_eq!(
+            format!("{:#}", error),
+            "line 2: bad price for 蛋糕: invalid amount \"abc\""
+        );
+        assert!(error.downcast_ref::<crate::money::MoneyError>().is_some());
+        let error = parse_prices("咖啡").unwrap_err();
+        assert_eq!(error.chain(), vec!["line 1: expected \"name price\""]);
+
+        // 自定义的 Result 转成标准库的再用 ?
+        let total = || -> std::result::Result<u32, Error> {
+            let a = parse_guess("40").into_std()?;
+            let b = parse_guess("2").into_std()?;
+            std::result::Result::Ok(a + b)
+        };
+        assert_eq!(total().unwrap(), 42);
+    }
+
+    #[test]
+    fn io_errors_carry_the_path() {
+        let error = load_prices("/nonexistent/prices.txt").err().unwrap();
+        assert_eq!(error.chain().len(), 3);
+        assert_eq!(
+            error.chain()[..2],
+            ["loading price list", "cannot open /nonexistent/prices.txt"]
+        );
+        assert!(error.downcast_ref::<std::io::Error>().is_some());
+    }
+
+    #[test]
+    fn debug_shows_causes_and_trace() {
+        let debug = format!("{:?}", Error::msg("disk full").context("saving order"));
+        assert!(debug.starts_with(
+            "saving order\n\nCaused by:\n    0: disk full\n\nTrace:\n    at src/error.rs:"
+        ));
+    }
+}
@@ -0,0 +1,455 @@
+// 事件
+// 给 Screen 加上输入：按键和鼠标点击。
+// 组件树里的每个组件用一条路径表示：[2] 是 Screen 的第 3 个组件，[2, 0] 是它的第 1 个子组件，以此类推。
+//   - 点击：命中测试找到点击位置最上层、最深的组件，能获得焦点的话先把焦点给它，再把事件交给它处理；
+//   - Tab / Shift+Tab：按组件树的先序遍历顺序在可获得焦点的组件之间切换，到头了从另一头开始；
+//   - 其它按键：交给获得焦点的组件。
+// 组件处理完事件会返回一个 Response，如果在这个组件的路径上注册了处理函数，就把 Response 交给处理函数。
+// 事件也可以写成脚本批量注入，不需要真的终端就能测试整个交互流程。
+use std::error::Error;
+use std::fmt;
+
+use crate::layout::login_form;
+use crate::widget::{Draw, List, Rect, Screen};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    Enter,
+    Tab,
+    BackTab,
+    Backspace,
+    Escape,
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    Key(Key),
+    // 屏幕坐标
+    Click { x: u32, y: u32 },
+}
+
+// 组件处理事件的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Response {
+    // 这个组件不关心这个事件
+    Ignored,
+    // 处理了，但没有值得通知的变化（比如移动光标）
+    Handled,
+    Clicked,
+    Toggled(bool),
+    Selected(usize),
+    Edited(String),
+}
+
+pub type Handler = Box<dyn FnMut(&Response)>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptError {
+    // 从 1 开始
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for ScriptError {}
+
+// 解析事件脚本，每行一条命令，# 开头的行是注释：
+//   click <x> <y>      在屏幕坐标 (x, y) 点击
+//   key <name>         按一个键：enter tab shift-tab backspace esc left right up down space
+//   type <text>        依次输入 text 中的每个字符（包括中间的空格）
+pub fn parse_script(script: &str) -> Result<Vec<Event>, ScriptError> {
+    let mut events = Vec::new();
+    for (index, line) in script.lines().enumerate() {
+        let error = |message: String| ScriptError {
+            line: index + 1,
+            message,
+        };
+        let line = line.trim_start();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+        match command {
+            "click" => {
+                let coordinates: Vec<&str> = rest.split_whitespace().collect();
+                let parse = |text: &str| {
+                    text.parse::<u32>()
+                        .map_err(|_| error(format!("invalid coordinate {:?}", text)))
+                };
+                match coordinates[..] {
+                    [x, y] => events.push(Event::Click {
+                        x: parse(x)?,
+                        y: parse(y)?,
+                    }),
+                    _ => return Err(error("click needs x and y".to_string())),
+                }
+            }
+            "key" => {
+                let key = match rest.trim() {
+                    "enter" => Key::Enter,
+                    "tab" => Key::Tab,
+                    "shift-tab" => Key::BackTab,
+                    "backspace" => Key::Backspace,
+                    "esc" => Key::Escape,
+                    "left" => Key::Left,
+                    "right" => Key::Right,
+                    "up" => Key::Up,
+                    "down" => Key::Down,
+                    "space" => Key::Char(' '),
+                    other => return Err(error(format!("unknown key {:?}", other))),
+                };
+                events.push(Event::Key(key));
+            }
+            "type" => events.extend(rest.chars().map(|c| Event::Key(Key::Char(c)))),
+            other => return Err(error(format!("unknown command {:?}", other))),
+        }
+    }
+    Ok(events)
+}
+
+// 命中测试：widget 包含这个点时，在 path 后面追加最深的子组件的下标。后画的组件在上层，所以倒着找
+fn hit(widget: &dyn Draw, x: u32, y: u32, path: &mut Vec<usize>) -> bool {
+    if !widget.area().contains(x, y) {
+        return false;
+    }
+    for (i, child) in widget.children().into_iter().enumerate().rev() {
+        path.push(i);
+        if hit(child, x, y, path) {
+            return true;
+        }
+        path.pop();
+    }
+    true
+}
+
+// 先序遍历，收集所有能获得焦点的组件的路径
+fn collect_focusable(widget: &dyn Draw, path: &mut Vec<usize>, out: &mut Vec<Vec<usize>>) {
+    if widget.focusable() {
+        out.push(path.clone());
+    }
+    for (i, child) in widget.children().into_iter().enumerate() {
+        path.push(i);
+        collect_focusable(child, path, out);
+        path.pop();
+    }
+}
+
+impl Screen {
+    pub fn widget(&self, path: &[usize]) -> Option<&dyn Draw> {
+        let (first, rest) = path.split_first()?;
+        let mut widget: &dyn Draw = self.components.get(*first)?.as_ref();
+        for &i in rest {
+            widget = *widget.children().get(i)?;
+        }
+        Some(widget)
+    }
+
+    pub fn widget_mut(&mut self, path: &[usize]) -> Option<&mut dyn Draw> {
+        let (first, rest) = path.split_first()?;
+        let mut widget: &mut dyn Draw = self.components.get_mut(*first)?.as_mut();
+        for &i in rest {
+            widget = widget.child_mut(i)?;
+        }
+        Some(widget)
+    }
+
+    // path 指向的组件没被屏幕和各级容器裁掉的部分，和绘制时组件拿到的区域一样
+    pub fn visible(&self, path: &[usize]) -> Option<Rect> {
+        let (first, rest) = path.split_first()?;
+        let mut widget: &dyn Draw = self.components.get(*first)?.as_ref();
+        let mut visible = Rect::new(0, 0, self.width, self.height).intersect(&widget.area());
+        for &i in rest {
+            widget = *widget.children().get(i)?;
+            visible = visible.intersect(&widget.area());
+        }
+        Some(visible)
+    }
+
+    // 点 (x, y) 处最上层、最深的组件
+    pub fn hit_test(&self, x: u32, y: u32) -> Option<Vec<usize>> {
+        for (i, component) in self.components.iter().enumerate().rev() {
+            let mut path = vec![i];
+            if hit(component.as_ref(), x, y, &mut path) {
+                return Some(path);
+            }
+        }
+        None
+    }
+
+    pub fn focusable(&self) -> Vec<Vec<usize>> {
+        let mut out = Vec::new();
+        for (i, component) in self.components.iter().enumerate() {
+            collect_focusable(component.as_ref(), &mut vec![i], &mut out);
+        }
+        out
+    }
+
+    pub fn focus(&self) -> Option<&[usize]> {
+        self.focus.as_deref()
+    }
+
+    // 路径不存在或者组件不能获得焦点时返回 false，焦点不变
+    pub fn set_focus(&mut self, path: &[usize]) -> bool {
+        match self.widget(path) {
+            Some(widget) if widget.focusable() => {
+                self.focus = Some(path.to_vec());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn focus_next(&mut self) -> bool {
+        self.move_focus(true)
+    }
+
+    pub fn focus_previous(&mut self) -> bool {
+        self.move_focus(false)
+    }
+
+    fn move_focus(&mut self, forward: bool) -> bool {
+        let order = self.focusable();
+        if order.is_empty() {
+            return false;
+        }
+        let current = self
+            .focus
+            .as_ref()
+            .and_then(|focus| order.iter().position(|path| path == focus));
+        let next = match (current, forward) {
+            (None, true) => 0,
+            (None, false) => order.len() - 1,
+            (Some(i), true) => (i + 1) % order.len(),
+            (Some(i), false) => (i + order.len() - 1) % order.len(),
+        };
+        self.focus = Some(order[next].clone());
+        true
+    }
+
+    // 在 path 指向的组件上注册处理函数，组件点击、切换、选中或编辑时调用
+    pub fn on<F: FnMut(&Response) + 'static>(&mut self, path: &[usize], handler: F) -> &mut Self {
+        self.handlers.push((path.to_vec(), Box::new(handler)));
+        self
+    }
+
+    pub fn dispatch(&mut self, event: &Event) -> Response {
+        let target = match event {
+            Event::Key(key @ (Key::Tab | Key::BackTab)) => {
+                let moved = if *key == Key::Tab {
+                    self.focus_next()
+                } else {
+                    self.focus_previous()
+                };
+                return if moved {
+                    Response::Handled
+                } else {
+                    Response::Ignored
+                };
+            }
+            Event::Key(_) => self.focus.clone(),
+            Event::Click { x, y } => {
+                let path = self.hit_test(*x, *y);
+                if let Some(path) = &path {
+                    self.set_focus(path);
+                }
+                path
+            }
+        };
+        let Some(path) = target else {
+            return Response::Ignored;
+        };
+        let visible = self.visible(&path);
+        let response = match (self.widget_mut(&path), visible) {
+            (Some(widget), Some(visible)) => widget.handle(event, visible),
+            _ => Response::Ignored,
+        };
+        if !matches!(response, Response::Ignored | Response::Handled) {
+            for (handler_path, handler) in self.handlers.iter_mut() {
+                if *handler_path == path {
+                    handler(&response);
+                }
+            }
+        }
+        response
+    }
+
+    // 依次派发脚本中的事件，返回每个事件的处理结果
+    pub fn replay(&mut self, script: &str) -> Result<Vec<Response>, ScriptError> {
+        let events = parse_script(script)?;
+        Ok(events.iter().map(|event| self.dispatch(event)).collect())
+    }
+}
+
+// 用键盘填写表单：输入用户名，勾选复选框，按下 OK
+const FILL_FORM: &str = "key tab
+type 张 san
+key backspace
+key tab
+key space
+key tab
+key enter";
+
+// 布局示例里的登录表单加一个颜色列表
+// 组件的路径：表单是 [0]，输入框 [0, 1]，复选框 [0, 2]，按钮 [0, 3, 0] 和 [0, 3, 1]，列表 [1]
+fn sign_in_screen() -> Screen {
+    let form = login_form().area(Rect::new(0, 0, 30, 7));
+    let mut screen = Screen::new(30, 10);
+    screen.add(form).add(List {
+        x: 1,
+        y: 7,
+        width: 12,
+        height: 2,
+        items: vec!["red".into(), "green".into(), "blue".into()],
+        selected: None,
+    });
+    screen
+}
+
+pub(crate) fn event() {
+    let mut screen = sign_in_screen();
+    screen.on(&[0, 3, 0], |response| println!("OK: {:?}", response));
+    let responses = screen.replay(FILL_FORM).unwrap();
+    println!("{:?}", responses);
+    println!(
+        "focus {:?}, hit (13, 6) -> {:?}",
+        screen.focus(),
+        screen.hit_test(13, 6)
+    );
+    println!("{}", parse_script("key f13").unwrap_err());
+    screen.run();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+
+    // 处理函数通过共享的日志记录发生了什么
+    fn logged(screen: &mut Screen) -> Rc<RefCell<Vec<String>>> {
+        let log: Rc<RefCell<Vec<String>>> = Rc::new(RefCell::new(Vec::new()));
+        let ok = Rc::clone(&log);
+        let cancel = Rc::clone(&log);
+        let remember = Rc::clone(&log);
+        let color = Rc::clone(&log);
+        screen
+            .on(&[0, 3, 0], move |_| ok.borrow_mut().push("ok".to_string()))
+            .on(&[0, 3, 1], move |_| {
+                cancel.borrow_mut().push("cancel".to_string())
+            })
+            .on(&[0, 2], move |response| {
+                remember.borrow_mut().push(format!("{:?}", response))
+            })
+            .on(&[1], move |response| {
+                color.borrow_mut().push(format!("{:?}", response))
+            });
+        log
+    }
+
+    #[test]
+    fn focus_order_and_hit_testing() {
+        let screen = sign_in_screen();
+        assert_eq!(
+            screen.focusable(),
+            vec![
+                vec![0, 1],
+                vec![0, 2],
+                vec![0, 3, 0],
+                vec![0, 3, 1],
+                vec![1]
+            ]
+        );
+        assert_eq!(screen.hit_test(5, 0), Some(vec![0, 0]));
+        assert_eq!(screen.hit_test(13, 6), Some(vec![0, 3, 0]));
+        assert_eq!(screen.hit_test(5, 6), Some(vec![0, 3]));
+        assert_eq!(screen.hit_test(20, 9), None);
+    }
+
+    #[test]
+    fn keyboard_script_fills_the_form() {
+        let mut screen = sign_in_screen();
+        let log = logged(&mut screen);
+        let responses = screen
+            .replay(&format!("# 注释行会被跳过\n{}", FILL_FORM))
+            .unwrap();
+        assert_eq!(responses[0], Response::Handled);
+        assert_eq!(responses[5], Response::Edited("张 san".to_string()));
+        assert_eq!(responses[6], Response::Edited("张 sa".to_string()));
+        assert_eq!(responses.last(), Some(&Response::Clicked));
+        assert_eq!(screen.focus(), Some(&[0, 3, 0][..]));
+        assert_eq!(*log.borrow(), vec!["Toggled(true)", "ok"]);
+
+        let canvas = screen.render();
+        assert_eq!(canvas.row(2), format!(" 张 sa{} ", "_".repeat(23)));
+        assert_eq!(canvas.row(4).trim_end(), " [x] remember me");
+        // 焦点在 OK 上
+        assert_eq!(
+            canvas.row(6).trim_end(),
+            format!("{}< OK > [ Cancel ]", " ".repeat(12))
+        );
+    }
+
+    #[test]
+    fn clicks_move_focus_and_cursor() {
+        let mut screen = sign_in_screen();
+        screen.replay(FILL_FORM).unwrap();
+        let log = logged(&mut screen);
+
+        // 点击：焦点跟着移动，列表点哪行选哪行
+        let responses = screen
+            .replay("click 22 6\nclick 3 8\nkey up\nkey up\nclick 0 0\nkey shift-tab")
+            .unwrap();
+        assert_eq!(
+            responses,
+            vec![
+                Response::Clicked,
+                Response::Selected(1),
+                Response::Selected(0),
+                Response::Handled,
+                Response::Ignored,
+                Response::Handled
+            ]
+        );
+        assert_eq!(*log.borrow(), vec!["cancel", "Selected(1)", "Selected(0)"]);
+        // 点在表单的空白处不会改变焦点，shift-tab 从列表回到 Cancel
+        assert_eq!(screen.focus(), Some(&[0, 3, 1][..]));
+
+        // 点击输入框移动光标，然后在中间插入
+        screen.replay("click 3 2\ntype !").unwrap();
+        let input = screen.widget(&[0, 1]).unwrap();
+        assert_eq!(input.area(), Rect::new(1, 2, 28, 1));
+        assert_eq!(
+            screen.render().row(2),
+            format!(" 张! sa{} ", "_".repeat(22))
+        );
+        // 光标在末尾时画成 |
+        screen.replay("key right\nkey right\nkey right").unwrap();
+        assert_eq!(
+            screen.render().row(2),
+            format!(" 张! sa|{} ", "_".repeat(21))
+        );
+    }
+
+    #[test]
+    fn script_errors_report_the_line() {
+        assert_eq!(
+            parse_script("click 1\n"),
+            Err(ScriptError {
+                line: 1,
+                message: "click needs x and y".to_string()
+            })
+        );
+        assert!(parse_script("# ok\nkey f13").is_err());
+    }
+}
@@ -0,0 +1,514 @@
+// 订单历史
+// 每完成一笔结账，就把小票追加到本地文件的末尾，已经写入的内容永远不会被修改（append-only）。
+// 文件是纯文本，一笔订单占一行，字段之间用 \t 分隔，字段里的 \\、\t、\n 会被转义：
+//   v1 <时间戳> <商店> <币种> <实付金额> [<SKU> <名称> <数量> <金额>]...
+// 金额一律以最小货币单位的整数保存，读回来不会有任何精度损失。
+// 每笔订单用一次 write_all 写入，程序在写到一半时崩溃最多只会留下最后一行不完整（没有换行符）的记录，
+// 读取时会忽略这一行，下一次追加之前会先把它截掉；中间的行如果损坏则报错，不会悄悄跳过。
+// 商店的 history 字段指向一个 OrderHistory 时，Goods::checkout 会先写入记录再扣库存，见 shop.rs。
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::money::{cny, Currency, Money};
+use crate::shop::{Cart, CoffeeStore, Goods, Inventory, Product, Receipt};
+
+const VERSION: &str = "v1";
+
+// 一笔订单中的一种商品
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SaleLine {
+    pub sku: String,
+    pub name: String,
+    pub quantity: u32,
+    // 原价加上只作用于这个商品的折扣，整单折扣和税不分摊到商品上
+    pub revenue: Money,
+}
+
+// 一笔已完成的订单
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sale {
+    // Unix 时间戳（秒，UTC）
+    pub timestamp: u64,
+    pub shop: String,
+    pub currency: Currency,
+    pub lines: Vec<SaleLine>,
+    // 实付金额，包含整单折扣和税
+    pub total: Money,
+}
+
+impl Sale {
+    pub fn from_receipt(receipt: &Receipt, timestamp: u64) -> Self {
+        let lines = receipt
+            .lines
+            .iter()
+            .map(|line| {
+                let revenue = receipt
+                    .adjustments
+                    .iter()
+                    .filter(|adjustment| adjustment.sku.as_deref() == Some(line.sku.as_str()))
+                    .fold(line.subtotal(), |acc, adjustment| acc + adjustment.amount);
+                SaleLine {
+                    sku: line.sku.clone(),
+                    name: line.name.clone(),
+                    quantity: line.quantity,
+                    revenue,
+                }
+            })
+            .collect();
+        Sale {
+            timestamp,
+            shop: receipt.shop.clone(),
+            currency: receipt.currency,
+            lines,
+            total: receipt.total(),
+        }
+    }
+
+    // UTC 日期，例如 "2024-03-01"
+    pub fn date(&self) -> String {
+        let (year, month, day) = civil_from_days((self.timestamp / 86_400) as i64);
+        format!("{:04}-{:02}-{:02}", year, month, day)
+    }
+
+    fn encode(&self) -> String {
+        let mut fields = vec![
+            VERSION.to_string(),
+            self.timestamp.to_string(),
+            escape(&self.shop),
+            self.currency.code().to_string(),
+            self.total.minor().to_string(),
+        ];
+        for line in &self.lines {
+            fields.push(escape(&line.sku));
+            fields.push(escape(&line.name));
+            fields.push(line.quantity.to_string());
+            fields.push(line.revenue.minor().to_string());
+        }
+        fields.join("\t")
+    }
+
+    fn decode(text: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = text.split('\t').collect();
+        if fields.len() < 5 || !(fields.len() - 5).is_multiple_of(4) {
+            return Err(format!("unexpected field count {}", fields.len()));
+        }
+        if fields[0] != VERSION {
+            return Err(format!("unknown version {:?}", fields[0]));
+        }
+        let timestamp = fields[1]
+            .parse()
+            .map_err(|_| format!("invalid timestamp {:?}", fields[1]))?;
+        let currency = Currency::from_code(fields[3])
+            .ok_or_else(|| format!("unknown currency {:?}", fields[3]))?;
+        let minor = |text: &str| {
+            text.parse::<i64>()
+                .map(|minor| Money::new(minor, currency))
+                .map_err(|_| format!("invalid amount {:?}", text))
+        };
+        let mut lines = Vec::new();
+        for chunk in fields[5..].chunks(4) {
+            lines.push(SaleLine {
+                sku: unescape(chunk[0])?,
+                name: unescape(chunk[1])?,
+                quantity: chunk[2]
+                    .parse()
+                    .map_err(|_| format!("invalid quantity {:?}", chunk[2]))?,
+                revenue: minor(chunk[3])?,
+            });
+        }
+        Ok(Sale {
+            timestamp,
+            shop: unescape(fields[2])?,
+            currency,
+            lines,
+            total: minor(fields[4])?,
+        })
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape(text: &str) -> Result<String, String> {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('\\') => unescaped.push('\\'),
+            Some('t') => unescaped.push('\t'),
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            other => return Err(format!("invalid escape {:?}", other)),
+        }
+    }
+    Ok(unescaped)
+}
+
+// 从 1970-01-01 起的天数换算成公历日期（Howard Hinnant 的 civil_from_days 算法）
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[derive(Debug)]
+pub enum HistoryError {
+    Io(io::Error),
+    // 文件中间有一行无法解析，line 从 1 开始
+    Corrupt { line: usize, reason: String },
+}
+
+impl fmt::Display for HistoryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HistoryError::Io(e) => write!(f, "{}", e),
+            HistoryError::Corrupt { line, reason } => {
+                write!(f, "corrupt order history at line {}: {}", line, reason)
+            }
+        }
+    }
+}
+
+impl Error for HistoryError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            HistoryError::Io(e) => Some(e),
+            HistoryError::Corrupt { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for HistoryError {
+    fn from(e: io::Error) -> Self {
+        HistoryError::Io(e)
+    }
+}
+
+pub struct OrderHistory {
+    path: PathBuf,
+}
+
+impl OrderHistory {
+    // 文件不存在时会在第一次写入时创建
+    pub fn open<P: AsRef<Path>>(path: P) -> Self {
+        OrderHistory {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // 用当前时间记录一笔订单
+    pub fn record(&self, receipt: &Receipt) -> io::Result<Sale> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.record_at(receipt, timestamp)
+    }
+
+    pub fn record_at(&self, receipt: &Receipt, timestamp: u64) -> io::Result<Sale> {
+        let sale = Sale::from_receipt(receipt, timestamp);
+        self.append(&sale)?;
+        Ok(sale)
+    }
+
+    // 上次写到一半的记录先截掉，新记录总是从一行的开头写起
+    pub fn append(&self, sale: &Sale) -> io::Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .write(true)
+            .truncate(false)
+            .open(&self.path)?;
+        let len = file.metadata()?.len();
+        let end = complete_len(&mut file, len)?;
+        if end < len {
+            file.set_len(end)?;
+        }
+        file.seek(SeekFrom::Start(end))?;
+        let mut record = sale.encode();
+        record.push('\n');
+        file.write_all(record.as_bytes())?;
+        // 确保数据落盘之后才算记录成功
+        file.sync_data()
+    }
+
+    // 读取全部订单，文件不存在时返回空列表
+    pub fn load(&self) -> Result<Vec<Sale>, HistoryError> {
+        // 按字节读取：中断的写入可能把一个多字节字符截成两半，整个文件就不是合法的 UTF-8 了
+        let content = match fs::read(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        // 最后一段没有换行符，说明上次写到一半就中断了，和 complete_len 一样在最后一个换行符处截断
+        let complete = match content.iter().rposition(|&byte| byte == b'\n') {
+            Some(end) => &content[..end],
+            None => &[],
+        };
+        let mut sales = Vec::new();
+        for (index, bytes) in complete.split(|&byte| byte == b'\n').enumerate() {
+            if bytes.is_empty() {
+                continue;
+            }
+            let sale = std::str::from_utf8(bytes)
+                .map_err(|e| e.to_string())
+                .and_then(Sale::decode)
+                .map_err(|reason| HistoryError::Corrupt {
+                    line: index + 1,
+                    reason,
+                })?;
+            sales.push(sale);
+        }
+        Ok(sales)
+    }
+
+    // 只用于演示和清理，正常情况下历史记录不应该被删除
+    pub fn remove(&self) -> io::Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+// 文件中最后一个换行符之后的位置，也就是完整记录的总长度
+fn complete_len(file: &mut File, len: u64) -> io::Result<u64> {
+    if len == 0 {
+        return Ok(0);
+    }
+    let mut last = [0u8; 1];
+    file.seek(SeekFrom::Start(len - 1))?;
+    file.read_exact(&mut last)?;
+    if last[0] == b'\n' {
+        return Ok(len);
+    }
+    // 只有上次写入中断时才会走到这里，整个读一遍找最后一个换行符
+    let mut content = Vec::new();
+    file.seek(SeekFrom::Start(0))?;
+    file.read_to_end(&mut content)?;
+    Ok(content
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |i| i as u64 + 1))
+}
+
+// 名字里的制表符会被转义，读回来保持原样
+fn sample_store(history: Option<OrderHistory>) -> CoffeeStore {
+    let mut store = CoffeeStore {
+        name: "壹间\t万物".to_string(),
+        coffee_type: "瑰夏".to_string(),
+        inventory: Inventory::new(),
+        history,
+    };
+    store
+        .inventory
+        .add(Product::new("C001", "瑰夏手冲", cny("68"), 10))
+        .unwrap();
+    store
+        .inventory
+        .add(Product::new("C002", "拿铁", cny("32"), 10))
+        .unwrap();
+    store
+}
+
+pub(crate) fn history() {
+    let path = std::env::temp_dir().join(format!("generics-orders-{}.log", std::process::id()));
+    let history = OrderHistory::open(&path);
+    history.remove().unwrap();
+
+    // 配置了订单历史的商店，每次结账都会追加一行记录
+    let mut store = sample_store(Some(OrderHistory::open(&path)));
+    let mut cart = Cart::new();
+    cart.add("C001", 1).unwrap();
+    cart.add("C002", 2).unwrap();
+    let receipt = store.checkout(&cart).unwrap();
+    // 2024-03-01 08:00:00 UTC
+    let sale = history.record_at(&receipt, 1_709_280_000).unwrap();
+    for sale in history.load().unwrap() {
+        println!("{} {:?} {}", sale.date(), sale.shop, sale.total);
+    }
+    println!("{} -> {}", sale.date(), history.path().display());
+    history.remove().unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pricing::{PricingRules, Rule};
+    use crate::shop::ShopError;
+
+    // 每个测试用自己的文件，并行运行时互不影响
+    fn scratch(name: &str) -> OrderHistory {
+        let path = std::env::temp_dir().join(format!(
+            "generics-orders-{}-{}.log",
+            std::process::id(),
+            name
+        ));
+        let history = OrderHistory::open(path);
+        history.remove().unwrap();
+        history
+    }
+
+    fn sample_sale(history: &OrderHistory, timestamp: u64) -> Sale {
+        let mut cart = Cart::new();
+        cart.add("C001", 1).unwrap();
+        cart.add("C002", 2).unwrap();
+        let rules = PricingRules::default()
+            .rule(Rule::FixedOff {
+                sku: Some("C002".to_string()),
+                amount: cny("4"),
+            })
+            .unwrap();
+        let receipt = sample_store(None).checkout_with(&cart, &rules).unwrap();
+        history.record_at(&receipt, timestamp).unwrap()
+    }
+
+    #[test]
+    fn records_round_trip() {
+        let history = scratch("round-trip");
+        assert!(history.load().unwrap().is_empty());
+        // 2024-03-01 08:00:00 UTC
+        let sale = sample_sale(&history, 1_709_280_000);
+        assert_eq!(sale.date(), "2024-03-01");
+        assert_eq!(sale.shop, "壹间\t万物");
+        assert_eq!(sale.lines[1].revenue, cny("60"));
+        assert_eq!(sale.total, cny("128"));
+        assert_eq!(history.load().unwrap(), vec![sale.clone()]);
+        history.remove().unwrap();
+        assert!(!history.path().exists());
+
+        assert_eq!(
+            Sale {
+                timestamp: 951_782_400,
+                ..sale
+            }
+            .date(),
+            "2000-02-29"
+        );
+    }
+
+    #[test]
+    fn half_written_record_is_dropped_before_the_next_append() {
+        let history = scratch("torn");
+        let first = sample_sale(&history, 1_709_280_000);
+        // 模拟写到一半崩溃：最后一行不完整，读取时被忽略
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(history.path())
+            .unwrap();
+        file.write_all(b"v1\t1709280001\tbroken").unwrap();
+        drop(file);
+        assert_eq!(history.load().unwrap(), vec![first.clone()]);
+
+        // 之后的追加先截掉残缺的部分，所有完整的记录都能读回来
+        let second = sample_sale(&history, 1_709_280_002);
+        let third = sample_sale(&history, 1_709_280_003);
+        assert_eq!(history.load().unwrap(), vec![first, second, third]);
+        history.remove().unwrap();
+
+        // 整个文件只有半行时截成空文件
+        fs::write(history.path(), b"v1\t17").unwrap();
+        let only = sample_sale(&history, 1_709_280_004);
+        assert_eq!(history.load().unwrap(), vec![only]);
+        history.remove().unwrap();
+    }
+
+    #[test]
+    fn torn_multibyte_character_is_dropped() {
+        let history = scratch("torn-utf8");
+        let first = sample_sale(&history, 1_709_280_000);
+        // “咖啡”是 6 个字节，只写进去了前 4 个，“啡”被截成两半
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(history.path())
+            .unwrap();
+        file.write_all(&"咖啡".as_bytes()[..4]).unwrap();
+        drop(file);
+        assert_eq!(history.load().unwrap(), vec![first.clone()]);
+
+        let second = sample_sale(&history, 1_709_280_001);
+        assert_eq!(history.load().unwrap(), vec![first, second]);
+        history.remove().unwrap();
+    }
+
+    #[test]
+    fn corrupt_middle_line_is_an_error() {
+        let history = scratch("corrupt");
+        let sale = sample_sale(&history, 1_709_280_000);
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(history.path())
+            .unwrap();
+        file.write_all(b"v1\t1709280001\tbroken\n").unwrap();
+        drop(file);
+        history.append(&sale).unwrap();
+        match history.load() {
+            Err(HistoryError::Corrupt { line, .. }) => assert_eq!(line, 2),
+            other => panic!("expected corrupt history, got {:?}", other),
+        }
+        history.remove().unwrap();
+    }
+
+    #[test]
+    fn checkout_writes_the_log_before_taking_stock() {
+        let history = scratch("checkout");
+        let mut store = sample_store(Some(OrderHistory::open(history.path())));
+
+        // 结账失败时不写入任何记录
+        let mut cart = Cart::new();
+        cart.add("C404", 1).unwrap();
+        assert_eq!(
+            store.checkout(&cart),
+            Err(ShopError::UnknownSku("C404".to_string()))
+        );
+        assert!(!history.path().exists());
+
+        cart.remove("C404", 1).add("C001", 2).unwrap();
+        let receipt = store.checkout(&cart).unwrap();
+        let sales = history.load().unwrap();
+        assert_eq!(sales.len(), 1);
+        assert_eq!(sales[0].total, receipt.total());
+        assert_eq!(store.find("C001").unwrap().stock, 8);
+        history.remove().unwrap();
+    }
+
+    #[test]
+    fn failed_log_write_keeps_the_stock() {
+        // 把目录当成文件打开一定会失败
+        let mut store = sample_store(Some(OrderHistory::open(std::env::temp_dir())));
+        let mut cart = Cart::new();
+        cart.add("C001", 2).unwrap();
+        assert!(matches!(store.checkout(&cart), Err(ShopError::History(_))));
+        assert_eq!(store.find("C001").unwrap().stock, 10);
+    }
+}
@@ -0,0 +1,587 @@
+// 迭代器适配器
+// 教程里的 Iterator 只声明了 Item 和 next（定义从 main.rs 移到了这里），这里在它上面实现一套适配器：
+//   map filter zip chain windows chunks peekable step_by dedup interleave
+// 和标准库一样，适配器都是惰性的，只在调用 next 时才去拉取上游的元素。
+// into_std / from_std 负责和 std::iter::Iterator 互相转换，演示中把每个适配器的结果和标准库（或等价写法）对比。
+use std::collections::VecDeque;
+// 本模块定义了自己的 Iterator，会遮住 prelude 里的同名特征，标准库的迭代器方法需要换个名字引入
+use std::iter::Iterator as StdIterator;
+
+//     关联类型
+// 以下是标准库的迭代器特征Iterator，有一个Item关联类型用于替代遍历的值的类型
+// next方法也返回一个Item类型，不过是使用Option枚举进行了包裹
+// Self用来替代当前调用者的具体类型(self是用来替代该实例
+pub trait Iterator {
+    type Item;
+    fn next(&mut self) -> Option<Self::Item>;
+
+    fn map<B, F>(self, f: F) -> Map<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(Self::Item) -> B,
+    {
+        Map { iter: self, f }
+    }
+    fn filter<P>(self, predicate: P) -> Filter<Self, P>
+    where
+        Self: Sized,
+        P: FnMut(&Self::Item) -> bool,
+    {
+        Filter {
+            iter: self,
+            predicate,
+        }
+    }
+    // 任意一边结束就结束
+    fn zip<U: Iterator>(self, other: U) -> Zip<Self, U>
+    where
+        Self: Sized,
+    {
+        Zip { a: self, b: other }
+    }
+    fn chain<U: Iterator<Item = Self::Item>>(self, other: U) -> Chain<Self, U>
+    where
+        Self: Sized,
+    {
+        Chain {
+            first: Some(self),
+            second: other,
+        }
+    }
+    // 长度为 size 的滑动窗口，和 slice::windows 一样，元素不足 size 个时一个窗口也没有
+    fn windows(self, size: usize) -> Windows<Self>
+    where
+        Self: Sized,
+        Self::Item: Clone,
+    {
+        assert!(size != 0, "window size must be non-zero");
+        Windows {
+            iter: self,
+            size,
+            window: VecDeque::with_capacity(size),
+        }
+    }
+    // 每 size 个一组，和 slice::chunks 一样，最后一组可能不满
+    fn chunks(self, size: usize) -> Chunks<Self>
+    where
+        Self: Sized,
+    {
+        assert!(size != 0, "chunk size must be non-zero");
+        Chunks { iter: self, size }
+    }
+    fn peekable(self) -> Peekable<Self>
+    where
+        Self: Sized,
+    {
+        Peekable {
+            iter: self,
+            peeked: None,
+        }
+    }
+    // 先给出第一个元素，之后每隔 step 个给出一个
+    fn step_by(self, step: usize) -> StepBy<Self>
+    where
+        Self: Sized,
+    {
+        assert!(step != 0, "step must be non-zero");
+        StepBy {
+            iter: self,
+            step,
+            first: true,
+        }
+    }
+    // 去掉相邻的重复元素，和 Vec::dedup 一样
+    fn dedup(self) -> Dedup<Self>
+    where
+        Self: Sized,
+        Self::Item: PartialEq,
+    {
+        Dedup {
+            iter: self,
+            pending: None,
+        }
+    }
+    // 两边轮流取，一边用完后继续取另一边剩下的
+    fn interleave<U: Iterator<Item = Self::Item>>(self, other: U) -> Interleave<Self, U>
+    where
+        Self: Sized,
+    {
+        Interleave {
+            a: self,
+            b: other,
+            from_a: true,
+        }
+    }
+    fn collect_vec(mut self) -> Vec<Self::Item>
+    where
+        Self: Sized,
+    {
+        let mut items = Vec::new();
+        while let Some(item) = self.next() {
+            items.push(item);
+        }
+        items
+    }
+    // 转成标准库的迭代器，就能用 for 循环、collect 和标准库的其它适配器
+    fn into_std(self) -> IntoStd<Self>
+    where
+        Self: Sized,
+    {
+        IntoStd(self)
+    }
+}
+
+// 可变引用也是迭代器，这样用完一部分之后还能继续使用原来的迭代器
+impl<I: Iterator + ?Sized> Iterator for &mut I {
+    type Item = I::Item;
+    fn next(&mut self) -> Option<I::Item> {
+        (**self).next()
+    }
+}
+
+// 从标准库的迭代器（或者任何 IntoIterator）得到本模块的迭代器
+pub fn from_std<I: IntoIterator>(iter: I) -> FromStd<I::IntoIter> {
+    FromStd(iter.into_iter())
+}
+
+pub struct FromStd<I>(I);
+
+impl<I: StdIterator> Iterator for FromStd<I> {
+    type Item = I::Item;
+    fn next(&mut self) -> Option<I::Item> {
+        self.0.next()
+    }
+}
+
+pub struct IntoStd<I>(I);
+
+impl<I: Iterator> StdIterator for IntoStd<I> {
+    type Item = I::Item;
+    fn next(&mut self) -> Option<I::Item> {
+        self.0.next()
+    }
+}
+
+pub struct Map<I, F> {
+    iter: I,
+    f: F,
+}
+
+impl<B, I: Iterator, F: FnMut(I::Item) -> B> Iterator for Map<I, F> {
+    type Item = B;
+    fn next(&mut self) -> Option<B> {
+        self.iter.next().map(&mut self.f)
+    }
+}
+
+pub struct Filter<I, P> {
+    iter: I,
+    predicate: P,
+}
+
+impl<I: Iterator, P: FnMut(&I::Item) -> bool> Iterator for Filter<I, P> {
+    type Item = I::Item;
+    fn next(&mut self) -> Option<I::Item> {
+        while let Some(item) = self.iter.next() {
+            if (self.predicate)(&item) {
+                return Some(item);
+            }
+        }
+        None
+    }
+}
+
+pub struct Zip<A, B> {
+    a: A,
+    b: B,
+}
+
+impl<A: Iterator, B: Iterator> Iterator for Zip<A, B> {
+    type Item = (A::Item, B::Item);
+    fn next(&mut self) -> Option<Self::Item> {
+        let a = self.a.next()?;
+        let b = self.b.next()?;
+        Some((a, b))
+    }
+}
+
+pub struct Chain<A, B> {
+    // 第一个用完后置为 None，不再调用它的 next
+    first: Option<A>,
+    second: B,
+}
+
+impl<A: Iterator, B: Iterator<Item = A::Item>> Iterator for Chain<A, B> {
+    type Item = A::Item;
+    fn next(&mut self) -> Option<A::Item> {
+        if let Some(first) = &mut self.first {
+            match first.next() {
+                Some(item) => return Some(item),
+                None => self.first = None,
+            }
+        }
+        self.second.next()
+    }
+}
+
+pub struct Windows<I: Iterator> {
+    iter: I,
+    size: usize,
+    window: VecDeque<I::Item>,
+}
+
+impl<I: Iterator> Iterator for Windows<I>
+where
+    I::Item: Clone,
+{
+    type Item = Vec<I::Item>;
+    fn next(&mut self) -> Option<Vec<I::Item>> {
+        if self.window.len() == self.size {
+            self.window.pop_front();
+        }
+        while self.window.len() < self.size {
+            self.window.push_back(self.iter.next()?);
+        }
+        Some(self.window.iter().cloned().collect())
+    }
+}
+
+pub struct Chunks<I> {
+    iter: I,
+    size: usize,
+}
+
+impl<I: Iterator> Iterator for Chunks<I> {
+    type Item = Vec<I::Item>;
+    fn next(&mut self) -> Option<Vec<I::Item>> {
+        let mut chunk = Vec::with_capacity(self.size);
+        while chunk.len() < self.size {
+            match self.iter.next() {
+                Some(item) => chunk.push(item),
+                None => break,
+            }
+        }
+        if chunk.is_empty() {
+            None
+        } else {
+            Some(chunk)
+        }
+    }
+}
+
+pub struct Peekable<I: Iterator> {
+    iter: I,
+    // Some(None) 表示已经看过了，上游没有更多元素
+    peeked: Option<Option<I::Item>>,
+}
+
+impl<I: Iterator> Peekable<I> {
+    pub fn peek(&mut self) -> Option<&I::Item> {
+        let iter = &mut self.iter;
+        self.peeked.get_or_insert_with(|| iter.next()).as_ref()
+    }
+    // 下一个元素满足条件时才取出
+    pub fn next_if(&mut self, f: impl FnOnce(&I::Item) -> bool) -> Option<I::Item> {
+        match self.next() {
+            Some(item) if f(&item) => Some(item),
+            other => {
+                self.peeked = Some(other);
+                None
+            }
+        }
+    }
+}
+
+impl<I: Iterator> Iterator for Peekable<I> {
+    type Item = I::Item;
+    fn next(&mut self) -> Option<I::Item> {
+        match self.peeked.take() {
+            Some(peeked) => peeked,
+            None => self.iter.next(),
+        }
+    }
+}
+
+pub struct StepBy<I> {
+    iter: I,
+    step: usize,
+    first: bool,
+}
+
+impl<I: Iterator> Iterator for StepBy<I> {
+    type Item = I::Item;
+    fn next(&mut self) -> Option<I::Item> {
+        if self.first {
+            self.first = false;
+        } else {
+            for _ in 1..self.step {
+                self.iter.next()?;
+            }
+        }
+        self.iter.next()
+    }
+}
+
+pub struct Dedup<I: Iterator> {
+    iter: I,
+    // 上一次向后多读的那个和前面不同的元素
+    pending: Option<I::Item>,
+}
+
+impl<I: Iterator> Iterator for Dedup<I>
+where
+    I::Item: PartialEq,
+{
+    type Item = I::Item;
+    fn next(&mut self) -> Option<I::Item> {
+        let current = match self.pending.take() {
+            Some(item) => item,
+            None => self.iter.next()?,
+        };
+        while let Some(item) = self.iter.next() {
+            if item != current {
+                self.pending = Some(item);
+                break;
+            }
+        }
+        Some(current)
+    }
+}
+
+pub struct Interleave<A, B> {
+    a: A,
+    b: B,
+    from_a: bool,
+}
+
+impl<A: Iterator, B: Iterator<Item = A::Item>> Iterator for Interleave<A, B> {
+    type Item = A::Item;
+    fn next(&mut self) -> Option<A::Item> {
+        self.from_a = !self.from_a;
+        if self.from_a {
+            self.b.next().or_else(|| self.a.next())
+        } else {
+            self.a.next().or_else(|| self.b.next())
+        }
+    }
+}
+
+// 直接实现本模块的 Iterator，不经过标准库
+struct Countdown(u32);
+
+impl Iterator for Countdown {
+    type Item = u32;
+    fn next(&mut self) -> Option<u32> {
+        if self.0 == 0 {
+            None
+        } else {
+            self.0 -= 1;
+            Some(self.0 + 1)
+        }
+    }
+}
+
+// 按相同的元素分组，顺便演示 peek / next_if
+fn run_lengths(text: &str) -> Vec<(char, usize)> {
+    let mut chars = from_std(text.chars()).peekable();
+    let mut groups = Vec::new();
+    while let Some(c) = chars.next() {
+        let mut n = 1;
+        while chars.next_if(|&next| next == c).is_some() {
+            n += 1;
+        }
+        groups.push((c, n));
+    }
+    groups
+}
+
+pub(crate) fn iter() {
+    let numbers = || from_std(1..=10);
+    println!("平方: {:?}", numbers().map(|n| n * n).collect_vec());
+    println!(
+        "3 的倍数: {:?}",
+        numbers().filter(|n| n % 3 == 0).collect_vec()
+    );
+    println!(
+        "zip: {:?}",
+        numbers().zip(from_std("abc".chars())).collect_vec()
+    );
+    println!(
+        "chain: {:?}",
+        from_std([1, 2]).chain(from_std([100, 200])).collect_vec()
+    );
+    println!("windows(3): {:?}", from_std(1..=5).windows(3).collect_vec());
+    println!("chunks(4): {:?}", numbers().chunks(4).collect_vec());
+    println!("step_by(3): {:?}", numbers().step_by(3).collect_vec());
+    println!(
+        "dedup: {:?}",
+        from_std([1, 1, 2, 3, 3, 3, 1, 4, 4]).dedup().collect_vec()
+    );
+    println!(
+        "interleave: {:?}",
+        from_std([1, 3, 5, 7, 9])
+            .interleave(from_std([2, 4]))
+            .collect_vec()
+    );
+    let mut letters = from_std("xyz".chars()).peekable();
+    println!(
+        "peek: {:?}，分组: {:?}",
+        letters.peek(),
+        run_lengths("aaabccdd")
+    );
+
+    // 组合使用，再转回标准库的迭代器：120 + 80 + 40
+    let total: u32 = Countdown(12)
+        .filter(|n| n % 2 == 0)
+        .map(|n| n * 10)
+        .step_by(2)
+        .into_std()
+        .sum();
+    println!("total: {}", total);
+
+    // &mut 也是迭代器：先取前两组，再把剩下的交给别的适配器
+    let mut countdown = Countdown(7);
+    let first = (&mut countdown).chunks(2).zip(from_std(0..2)).collect_vec();
+    println!(
+        "前两组 {:?}，剩下 {:?}",
+        first,
+        countdown.into_std().collect::<Vec<_>>()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbers() -> Vec<i32> {
+        (1..=10).collect()
+    }
+
+    fn ours() -> FromStd<std::vec::IntoIter<i32>> {
+        from_std(numbers())
+    }
+
+    fn std() -> std::vec::IntoIter<i32> {
+        numbers().into_iter()
+    }
+
+    #[test]
+    fn basic_adapters_match_std() {
+        assert_eq!(
+            ours().map(|n| n * n).collect_vec(),
+            std().map(|n| n * n).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            ours().filter(|n| n % 3 == 0).collect_vec(),
+            std().filter(|n| n % 3 == 0).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            ours().zip(from_std("abc".chars())).collect_vec(),
+            std().zip("abc".chars()).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            ours().chain(from_std([100, 200])).collect_vec(),
+            std().chain([100, 200]).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn windows_chunks_and_step_by_match_std() {
+        let numbers = numbers();
+        for size in 1..=11 {
+            assert_eq!(
+                ours().windows(size).collect_vec(),
+                numbers
+                    .windows(size)
+                    .map(<[i32]>::to_vec)
+                    .collect::<Vec<_>>()
+            );
+            assert_eq!(
+                ours().chunks(size).collect_vec(),
+                numbers
+                    .chunks(size)
+                    .map(<[i32]>::to_vec)
+                    .collect::<Vec<_>>()
+            );
+            assert_eq!(
+                ours().step_by(size).collect_vec(),
+                std().step_by(size).collect::<Vec<_>>()
+            );
+        }
+        assert_eq!(from_std(Vec::<i32>::new()).chunks(3).collect_vec().len(), 0);
+    }
+
+    #[test]
+    fn dedup_matches_vec_dedup() {
+        let repeated = [1, 1, 2, 3, 3, 3, 1, 4, 4];
+        let mut expected = repeated.to_vec();
+        expected.dedup();
+        assert_eq!(from_std(repeated).dedup().collect_vec(), expected);
+        assert_eq!(from_std(Vec::<u8>::new()).dedup().collect_vec(), vec![]);
+    }
+
+    #[test]
+    fn interleave_appends_the_longer_tail() {
+        // 标准库没有 interleave，用 zip 加上剩余部分拼出同样的结果
+        let (left, right) = ([1, 3, 5, 7, 9], [2, 4]);
+        let expected: Vec<i32> = left
+            .iter()
+            .zip(right.iter())
+            .flat_map(|(a, b)| [*a, *b])
+            .chain(left[right.len()..].iter().copied())
+            .collect();
+        assert_eq!(
+            from_std(left).interleave(from_std(right)).collect_vec(),
+            expected
+        );
+        assert_eq!(
+            from_std(right).interleave(from_std(left)).collect_vec(),
+            vec![2, 1, 4, 3, 5, 7, 9]
+        );
+    }
+
+    #[test]
+    fn peekable_matches_std() {
+        let mut ours_peek = from_std("ab".chars()).peekable();
+        let mut std_peek = "ab".chars().peekable();
+        assert_eq!(ours_peek.peek(), std_peek.peek());
+        assert_eq!(ours_peek.next_if(|&c| c == 'b'), None);
+        assert_eq!(ours_peek.next_if(|&c| c == 'a'), std_peek.next_if_eq(&'a'));
+        assert_eq!(ours_peek.next(), std_peek.next());
+        assert_eq!(ours_peek.peek(), None);
+        assert_eq!(
+            run_lengths("aaabccdd"),
+            vec![('a', 3), ('b', 1), ('c', 2), ('d', 2)]
+        );
+        assert!(run_lengths("").is_empty());
+    }
+
+    #[test]
+    fn chained_adapters_match_std() {
+        let total: u32 = Countdown(12)
+            .filter(|n| n % 2 == 0)
+            .map(|n| n * 10)
+            .step_by(2)
+            .into_std()
+            .sum();
+        let std_total: u32 = (1..=12u32)
+            .rev()
+            .filter(|n| n % 2 == 0)
+            .map(|n| n * 10)
+            .step_by(2)
+            .sum();
+        assert_eq!(total, 240);
+        assert_eq!(total, std_total);
+    }
+
+    #[test]
+    fn mut_reference_is_an_iterator() {
+        let mut countdown = Countdown(7);
+        assert_eq!(
+            (&mut countdown).chunks(2).zip(from_std(0..2)).collect_vec(),
+            vec![(vec![7, 6], 0), (vec![5, 4], 1)]
+        );
+        // zip 在发现 from_std(0..2) 用完之前已经多取了一组 [3, 2]，这点和标准库一样
+        assert_eq!(countdown.into_std().collect::<Vec<_>>(), vec![1]);
+    }
+}
@@ -0,0 +1,547 @@
+// 布局
+// 原来 Screen 里的组件只是平铺在一个 Vec 里，位置要手工指定。Stack 是一个容器组件：
+// 它把子组件沿一个方向（横向 Row 或纵向 Column）依次排开，根据内边距、间距、对齐方式和每个子组件的尺寸约束
+// 算出每个子组件的矩形，再调用子组件的 set_area 把它放过去。Stack 本身也实现了 Draw，所以可以互相嵌套。
+//
+// 主轴（排列方向）上的尺寸计算分两步：
+//   1. 每个子组件先分到 min；
+//   2. 剩下的空间按 fill 权重分给可以伸展的子组件，到 max 为止，超出 max 的部分再分给其它子组件。
+// 空间不够放下所有 min 时不会压缩，超出容器的部分在绘制时被裁掉。
+// 交叉轴上，Stretch 占满整个宽度（或高度），其它对齐方式使用子组件加入时原来的大小。
+use crate::widget::{
+    display_width, Button, Checkbox, Draw, Label, List, Rect, Region, Screen, TextInput,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Row,
+    Column,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Start,
+    Center,
+    End,
+    // 只对交叉轴有效，主轴上和 Start 一样
+    Stretch,
+}
+
+impl Align {
+    // 把 size 放进 available 里，返回起始偏移
+    fn offset(self, available: u32, size: u32) -> u32 {
+        let free = available.saturating_sub(size);
+        match self {
+            Align::Start | Align::Stretch => 0,
+            Align::Center => free / 2,
+            Align::End => free,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Padding {
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+    pub left: u32,
+}
+
+impl Padding {
+    pub fn all(n: u32) -> Self {
+        Padding {
+            top: n,
+            right: n,
+            bottom: n,
+            left: n,
+        }
+    }
+    // 上下 vertical，左右 horizontal
+    pub fn symmetric(vertical: u32, horizontal: u32) -> Self {
+        Padding {
+            top: vertical,
+            right: horizontal,
+            bottom: vertical,
+            left: horizontal,
+        }
+    }
+    fn shrink(&self, area: Rect) -> Rect {
+        let horizontal = self.left.saturating_add(self.right);
+        let vertical = self.top.saturating_add(self.bottom);
+        Rect::new(
+            area.x + self.left.min(area.width),
+            area.y + self.top.min(area.height),
+            area.width.saturating_sub(horizontal),
+            area.height.saturating_sub(vertical),
+        )
+    }
+}
+
+// 子组件在主轴上的尺寸约束
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Constraint {
+    pub min: u32,
+    pub max: Option<u32>,
+    // 伸展权重，0 表示不伸展
+    pub fill: u32,
+}
+
+impl Constraint {
+    // 固定大小
+    pub fn fixed(size: u32) -> Self {
+        Constraint {
+            min: size,
+            max: Some(size),
+            fill: 0,
+        }
+    }
+    // 按权重分配剩余空间
+    pub fn fill(weight: u32) -> Self {
+        Constraint {
+            min: 0,
+            max: None,
+            fill: weight,
+        }
+    }
+    pub fn with_min(mut self, min: u32) -> Self {
+        self.min = min;
+        self
+    }
+    pub fn with_max(mut self, max: u32) -> Self {
+        self.max = Some(max);
+        self
+    }
+    // max 比 min 还小时以 min 为准
+    fn limit(&self) -> u32 {
+        self.max.map_or(u32::MAX, |max| max.max(self.min))
+    }
+}
+
+// 在 available 的空间里给每个约束分配大小
+// 尺寸的合计都用饱和加法，几个很大的 min 加起来也只是“放不下”，不会溢出
+pub fn distribute(available: u32, constraints: &[Constraint]) -> Vec<u32> {
+    let mut sizes: Vec<u32> = constraints.iter().map(|c| c.min).collect();
+    let mut remaining = available.saturating_sub(saturating_sum(&sizes));
+    let can_grow =
+        |i: usize, sizes: &[u32]| constraints[i].fill > 0 && sizes[i] < constraints[i].limit();
+    let mut growing: Vec<usize> = (0..constraints.len())
+        .filter(|&i| can_grow(i, &sizes))
+        .collect();
+    while remaining > 0 && !growing.is_empty() {
+        let total: u64 = growing.iter().map(|&i| constraints[i].fill as u64).sum();
+        let mut shares: Vec<u32> = growing
+            .iter()
+            .map(|&i| (remaining as u64 * constraints[i].fill as u64 / total) as u32)
+            .collect();
+        // 除不尽的零头从前往后每个多给一格
+        let mut leftover = remaining - shares.iter().sum::<u32>();
+        for share in shares.iter_mut() {
+            if leftover == 0 {
+                break;
+            }
+            *share += 1;
+            leftover -= 1;
+        }
+        let mut given = 0;
+        for (share, &i) in shares.iter().zip(&growing) {
+            let add = (*share).min(constraints[i].limit() - sizes[i]);
+            sizes[i] += add;
+            given += add;
+        }
+        remaining -= given;
+        growing.retain(|&i| can_grow(i, &sizes));
+    }
+    sizes
+}
+
+fn saturating_sum(sizes: &[u32]) -> u32 {
+    sizes.iter().fold(0, |sum, &size| sum.saturating_add(size))
+}
+
+struct Child {
+    widget: Box<dyn Draw>,
+    constraint: Constraint,
+    // 加入时的大小，交叉轴不拉伸时使用
+    preferred: Rect,
+}
+
+pub struct Stack {
+    area: Rect,
+    direction: Direction,
+    padding: Padding,
+    gap: u32,
+    // 主轴上的对齐方式，只有子组件都不伸展、还有剩余空间时才有用
+    justify: Align,
+    // 交叉轴上的对齐方式
+    align: Align,
+    children: Vec<Child>,
+}
+
+impl Stack {
+    pub fn new(direction: Direction) -> Self {
+        Stack {
+            area: Rect::default(),
+            direction,
+            padding: Padding::default(),
+            gap: 0,
+            justify: Align::Start,
+            align: Align::Stretch,
+            children: Vec::new(),
+        }
+    }
+    // 横向排列
+    pub fn row() -> Self {
+        Stack::new(Direction::Row)
+    }
+    // 纵向排列
+    pub fn column() -> Self {
+        Stack::new(Direction::Column)
+    }
+    pub fn area(mut self, area: Rect) -> Self {
+        self.set_area(area);
+        self
+    }
+    pub fn padding(mut self, padding: Padding) -> Self {
+        self.padding = padding;
+        self.layout();
+        self
+    }
+    pub fn gap(mut self, gap: u32) -> Self {
+        self.gap = gap;
+        self.layout();
+        self
+    }
+    pub fn justify(mut self, justify: Align) -> Self {
+        self.justify = justify;
+        self.layout();
+        self
+    }
+    pub fn align(mut self, align: Align) -> Self {
+        self.align = align;
+        self.layout();
+        self
+    }
+    // 按子组件原来的大小固定占位
+    pub fn child<D: Draw + 'static>(self, widget: D) -> Self {
+        let area = widget.area();
+        let size = match self.direction {
+            Direction::Row => area.width,
+            Direction::Column => area.height,
+        };
+        self.child_with(widget, Constraint::fixed(size))
+    }
+    pub fn child_with<D: Draw + 'static>(mut self, widget: D, constraint: Constraint) -> Self {
+        self.children.push(Child {
+            preferred: widget.area(),
+            widget: Box::new(widget),
+            constraint,
+        });
+        self.layout();
+        self
+    }
+    pub fn get(&self, index: usize) -> Option<&dyn Draw> {
+        self.children.get(index).map(|child| child.widget.as_ref())
+    }
+    // 每个子组件计算出来的矩形（屏幕坐标）
+    pub fn areas(&self) -> Vec<Rect> {
+        self.children.iter().map(|c| c.widget.area()).collect()
+    }
+
+    // 重新计算所有子组件的位置
+    pub fn layout(&mut self) {
+        let inner = self.padding.shrink(self.area);
+        let (main, cross) = match self.direction {
+            Direction::Row => (inner.width, inner.height),
+            Direction::Column => (inner.height, inner.width),
+        };
+        let gaps = self
+            .gap
+            .saturating_mul((self.children.len() as u32).saturating_sub(1));
+        let constraints: Vec<Constraint> = self.children.iter().map(|c| c.constraint).collect();
+        let sizes = distribute(main.saturating_sub(gaps), &constraints);
+        let used = saturating_sum(&sizes).saturating_add(gaps);
+        let mut position = self.justify.offset(main, used);
+        for (child, size) in self.children.iter_mut().zip(sizes) {
+            let preferred = match self.direction {
+                Direction::Row => child.preferred.height,
+                Direction::Column => child.preferred.width,
+            };
+            let cross_size = if self.align == Align::Stretch {
+                cross
+            } else {
+                preferred.min(cross)
+            };
+            let cross_offset = self.align.offset(cross, cross_size);
+            let area = match self.direction {
+                Direction::Row => Rect::new(
+                    inner.x.saturating_add(position),
+                    inner.y + cross_offset,
+                    size,
+                    cross_size,
+                ),
+                Direction::Column => Rect::new(
+                    inner.x + cross_offset,
+                    inner.y.saturating_add(position),
+                    cross_size,
+                    size,
+                ),
+            };
+            child.widget.set_area(area);
+            position = position.saturating_add(size).saturating_add(self.gap);
+        }
+    }
+}
+
+impl Draw for Stack {
+    fn area(&self) -> Rect {
+        self.area
+    }
+    fn set_area(&mut self, area: Rect) {
+        self.area = area;
+        self.layout();
+    }
+    // 子组件的矩形是屏幕坐标，要换算成相对于容器的坐标
+    fn draw(&self, region: &mut Region) {
+        for child in &self.children {
+            let area = child.widget.area();
+            let local = Rect::new(
+                area.x.saturating_sub(self.area.x),
+                area.y.saturating_sub(self.area.y),
+                area.width,
+                area.height,
+            );
+            child.widget.draw(&mut region.sub(local));
+        }
+    }
+    fn children(&self) -> Vec<&dyn Draw> {
+        self.children.iter().map(|c| c.widget.as_ref()).collect()
+    }
+    fn child_mut(&mut self, index: usize) -> Option<&mut dyn Draw> {
+        match self.children.get_mut(index) {
+            Some(child) => Some(child.widget.as_mut()),
+            None => None,
+        }
+    }
+}
+
+fn label(text: &str, width: u32, height: u32) -> Label {
+    Label {
+        x: 0,
+        y: 0,
+        width,
+        height,
+        text: text.to_string(),
+    }
+}
+
+// 单行的 [ text ] 按钮，宽度按显示宽度算，中文按钮也刚好放得下
+pub(crate) fn button(text: &str) -> Button {
+    Button {
+        x: 0,
+        y: 0,
+        width: display_width(text) as u32 + 4,
+        height: 1,
+        label: text.to_string(),
+    }
+}
+
+// 登录表单：标题、输入框、复选框，按钮行占满剩下的高度并靠右
+// event.rs 在这个表单上演示事件处理，子组件的顺序就是那边用到的路径
+pub(crate) fn login_form() -> Stack {
+    Stack::column()
+        .padding(Padding::symmetric(0, 1))
+        .gap(1)
+        .child(label("用户登录", 8, 1))
+        .child(TextInput::new(0, 0, 1, "username"))
+        .child(Checkbox {
+            x: 0,
+            y: 0,
+            width: 1,
+            height: 1,
+            label: "remember me".to_string(),
+            checked: false,
+        })
+        .child_with(
+            Stack::row()
+                .gap(1)
+                .justify(Align::End)
+                .align(Align::Center)
+                .child(button("OK"))
+                .child(button("Cancel")),
+            Constraint::fill(1).with_min(1),
+        )
+}
+
+// 左边最多 10 列的侧边栏，右边是登录表单
+fn mail_screen() -> Screen {
+    let sidebar = List {
+        x: 0,
+        y: 0,
+        width: 10,
+        height: 1,
+        items: vec!["inbox".into(), "sent".into(), "trash".into()],
+        selected: Some(0),
+    };
+    let mut screen = Screen::new(44, 10);
+    screen.add(
+        Stack::row()
+            .gap(1)
+            .child_with(sidebar, Constraint::fill(1).with_max(10))
+            .child_with(login_form(), Constraint::fill(1))
+            .area(Rect::new(0, 0, 44, 8)),
+    );
+    screen
+}
+
+pub(crate) fn layout() {
+    // fill 按权重分配，max 封顶之后多出来的再分给别人
+    let constraints = [
+        Constraint::fill(1).with_max(3),
+        Constraint::fill(2),
+        Constraint::fixed(4),
+    ];
+    println!("distribute(20) = {:?}", distribute(20, &constraints));
+
+    // 横向：内边距 1、间距 2，中间的组件伸展
+    let row = Stack::row()
+        .padding(Padding::all(1))
+        .gap(2)
+        .child(label("A", 3, 1))
+        .child_with(label("B", 1, 1), Constraint::fill(1).with_min(5))
+        .child(label("C", 4, 1))
+        .area(Rect::new(2, 3, 30, 5));
+    for area in row.areas() {
+        println!("({}, {}) {}x{}", area.x, area.y, area.width, area.height);
+    }
+
+    // 按钮行占满表单剩下的高度
+    let mut form = login_form();
+    form.set_area(Rect::new(0, 0, 24, 9));
+    if let Some(buttons) = form.get(3) {
+        let area = buttons.area();
+        println!(
+            "buttons at ({}, {}) {}x{}",
+            area.x, area.y, area.width, area.height
+        );
+    }
+
+    mail_screen().run();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distribute_fill_weights() {
+        // max 封顶之后多出来的再分给别人，除不尽的零头给前面的
+        assert_eq!(
+            distribute(10, &[Constraint::fill(1), Constraint::fill(1)]),
+            vec![5, 5]
+        );
+        assert_eq!(
+            distribute(11, &[Constraint::fill(1), Constraint::fill(2)]),
+            vec![4, 7]
+        );
+        assert_eq!(
+            distribute(
+                20,
+                &[
+                    Constraint::fill(1).with_max(3),
+                    Constraint::fill(1),
+                    Constraint::fixed(4)
+                ]
+            ),
+            vec![3, 13, 4]
+        );
+        assert_eq!(
+            distribute(5, &[Constraint::fill(1).with_min(4), Constraint::fill(1)]),
+            vec![5, 0]
+        );
+        // 空间不够时保留 min，不压缩
+        assert_eq!(
+            distribute(3, &[Constraint::fixed(2), Constraint::fixed(2)]),
+            vec![2, 2]
+        );
+    }
+
+    #[test]
+    fn huge_sizes_saturate() {
+        assert_eq!(
+            distribute(10, &[Constraint::fixed(u32::MAX), Constraint::fixed(1)]),
+            vec![u32::MAX, 1]
+        );
+        // 间距乘以个数、尺寸加间距都会超过 u32，放不下的组件被挤到最右边
+        let row = Stack::row()
+            .padding(Padding::symmetric(u32::MAX, u32::MAX))
+            .gap(u32::MAX)
+            .child(label("A", 3, 1))
+            .child(label("B", 3, 1))
+            .child(label("C", 3, 1))
+            .area(Rect::new(0, 0, 30, 5));
+        assert_eq!(row.areas()[2].x, u32::MAX);
+    }
+
+    #[test]
+    fn row_with_padding_and_gap() {
+        // 内边距 1、间距 2，中间的组件伸展
+        let row = Stack::row()
+            .padding(Padding::all(1))
+            .gap(2)
+            .child(label("A", 3, 1))
+            .child_with(label("B", 1, 1), Constraint::fill(1).with_min(5))
+            .child(label("C", 4, 1))
+            .area(Rect::new(2, 3, 30, 5));
+        assert_eq!(
+            row.areas(),
+            vec![
+                Rect::new(3, 4, 3, 3),
+                Rect::new(8, 4, 17, 3),
+                Rect::new(27, 4, 4, 3)
+            ]
+        );
+    }
+
+    #[test]
+    fn column_justify_and_align() {
+        let column = Stack::column()
+            .justify(Align::End)
+            .align(Align::Center)
+            .child(button("OK"))
+            .child(button("Cancel"))
+            .area(Rect::new(0, 0, 12, 6));
+        assert_eq!(
+            column.areas(),
+            vec![Rect::new(3, 4, 6, 1), Rect::new(1, 5, 10, 1)]
+        );
+        let column = column.justify(Align::Center).align(Align::End);
+        assert_eq!(
+            column.areas(),
+            vec![Rect::new(6, 2, 6, 1), Rect::new(2, 3, 10, 1)]
+        );
+        // 按钮宽度按显示宽度算，两个汉字占 4 格
+        assert_eq!(button("取消").width, button("Exit").width);
+    }
+
+    #[test]
+    fn nested_stacks_follow_resize() {
+        // 外层改变大小时内层跟着重新布局
+        let mut form = login_form();
+        form.set_area(Rect::new(0, 0, 24, 9));
+        assert_eq!(form.get(0).unwrap().area(), Rect::new(1, 0, 22, 1));
+        assert_eq!(form.get(3).unwrap().area(), Rect::new(1, 6, 22, 3));
+        form.set_area(Rect::new(0, 0, 30, 8));
+        assert_eq!(form.get(1).unwrap().area(), Rect::new(1, 2, 28, 1));
+        assert_eq!(form.get(3).unwrap().area(), Rect::new(1, 6, 28, 2));
+    }
+
+    #[test]
+    fn screen_renders_nested_layout() {
+        let canvas = mail_screen().render();
+        assert_eq!(canvas.row(0).trim_end(), "> inbox     用户登录");
+        assert_eq!(
+            canvas.row(6).trim_end(),
+            format!("{}[ OK ] [ Cancel ]", " ".repeat(26))
+        );
+    }
+}
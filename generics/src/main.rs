@@ -3,32 +3,59 @@
 
 extern crate core;
 
+mod backend;
+mod bench;
+mod cache;
+mod container;
+mod error;
+mod event;
+mod history;
+mod iter;
+mod layout;
+mod money;
+mod numeric;
+mod plugin;
+mod point;
+mod pricing;
+mod report;
+mod shop;
+mod stats;
+mod units;
+mod widget;
 
 use core::str;
-use std::fmt;
-use std::hash::Hash;
 use std::ops::Add;
 
-fn add_i8(a: i8, b: i8) -> i8 {
-    a + b
-}
-
-fn add_i32(a: i32, b: i32) -> i32 {
-    a + b
-}
-
-fn add_f64(a: f64, b: f64) -> f64 {
-    a + b
-}
-// 上述代码可以正常运行，但是很啰嗦
+use numeric::Overflow;
+use point::{Point, PointT};
+use shop::{CarShop, CoffeeStore, DefaultShop, Goods, Inventory};
+use widget::{Button, Image, Screen};
 
 fn main() {
-    println!("add i8: {}", add_i8(2i8, 3i8));
-    println!("add i32: {}", add_i32(20, 30));
-    println!("add f64: {}", add_f64(1.23, 1.23));
+    // cargo run --release -- bench 只跑分发方式的性能测试，见 bench.rs
+    if std::env::args().nth(1).as_deref() == Some("bench") {
+        bench::bench_main();
+        return;
+    }
+    // cargo run -- render [text|ansi|html|html-page] 用指定的后端输出演示界面，见 backend.rs
+    if std::env::args().nth(1).as_deref() == Some("render") {
+        backend::render_main(std::env::args().nth(2).as_deref());
+        return;
+    }
+    // cargo run -- plugins 列出插件，见 plugin.rs
+    if std::env::args().nth(1).as_deref() == Some("plugins") {
+        let args: Vec<String> = std::env::args().skip(2).collect();
+        plugin::plugin_main(&args);
+        return;
+    }
+    println!("add i8: {:?}", numeric::add(2i8, 3i8, Overflow::Checked));
+    println!("add i32: {:?}", numeric::add(20, 30, Overflow::Checked));
+    println!("add f64: {:?}", numeric::add(1.23, 1.23, Overflow::Checked));
     let number_list = vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12];
-    let result = largest(&number_list);
-    println!("{}", result);
+    // largest 会返回 Option，空列表时是 None 而不是 panic
+    if let Some(result) = stats::largest(&number_list) {
+        println!("{}", result);
+    }
     // 提前声明，跟泛型函数定义类似，首先我们在使用泛型参数之前必需要进行声明 Point<T>，
     // 接着就可以在结构体的字段类型中使用 T 来替代具体的类型
     // x 和 y 是相同的类型
@@ -43,32 +70,27 @@ fn main() {
             Ok(T),
             Err(E),
         }
-    }
-    {
-        // 使用泛型参数前，依然需要提前声明：impl<T>，只有提前声明了，我们才能在Point<T>中使用它，这样 Rust 就知道 Point 的尖括号中的类型是泛型而不是具体类型。需要注意的是，
-        // 这里的 Point<T> 不再是泛型声明，而是一个完整的结构体类型，因为我们定义的结构体就是 Point<T> 而不再是 Point
-        impl<T> Point<T> {
-            fn new(x: T, y: T) -> Self {
-                Point { x: x, y: y }
-            }
-            pub fn x(&self) -> &T {
-                &self.x
-            }
-        }
+        // 带组合子和错误链的完整版本见 error.rs
     }
     {
         let coffee_store = CoffeeStore {
             name: "壹间万物".to_string(),
             coffee_type: "瑰夏".to_string(),
+            inventory: Inventory::new(),
+            history: None,
         };
         let car_shop = CarShop {
             name: "宝渝".to_string(),
             car_brand: "BMW".to_string(),
+            inventory: Inventory::new(),
+            history: None,
         };
         println!("{}", car_shop.sale());
         println!("{}", coffee_store.sale());
         let default = DefaultShop {
             name: "default".to_string(),
+            inventory: Inventory::new(),
+            history: None,
         };
         println!("{}", default.sale());
         println!("{}", default.Buy())
@@ -77,83 +99,25 @@ fn main() {
         // impl Trait 的返回值类型并不支持多种不同的类型返回
         // 如果想返回多钟类型，可以使用枚举，但在对枚举中类型并不清楚的情况下，枚举并不是最佳选择。
         // 特征对象
+        // Draw、Button、Image、Screen 等组件定义在 widget.rs 中
         {
-            pub trait Draw {
-                fn draw(&self);
-            }
-            pub struct Button {
-                pub width: u32,
-                pub height: u32,
-                pub label: String,
-            }
-            impl Draw for Button {
-                fn draw(&self) {
-                    println!("label is {}", self.label);
-                }
-            }
-            pub struct Image {
-                pub width: u32,
-                pub height: u32,
-                pub pixel: String,
-            }
-            impl Draw for Image {
-                fn draw(&self) {
-                    println!("piexel is {}", self.pixel);
-                }
-            }
-            // 还需要一个动态数组来存储这些 UI 对象
-            // 特征对象指向实现了 Draw 特征的类型的实例，也就是指向了 Button 或者 Image 的实例，
-            // 这种映射关系是存储在一张表中，可以在运行时通过特征对象找到具体调用的类型方法。
-            // 可以通过&引用或者用Box<>智能指针
-            fn draw1(x: &dyn Draw) {
-                x.draw();
-            }
-            fn draw2(x: Box<dyn Draw>) {
-                x.draw();
-            }
-            pub struct Screen {
-                pub components: Vec<Box<dyn Draw>>,
-            }
-            impl Screen {
-                pub fn run(&self) {
-                    for component in self.components.iter() {
-                        component.draw();
-                    }
-                }
-            }
-            let mut screen = Screen {
-                components: vec![
-                    Box::new(Button {
-                        width: 10,
-                        height: 25,
-                        label: "120".to_string(),
-                    }),
-                    Box::new(Image {
-                        width: 10,
-                        height: 25,
-                        pixel: "00000010".to_string(),
-                    }),
-                ],
-            };
+            let mut screen = Screen::new(24, 3);
+            screen
+                .add(Button {
+                    x: 0,
+                    y: 0,
+                    width: 10,
+                    height: 3,
+                    label: "120".to_string(),
+                })
+                .add(Image {
+                    x: 12,
+                    y: 0,
+                    width: 4,
+                    height: 2,
+                    pixel: "00000010".to_string(),
+                });
             screen.run();
-
-            // 下面的 Screen1 的列表中，存储了类型为 T 的元素，然后在 Screen 中使用特征约束让 T 实现了 Draw 特征，进而可以调用 draw 方法。
-            // 但是这种写法限制了 Screen 实例的 Vec<T> 中的每个元素必须是 Button 类型或者全是 SelectBox 类型。如果只需要同质（相同类型）集合，
-            // 更倾向于这种写法：使用泛型和 特征约束，因为实现更清晰，且性能更好(特征对象，需要在运行时从 vtable 动态查找需要调用的方法)。
-            pub struct Screen1<T: Draw> {
-                pub components: Vec<T>,
-            }
-
-            impl<T> Screen1<T>
-                where
-                    T: Draw,
-            {
-                pub fn run(&self) {
-                    for component in self.components.iter() {
-                        component.draw();
-                    }
-                }
-            }
         }
         // 特征对象的动态分发
         // 回忆一下泛型章节我们提到过的，泛型是在编译期完成处理的：编译器会为每一个泛型参数对应的具体类型生成一份代码，这种方式是静态分发(static dispatch)，因为是在编译期完成的，对于运行期性能完全没有任何影响。
@@ -171,33 +135,10 @@ fn main() {
     }
     {
         //     关联类型
-        // 以下是标准库的迭代器特征Iterator，有一个Item关联类型用于替代遍历的值的类型
-        // next方法也返回一个Item类型，不过是使用Option枚举进行了包裹
-        // Self用来替代当前调用者的具体类型(self是用来替代该实例
-        pub trait Iterator {
-            type Item;
-            fn next(&mut self) -> Option<Self::Item>;
-        }
+        // 标准库迭代器特征 Iterator 的简化版本和基于它的适配器见 iter.rs
         //     使用特征类型和使用泛型的不同
-        // 调用是使用Address比完整地写完AsRef<[u8]> + Clone + fmt::Debug + Eq + Hash要简单和可读性更强
-        pub trait CacheableItem: Clone + Default + fmt::Debug {
-            type Address: AsRef<[u8]> + Clone + fmt::Debug + Eq + Hash;
-            fn is_null(&self) -> bool;
-        }
-        // 使用泛型
-        trait Container<A, B> {
-            fn container(&self, a: A, b: B) -> bool;
-        }
-        fn difference<A, B, C>(container: &C) -> i32
-            where
-                C: Container<A, B> { return 128i32; }
-        // 可以看到使用了关联类型，可读性好不少
-        trait ContainerII {
-            type A;
-            type B;
-            fn contains(&self, a: &Self::A, b: &Self::B) -> bool;
-        }
-        fn difference_ii<C: ContainerII>(container: &C) {}
+        // CacheableItem 的定义和基于它的缓存在 cache.rs 中
+        // 使用泛型的 Container<A, B> 和使用关联类型的 ContainerII 对比见 container.rs
     }
     {
         //     默认泛型类型参数
@@ -229,6 +170,7 @@ fn main() {
         // 默认类型参数主要用于两个方面：
         // 减少实现的样板代码
         // 扩展类型但是无需大幅修改现有的代码
+        // 单位更多、还要做乘除法时，每一对单位写一个 Add 就不现实了，见 units.rs 里的量纲分析
         impl Add<Meters> for Millimeters {
             type Output = Millimeters;
             fn add(self, other: Meters) -> Self::Output {
@@ -289,99 +231,28 @@ fn main() {
         // 在尖括号中，通过 as 关键字向 Rust 编译器提供了类型注解，也就是 Animal 就是 Dog，而不是其他动物，因此最终会调用 impl Animal for Dog 中的方法
         // 完全限定语法可以用于任何函数或方法调用
         println!("A baby dog is called a {}", <Dog as Animal>::baby_name());
+        // 这些特征在插件注册表里的用法见 plugin.rs
     }
     {
         // 好难哦
         // todo 特征定义中的特征约束
     }
+    point::point();
+    stats::stats();
+    numeric::numeric();
+    money::money();
+    shop::shop();
+    history::history();
+    report::report();
+    widget::widget();
+    layout::layout();
+    event::event();
+    bench::bench();
+    cache::cache();
+    units::units();
+    container::container();
+    iter::iter();
+    plugin::plugin();
+    error::error();
+    backend::backend();
 }
-
-// 实际上，泛型就是一种多态。泛型主要目的是为程序员提供编程的便利，减少代码的臃肿，同时可以极大地丰富语言本身的表达能
-fn largest<T: std::cmp::PartialOrd>(list: &[T]) -> &T {
-    let mut largest = &list[0];
-    for item in list.iter() {
-        if item > largest {
-            largest = item;
-        }
-    }
-    largest
-}
-
-//以下代码的问题
-// 主要是返回的T，目前的rust会判定T没有实现Copy,所以，无法返回一个完整所有权，只能返回一个借用
-
-// fn largest<T: std::cmp::PartialOrd>(list: &[T]) -> T {
-//     let mut largest = list[0];
-
-//     for &item in list.iter() {
-//         if item > largest {
-//             largest = item;
-//         }
-//     }
-
-//     largest
-// }
-
-// 结构体中的字段类型也可以用泛型来定义，下面代码定义了一个坐标点 Point，它可以存放任何类型的坐标值
-struct Point<T> {
-    x: T,
-    y: T,
-}
-
-// 如果想让 x 和 y 即能类型相同，又能类型不同，需要使用不同的泛型参数
-struct PointT<T, U> {
-    x: T,
-    y: U,
-}
-// 定义特征
-
-pub trait Goods {
-    fn sale(&self) -> String {
-        String::from("this is what you sale")
-    }
-    fn Buy(&self) -> String {
-        String::from("that is what you buy")
-    }
-}
-
-struct CoffeeStore {
-    name: String,
-    coffee_type: String,
-}
-
-struct CarShop {
-    name: String,
-    car_brand: String,
-}
-
-struct DefaultShop {
-    name: String,
-}
-
-// 特征。和golang中的interface很像
-// 实现特征的语法与为结构体、枚举实现方法很像
-// 关于特征实现与定义的位置，有一条非常重要的原则：如果你想要为类型 A 实现特征 T，那么 A 或者 T 至少有一个是在当前作用域中定义的！
-// 例如我们可以为上面的 Post 类型实现标准库中的 Display 特征，这是因为 Post 类型定义在当前的作用域中。同时，我们也可以在当前包中为
-//  String 类型实现 Summary 特征，因为 Summary 定义在当前作用域中。
-// 但是你无法在当前作用域中，为 String 类型实现 Display 特征，因为它们俩都定义在标准库中，其定义所在的位置都不在当前作用域，跟你半毛钱关系都没有，看看就行了。
-// 该规则被称为孤儿规则，可以确保其它人编写的代码不会破坏你的代码，也确保了你不会莫名其妙就破坏了风马牛不相及的代码。
-// 默认实现允许调用相同特征中的其他方法，哪怕这些方法没有默认实现。如此，特征可以提供很多有用的功能而只需要实现指定的一小部分内容。
-impl Goods for CoffeeStore {
-    fn sale(&self) -> String {
-        format!(
-            "{} has some type of coffee,such as {}",
-            self.name, self.coffee_type
-        )
-    }
-}
-
-impl Goods for CarShop {
-    fn sale(&self) -> String {
-        format!(
-            "{} has some type of car, such as {}",
-            self.name, self.car_brand
-        )
-    }
-}
-
-impl Goods for DefaultShop {}
@@ -0,0 +1,404 @@
+// 定点数金额
+// 金额以“最小货币单位”（人民币的分、美元的美分、日元的 1 円）保存为整数，全程不用浮点数；
+// 每个金额都带着币种，不同币种之间不能直接相加。
+// 乘以比例（折扣、税率）时必然要舍入，舍入方式由调用方显式选择。
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Currency {
+    Cny,
+    Usd,
+    Eur,
+    Jpy,
+}
+
+impl Currency {
+    // 小数位数：元有 2 位（分），日元没有小数
+    pub fn minor_digits(&self) -> u32 {
+        match self {
+            Currency::Jpy => 0,
+            _ => 2,
+        }
+    }
+    fn minor_per_major(&self) -> i64 {
+        10i64.pow(self.minor_digits())
+    }
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Currency::Cny => "¥",
+            Currency::Usd => "$",
+            Currency::Eur => "€",
+            Currency::Jpy => "JP¥",
+        }
+    }
+    pub fn code(&self) -> &'static str {
+        match self {
+            Currency::Cny => "CNY",
+            Currency::Usd => "USD",
+            Currency::Eur => "EUR",
+            Currency::Jpy => "JPY",
+        }
+    }
+    pub fn from_code(code: &str) -> Option<Currency> {
+        [Currency::Cny, Currency::Usd, Currency::Eur, Currency::Jpy]
+            .into_iter()
+            .find(|currency| currency.code() == code)
+    }
+}
+
+// 舍入方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    // 四舍五入，.5 远离 0
+    HalfUp,
+    // 银行家舍入，.5 舍入到偶数，大量累加时误差不会偏向一边
+    HalfEven,
+    // 直接截断（向 0 舍入）
+    Down,
+    // 只要有余数就进位（远离 0）
+    Up,
+}
+
+impl Rounding {
+    // 计算 numer / denom 并按规则舍入到整数，denom 必须为正
+    pub fn divide(self, numer: i128, denom: i128) -> i128 {
+        assert!(denom > 0, "denominator must be positive");
+        let quotient = numer / denom;
+        let remainder = numer % denom;
+        if remainder == 0 {
+            return quotient;
+        }
+        // 远离 0 的方向
+        let away = if numer < 0 { -1 } else { 1 };
+        let twice = remainder.abs() * 2;
+        match self {
+            Rounding::Down => quotient,
+            Rounding::Up => quotient + away,
+            Rounding::HalfUp => {
+                if twice >= denom {
+                    quotient + away
+                } else {
+                    quotient
+                }
+            }
+            Rounding::HalfEven => {
+                if twice > denom || (twice == denom && quotient % 2 != 0) {
+                    quotient + away
+                } else {
+                    quotient
+                }
+            }
+        }
+    }
+}
+
+// 比例，以万分之一（基点）为单位：15% = 1500，6.5% = 650
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Rate(i64);
+
+impl Rate {
+    pub fn percent(percent: i64) -> Self {
+        Rate(percent * 100)
+    }
+    pub fn basis_points(points: i64) -> Self {
+        Rate(points)
+    }
+}
+
+impl fmt::Display for Rate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0 % 100 == 0 {
+            write!(f, "{}%", self.0 / 100)
+        } else {
+            write!(f, "{}.{:02}%", self.0 / 100, (self.0 % 100).abs())
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Money {
+    currency: Currency,
+    minor: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MoneyError {
+    CurrencyMismatch(Currency, Currency),
+    Parse(String),
+    // 结果超出了 i64 能表示的最小货币单位数量
+    Overflow,
+}
+
+impl fmt::Display for MoneyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MoneyError::CurrencyMismatch(a, b) => {
+                write!(f, "currency mismatch: {} vs {}", a.code(), b.code())
+            }
+            MoneyError::Parse(text) => write!(f, "invalid amount {:?}", text),
+            MoneyError::Overflow => write!(f, "amount overflow"),
+        }
+    }
+}
+
+impl std::error::Error for MoneyError {}
+
+impl Money {
+    // minor 是最小货币单位的数量，Money::new(1234, Cny) 就是 ¥12.34
+    pub fn new(minor: i64, currency: Currency) -> Self {
+        Money { currency, minor }
+    }
+    pub fn zero(currency: Currency) -> Self {
+        Money::new(0, currency)
+    }
+    pub fn from_major(major: i64, currency: Currency) -> Self {
+        Money::new(major * currency.minor_per_major(), currency)
+    }
+    // 解析 "12.34"、"-0.5"、"100" 这样的十进制字符串，小数位数不能超过币种允许的位数
+    pub fn parse(text: &str, currency: Currency) -> Result<Self, MoneyError> {
+        let error = || MoneyError::Parse(text.to_string());
+        let (negative, digits) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        };
+        let (major, minor) = match digits.split_once('.') {
+            Some((major, minor)) => (major, minor),
+            None => (digits, ""),
+        };
+        let places = currency.minor_digits() as usize;
+        let all_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+        if major.is_empty() || !all_digits(major) || !all_digits(minor) || minor.len() > places {
+            return Err(error());
+        }
+        if digits.contains('.') && minor.is_empty() {
+            return Err(error());
+        }
+        let major: i64 = major.parse().map_err(|_| error())?;
+        let minor_value: i64 = if minor.is_empty() {
+            0
+        } else {
+            minor.parse::<i64>().map_err(|_| error())? * 10i64.pow((places - minor.len()) as u32)
+        };
+        let value = major
+            .checked_mul(currency.minor_per_major())
+            .and_then(|v| v.checked_add(minor_value))
+            .ok_or_else(error)?;
+        Ok(Money::new(if negative { -value } else { value }, currency))
+    }
+    pub fn minor(&self) -> i64 {
+        self.minor
+    }
+    pub fn currency(&self) -> Currency {
+        self.currency
+    }
+    pub fn is_zero(&self) -> bool {
+        self.minor == 0
+    }
+    pub fn try_add(self, rhs: Money) -> Result<Money, MoneyError> {
+        if self.currency != rhs.currency {
+            return Err(MoneyError::CurrencyMismatch(self.currency, rhs.currency));
+        }
+        let minor = self
+            .minor
+            .checked_add(rhs.minor)
+            .ok_or(MoneyError::Overflow)?;
+        Ok(Money::new(minor, self.currency))
+    }
+    pub fn try_mul(self, rhs: u32) -> Result<Money, MoneyError> {
+        let minor = self
+            .minor
+            .checked_mul(rhs as i64)
+            .ok_or(MoneyError::Overflow)?;
+        Ok(Money::new(minor, self.currency))
+    }
+    // 乘以比例并舍入到最小货币单位
+    pub fn apply_rate(self, rate: Rate, rounding: Rounding) -> Money {
+        let value = rounding.divide(self.minor as i128 * rate.0 as i128, 10_000);
+        Money::new(value as i64, self.currency)
+    }
+    // 乘以分数 numer/denom，例如 “买 3 送 1” 时按 1/4 计算
+    pub fn mul_ratio(self, numer: i64, denom: i64, rounding: Rounding) -> Money {
+        let value = rounding.divide(self.minor as i128 * numer as i128, denom as i128);
+        Money::new(value as i64, self.currency)
+    }
+    // 数值部分，不带币种符号，例如 "12.34"
+    pub fn amount_string(&self) -> String {
+        let per = self.currency.minor_per_major();
+        let sign = if self.minor < 0 { "-" } else { "" };
+        let abs = self.minor.unsigned_abs();
+        let digits = self.currency.minor_digits() as usize;
+        if digits == 0 {
+            format!("{}{}", sign, abs)
+        } else {
+            format!(
+                "{}{}.{:0width$}",
+                sign,
+                abs / per as u64,
+                abs % per as u64,
+                width = digits
+            )
+        }
+    }
+}
+
+// 运算符版本在币种不一致或溢出时直接 panic，就像整数溢出一样属于程序错误；
+// 需要处理不可信输入时请用 try_add / try_mul
+impl Add for Money {
+    type Output = Money;
+    fn add(self, rhs: Money) -> Money {
+        match self.try_add(rhs) {
+            Ok(money) => money,
+            Err(e) => panic!("{}", e),
+        }
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+    fn sub(self, rhs: Money) -> Money {
+        self + (-rhs)
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+    fn neg(self) -> Money {
+        Money::new(-self.minor, self.currency)
+    }
+}
+
+// 单价乘数量
+impl Mul<u32> for Money {
+    type Output = Money;
+    fn mul(self, rhs: u32) -> Money {
+        match self.try_mul(rhs) {
+            Ok(money) => money,
+            Err(e) => panic!("{}", e),
+        }
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self.amount_string().strip_prefix('-') {
+            Some(abs) => format!("-{}{}", self.currency.symbol(), abs),
+            None => format!("{}{}", self.currency.symbol(), self.amount_string()),
+        };
+        // 支持 {:>10} 这样的对齐
+        f.pad(&text)
+    }
+}
+
+// 演示和测试里写人民币金额的简写，text 必须是合法的金额
+pub(crate) fn cny(text: &str) -> Money {
+    Money::parse(text, Currency::Cny).unwrap()
+}
+
+pub(crate) fn money() {
+    let price = Money::parse("12.34", Currency::Cny).unwrap();
+    println!(
+        "{} * 3 = {}, negated {}, {} minor units",
+        price,
+        price * 3,
+        -price,
+        price.minor()
+    );
+    println!(
+        "{} {} {:?}",
+        Money::parse("1200", Currency::Jpy).unwrap(),
+        Money::from_major(-3, Currency::Eur),
+        Money::parse("1.5", Currency::Jpy)
+    );
+    println!("{:?}", price.try_add(Money::new(1, Currency::Usd)));
+
+    // ¥0.25 * 10% = 0.025 分，四种舍入方式的结果不同
+    let amount = Money::new(25, Currency::Cny);
+    for rounding in [
+        Rounding::HalfUp,
+        Rounding::HalfEven,
+        Rounding::Down,
+        Rounding::Up,
+    ] {
+        println!(
+            "{} x {} {:?} = {}",
+            amount,
+            Rate::percent(10),
+            rounding,
+            amount.apply_rate(Rate::percent(10), rounding)
+        );
+    }
+    println!(
+        "{} x {} = {}",
+        price,
+        Rate::basis_points(1300),
+        price.apply_rate(Rate::percent(13), Rounding::HalfEven)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_format() {
+        let price = Money::parse("12.34", Currency::Cny).unwrap();
+        assert_eq!(price, Money::new(1234, Currency::Cny));
+        assert_eq!(price.to_string(), "¥12.34");
+        assert_eq!((-price).to_string(), "-¥12.34");
+        assert_eq!(Money::parse("0.5", Currency::Usd).unwrap().minor(), 50);
+        assert_eq!(
+            Money::parse("1200", Currency::Jpy).unwrap().to_string(),
+            "JP¥1200"
+        );
+        assert!(Money::parse("1.234", Currency::Cny).is_err());
+        assert!(Money::parse("1.5", Currency::Jpy).is_err());
+        assert!(Money::parse("abc", Currency::Cny).is_err());
+        assert_eq!(Money::from_major(-3, Currency::Eur).to_string(), "-€3.00");
+        assert!(Money::parse("1.", Currency::Cny).is_err());
+        assert_eq!(Rate::basis_points(650).to_string(), "6.50%");
+    }
+
+    #[test]
+    fn arithmetic_checks_currency() {
+        let price = Money::parse("12.34", Currency::Cny).unwrap();
+        assert_eq!(
+            price * 3,
+            Money::from_major(37, Currency::Cny) + Money::new(2, Currency::Cny)
+        );
+        assert_eq!(
+            price.try_add(Money::new(1, Currency::Usd)),
+            Err(MoneyError::CurrencyMismatch(Currency::Cny, Currency::Usd))
+        );
+    }
+
+    #[test]
+    fn overflow_is_reported() {
+        let big = Money::new(i64::MAX / 2 + 1, Currency::Cny);
+        assert_eq!(big.try_mul(2), Err(MoneyError::Overflow));
+        assert_eq!(big.try_add(big), Err(MoneyError::Overflow));
+        assert_eq!(
+            Money::new(i64::MAX, Currency::Cny).try_mul(u32::MAX),
+            Err(MoneyError::Overflow)
+        );
+        assert_eq!(big.try_mul(1), Ok(big));
+    }
+
+    #[test]
+    fn rounding_modes() {
+        // ¥0.25 * 10% = 0.025 分
+        let amount = Money::new(25, Currency::Cny);
+        let rate = Rate::percent(10);
+        assert_eq!(amount.apply_rate(rate, Rounding::HalfUp).minor(), 3);
+        assert_eq!(amount.apply_rate(rate, Rounding::HalfEven).minor(), 2);
+        assert_eq!(amount.apply_rate(rate, Rounding::Down).minor(), 2);
+        assert_eq!(amount.apply_rate(Rate::percent(1), Rounding::Up).minor(), 1);
+        assert_eq!((-amount).apply_rate(rate, Rounding::HalfUp).minor(), -3);
+        assert_eq!(
+            Money::new(35, Currency::Cny)
+                .apply_rate(rate, Rounding::HalfEven)
+                .minor(),
+            4
+        );
+    }
+}
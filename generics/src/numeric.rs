@@ -0,0 +1,366 @@
+// Numeric 特征：把整数、浮点数、复数的共同点抽出来，一个泛型 add/sum/product/pow 就能处理所有数字类型
+// 溢出时怎么办由调用方通过 Overflow 显式指定，而不是依赖 debug 下 panic、release 下回绕的默认行为
+use std::fmt;
+
+pub trait Numeric: Copy + PartialEq + fmt::Debug {
+    fn zero() -> Self;
+    fn one() -> Self;
+    // 溢出时返回 None
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+    // 溢出时回绕
+    fn wrapping_add(self, rhs: Self) -> Self;
+    fn wrapping_sub(self, rhs: Self) -> Self;
+    fn wrapping_mul(self, rhs: Self) -> Self;
+    // 溢出时停在最大值或最小值
+    fn saturating_add(self, rhs: Self) -> Self;
+    fn saturating_sub(self, rhs: Self) -> Self;
+    fn saturating_mul(self, rhs: Self) -> Self;
+}
+
+// 整数直接转发给标准库里的同名方法，用宏避免为每种整数写一遍
+macro_rules! impl_numeric_int {
+    ($($t:ty),*) => {
+        $(
+            impl Numeric for $t {
+                fn zero() -> Self {
+                    0
+                }
+                fn one() -> Self {
+                    1
+                }
+                fn checked_add(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_add(self, rhs)
+                }
+                fn checked_sub(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_sub(self, rhs)
+                }
+                fn checked_mul(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_mul(self, rhs)
+                }
+                fn wrapping_add(self, rhs: Self) -> Self {
+                    <$t>::wrapping_add(self, rhs)
+                }
+                fn wrapping_sub(self, rhs: Self) -> Self {
+                    <$t>::wrapping_sub(self, rhs)
+                }
+                fn wrapping_mul(self, rhs: Self) -> Self {
+                    <$t>::wrapping_mul(self, rhs)
+                }
+                fn saturating_add(self, rhs: Self) -> Self {
+                    <$t>::saturating_add(self, rhs)
+                }
+                fn saturating_sub(self, rhs: Self) -> Self {
+                    <$t>::saturating_sub(self, rhs)
+                }
+                fn saturating_mul(self, rhs: Self) -> Self {
+                    <$t>::saturating_mul(self, rhs)
+                }
+            }
+        )*
+    };
+}
+
+impl_numeric_int!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+// 浮点数没有回绕的概念：
+// checked 在结果变成无穷大或 NaN 时返回 None，wrapping 就是普通的 IEEE 运算，saturating 把无穷大截到 MAX/MIN
+macro_rules! impl_numeric_float {
+    ($($t:ty),*) => {
+        $(
+            impl Numeric for $t {
+                fn zero() -> Self {
+                    0.0
+                }
+                fn one() -> Self {
+                    1.0
+                }
+                fn checked_add(self, rhs: Self) -> Option<Self> {
+                    Some(self + rhs).filter(|v| v.is_finite())
+                }
+                fn checked_sub(self, rhs: Self) -> Option<Self> {
+                    Some(self - rhs).filter(|v| v.is_finite())
+                }
+                fn checked_mul(self, rhs: Self) -> Option<Self> {
+                    Some(self * rhs).filter(|v| v.is_finite())
+                }
+                fn wrapping_add(self, rhs: Self) -> Self {
+                    self + rhs
+                }
+                fn wrapping_sub(self, rhs: Self) -> Self {
+                    self - rhs
+                }
+                fn wrapping_mul(self, rhs: Self) -> Self {
+                    self * rhs
+                }
+                fn saturating_add(self, rhs: Self) -> Self {
+                    (self + rhs).clamp(<$t>::MIN, <$t>::MAX)
+                }
+                fn saturating_sub(self, rhs: Self) -> Self {
+                    (self - rhs).clamp(<$t>::MIN, <$t>::MAX)
+                }
+                fn saturating_mul(self, rhs: Self) -> Self {
+                    (self * rhs).clamp(<$t>::MIN, <$t>::MAX)
+                }
+            }
+        )*
+    };
+}
+
+impl_numeric_float!(f32, f64);
+
+// 和 num::Complex 类似的复数，实部和虚部可以是任意 Numeric
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Complex<T> {
+    pub re: T,
+    pub im: T,
+}
+
+impl<T> Complex<T> {
+    pub fn new(re: T, im: T) -> Self {
+        Complex { re, im }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Complex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}+{}i", self.re, self.im)
+    }
+}
+
+// 复数的运算按分量组合，溢出策略也逐个分量生效
+// (a+bi)(c+di) = (ac-bd) + (ad+bc)i
+impl<T: Numeric> Numeric for Complex<T> {
+    fn zero() -> Self {
+        Complex::new(T::zero(), T::zero())
+    }
+    fn one() -> Self {
+        Complex::new(T::one(), T::zero())
+    }
+    fn checked_add(self, rhs: Self) -> Option<Self> {
+        Some(Complex::new(
+            self.re.checked_add(rhs.re)?,
+            self.im.checked_add(rhs.im)?,
+        ))
+    }
+    fn checked_sub(self, rhs: Self) -> Option<Self> {
+        Some(Complex::new(
+            self.re.checked_sub(rhs.re)?,
+            self.im.checked_sub(rhs.im)?,
+        ))
+    }
+    fn checked_mul(self, rhs: Self) -> Option<Self> {
+        let re = self
+            .re
+            .checked_mul(rhs.re)?
+            .checked_sub(self.im.checked_mul(rhs.im)?)?;
+        let im = self
+            .re
+            .checked_mul(rhs.im)?
+            .checked_add(self.im.checked_mul(rhs.re)?)?;
+        Some(Complex::new(re, im))
+    }
+    fn wrapping_add(self, rhs: Self) -> Self {
+        Complex::new(self.re.wrapping_add(rhs.re), self.im.wrapping_add(rhs.im))
+    }
+    fn wrapping_sub(self, rhs: Self) -> Self {
+        Complex::new(self.re.wrapping_sub(rhs.re), self.im.wrapping_sub(rhs.im))
+    }
+    fn wrapping_mul(self, rhs: Self) -> Self {
+        Complex::new(
+            self.re
+                .wrapping_mul(rhs.re)
+                .wrapping_sub(self.im.wrapping_mul(rhs.im)),
+            self.re
+                .wrapping_mul(rhs.im)
+                .wrapping_add(self.im.wrapping_mul(rhs.re)),
+        )
+    }
+    fn saturating_add(self, rhs: Self) -> Self {
+        Complex::new(
+            self.re.saturating_add(rhs.re),
+            self.im.saturating_add(rhs.im),
+        )
+    }
+    fn saturating_sub(self, rhs: Self) -> Self {
+        Complex::new(
+            self.re.saturating_sub(rhs.re),
+            self.im.saturating_sub(rhs.im),
+        )
+    }
+    fn saturating_mul(self, rhs: Self) -> Self {
+        Complex::new(
+            self.re
+                .saturating_mul(rhs.re)
+                .saturating_sub(self.im.saturating_mul(rhs.im)),
+            self.re
+                .saturating_mul(rhs.im)
+                .saturating_add(self.im.saturating_mul(rhs.re)),
+        )
+    }
+}
+
+// 溢出策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    // 溢出时整个计算结果为 None
+    Checked,
+    // 溢出时回绕（对浮点数就是普通运算）
+    Wrapping,
+    // 溢出时停在边界值
+    Saturating,
+}
+
+impl Overflow {
+    fn add<T: Numeric>(self, a: T, b: T) -> Option<T> {
+        match self {
+            Overflow::Checked => a.checked_add(b),
+            Overflow::Wrapping => Some(a.wrapping_add(b)),
+            Overflow::Saturating => Some(a.saturating_add(b)),
+        }
+    }
+    fn mul<T: Numeric>(self, a: T, b: T) -> Option<T> {
+        match self {
+            Overflow::Checked => a.checked_mul(b),
+            Overflow::Wrapping => Some(a.wrapping_mul(b)),
+            Overflow::Saturating => Some(a.saturating_mul(b)),
+        }
+    }
+}
+
+// 只有 Overflow::Checked 才可能返回 None，另外两种策略总是 Some
+pub fn add<T: Numeric>(a: T, b: T, overflow: Overflow) -> Option<T> {
+    overflow.add(a, b)
+}
+
+// 空迭代器的和是 0
+pub fn sum<I>(iter: I, overflow: Overflow) -> Option<I::Item>
+where
+    I: IntoIterator,
+    I::Item: Numeric,
+{
+    iter.into_iter()
+        .try_fold(I::Item::zero(), |acc, x| overflow.add(acc, x))
+}
+
+// 空迭代器的积是 1
+pub fn product<I>(iter: I, overflow: Overflow) -> Option<I::Item>
+where
+    I: IntoIterator,
+    I::Item: Numeric,
+{
+    iter.into_iter()
+        .try_fold(I::Item::one(), |acc, x| overflow.mul(acc, x))
+}
+
+// 快速幂：把指数按二进制拆开，只需要 O(log exp) 次乘法
+pub fn pow<T: Numeric>(base: T, exp: u32, overflow: Overflow) -> Option<T> {
+    let mut result = T::one();
+    let mut base = base;
+    let mut exp = exp;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = overflow.mul(result, base)?;
+        }
+        exp >>= 1;
+        if exp > 0 {
+            base = overflow.mul(base, base)?;
+        }
+    }
+    Some(result)
+}
+
+pub(crate) fn numeric() {
+    // 同一个 add 处理所有数字类型，溢出时怎么处理要显式指定
+    println!(
+        "2i8 + 3i8 = {:?}, 1.5 + 2.25 = {:?}, (1+2i) + (3-4i) = {}",
+        add(2i8, 3i8, Overflow::Checked),
+        add(1.5f32, 2.25, Overflow::Checked),
+        add(Complex::new(1, 2), Complex::new(3, -4), Overflow::Checked).unwrap()
+    );
+    for policy in [Overflow::Checked, Overflow::Wrapping, Overflow::Saturating] {
+        println!(
+            "{:?}: 100i8 + 100 = {:?}, sum [200u8, 100] = {:?}, 2^31 = {:?}",
+            policy,
+            add(100i8, 100, policy),
+            sum([200u8, 100], policy),
+            pow(2i32, 31, policy)
+        );
+    }
+    println!(
+        "5! = {:?}, i^2 = {}",
+        product(1..=5u32, Overflow::Checked),
+        pow(Complex::new(0, 1), 2, Overflow::Checked).unwrap()
+    );
+    let c = product(
+        [Complex::new(1.0, 1.0), Complex::new(1.0, -1.0)],
+        Overflow::Checked,
+    );
+    println!("(1+1i)(1-1i) = {}", c.unwrap());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_works_for_every_numeric_type() {
+        assert_eq!(add(2i8, 3i8, Overflow::Checked), Some(5));
+        assert_eq!(add(1.5f32, 2.25, Overflow::Checked), Some(3.75));
+        assert_eq!(
+            add(Complex::new(1, 2), Complex::new(3, -4), Overflow::Checked),
+            Some(Complex::new(4, -2))
+        );
+    }
+
+    #[test]
+    fn overflow_policies() {
+        assert_eq!(add(100i8, 100, Overflow::Checked), None);
+        assert_eq!(add(100i8, 100, Overflow::Wrapping), Some(-56));
+        assert_eq!(add(100i8, 100, Overflow::Saturating), Some(127));
+        assert_eq!(add(0u8, 255, Overflow::Checked), Some(255));
+        assert_eq!(add(f64::MAX, f64::MAX, Overflow::Checked), None);
+        assert_eq!(
+            add(f64::MAX, f64::MAX, Overflow::Wrapping),
+            Some(f64::INFINITY)
+        );
+        assert_eq!(
+            add(f64::MAX, f64::MAX, Overflow::Saturating),
+            Some(f64::MAX)
+        );
+    }
+
+    #[test]
+    fn sum_and_product() {
+        assert_eq!(sum([1, 2, 3, 4], Overflow::Checked), Some(10));
+        assert_eq!(sum(Vec::<u64>::new(), Overflow::Checked), Some(0));
+        assert_eq!(sum([200u8, 100], Overflow::Checked), None);
+        assert_eq!(sum([200u8, 100], Overflow::Saturating), Some(255));
+        assert_eq!(product(1..=5u32, Overflow::Checked), Some(120));
+        assert_eq!(product(1..=30u64, Overflow::Checked), None);
+        assert_eq!(product(Vec::<f64>::new(), Overflow::Checked), Some(1.0));
+        assert_eq!(
+            product(
+                [Complex::new(1.0, 1.0), Complex::new(1.0, -1.0)],
+                Overflow::Checked
+            ),
+            Some(Complex::new(2.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn pow_respects_overflow_policy() {
+        assert_eq!(pow(3i32, 4, Overflow::Checked), Some(81));
+        assert_eq!(pow(2i32, 31, Overflow::Checked), None);
+        assert_eq!(pow(2i32, 31, Overflow::Wrapping), Some(i32::MIN));
+        assert_eq!(pow(-3i32, 41, Overflow::Saturating), Some(i32::MIN));
+        assert_eq!(pow(7u8, 0, Overflow::Checked), Some(1));
+        assert_eq!(pow(1.5f64, 2, Overflow::Checked), Some(2.25));
+        // i 的平方是 -1
+        let i = Complex::new(0, 1);
+        assert_eq!(pow(i, 2, Overflow::Checked), Some(Complex::new(-1, 0)));
+        assert_eq!(pow(i, 4, Overflow::Checked), Some(Complex::<i32>::one()));
+        assert_eq!(pow(Complex::new(100i8, 0), 2, Overflow::Checked), None);
+    }
+}
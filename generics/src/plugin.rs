@@ -0,0 +1,618 @@
+// 插件注册表
+// main.rs 里 Pilot/Wizard 的 fly 重名、Dog 的 baby_name 需要完全限定语法，这里把它们用到一个实际场景里：
+//   - 一个插件类型可以实现多个能力特征（Pilot、Wizard、Animal），注册时逐个声明；
+//   - 运行时按名字找到插件，再按命令调用它的能力；
+//   - 命令可以写成 "wizard.fly" 这样带能力名的形式，相当于 Wizard::fly(&plugin)；
+//     只写 "fly" 时如果有多个能力都提供 fly，不会随便选一个，而是返回 Ambiguous 错误，要求调用方写清楚；
+//   - Animal::baby_name 没有 self，不能做成特征对象，注册时用 <T as Animal>::baby_name 取出函数指针保存。
+// cargo run -- plugins 列出所有插件和它们的能力，cargo run -- plugins <插件> <命令> [参数] 调用一个命令。
+use std::collections::BTreeMap;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::report::{Cell, Table};
+
+pub trait Pilot {
+    fn fly(&self) -> String;
+}
+
+pub trait Wizard {
+    fn fly(&self) -> String;
+    fn cast(&self, spell: &str) -> String;
+}
+
+pub trait Animal {
+    fn baby_name() -> String;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Capability {
+    Pilot,
+    Wizard,
+    Animal,
+}
+
+impl Capability {
+    const ALL: [Capability; 3] = [Capability::Pilot, Capability::Wizard, Capability::Animal];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Capability::Pilot => "pilot",
+            Capability::Wizard => "wizard",
+            Capability::Animal => "animal",
+        }
+    }
+    pub fn from_name(name: &str) -> Option<Capability> {
+        Capability::ALL.into_iter().find(|c| c.name() == name)
+    }
+    // 这个能力提供的命令
+    pub fn methods(self) -> &'static [&'static str] {
+        match self {
+            Capability::Pilot => &["fly"],
+            Capability::Wizard => &["fly", "cast"],
+            Capability::Animal => &["baby_name"],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PluginError {
+    Duplicate(String),
+    UnknownPlugin(String),
+    UnknownCommand {
+        plugin: String,
+        command: String,
+    },
+    // 插件存在，但没有注册这个能力
+    MissingCapability {
+        plugin: String,
+        capability: Capability,
+    },
+    // 同名的命令由多个能力提供，需要写成 "能力.命令"
+    Ambiguous {
+        plugin: String,
+        method: String,
+        candidates: Vec<Capability>,
+    },
+    BadArguments {
+        command: String,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for PluginError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PluginError::Duplicate(name) => write!(f, "plugin {:?} is already registered", name),
+            PluginError::UnknownPlugin(name) => write!(f, "no plugin named {:?}", name),
+            PluginError::UnknownCommand { plugin, command } => {
+                write!(f, "plugin {:?} has no command {:?}", plugin, command)
+            }
+            PluginError::MissingCapability { plugin, capability } => write!(
+                f,
+                "plugin {:?} does not have the {} capability",
+                plugin,
+                capability.name()
+            ),
+            PluginError::Ambiguous {
+                plugin,
+                method,
+                candidates,
+            } => {
+                let names: Vec<String> = candidates
+                    .iter()
+                    .map(|c| format!("{}.{}", c.name(), method))
+                    .collect();
+                write!(
+                    f,
+                    "{:?} is ambiguous for plugin {:?}, use one of: {}",
+                    method,
+                    plugin,
+                    names.join(", ")
+                )
+            }
+            PluginError::BadArguments {
+                command,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{} takes {} argument(s), {} given",
+                command, expected, found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PluginError {}
+
+// 同一个插件实例以不同的特征对象保存，各能力共享这一个实例
+#[derive(Default)]
+struct Entry {
+    description: String,
+    pilot: Option<Rc<dyn Pilot>>,
+    wizard: Option<Rc<dyn Wizard>>,
+    baby_name: Option<fn() -> String>,
+}
+
+impl Entry {
+    fn has(&self, capability: Capability) -> bool {
+        match capability {
+            Capability::Pilot => self.pilot.is_some(),
+            Capability::Wizard => self.wizard.is_some(),
+            Capability::Animal => self.baby_name.is_some(),
+        }
+    }
+    fn capabilities(&self) -> Vec<Capability> {
+        Capability::ALL
+            .into_iter()
+            .filter(|&c| self.has(c))
+            .collect()
+    }
+}
+
+#[derive(Default)]
+pub struct PluginRegistry {
+    plugins: BTreeMap<String, Entry>,
+}
+
+// register 返回的注册器，每个方法只在 T 实现了对应特征时才能调用
+pub struct Registration<'a, T> {
+    entry: &'a mut Entry,
+    plugin: Rc<T>,
+}
+
+impl<T: 'static> Registration<'_, T> {
+    pub fn pilot(self) -> Self
+    where
+        T: Pilot,
+    {
+        self.entry.pilot = Some(self.plugin.clone());
+        self
+    }
+    pub fn wizard(self) -> Self
+    where
+        T: Wizard,
+    {
+        self.entry.wizard = Some(self.plugin.clone());
+        self
+    }
+    // 关联函数没有 self，只能通过完全限定语法指明用的是 T 作为 Animal 的实现
+    pub fn animal(self) -> Self
+    where
+        T: Animal,
+    {
+        self.entry.baby_name = Some(<T as Animal>::baby_name);
+        self
+    }
+}
+
+impl PluginRegistry {
+    pub fn new() -> Self {
+        PluginRegistry::default()
+    }
+
+    pub fn register<T: 'static>(
+        &mut self,
+        name: &str,
+        description: &str,
+        plugin: T,
+    ) -> Result<Registration<'_, T>, PluginError> {
+        if self.plugins.contains_key(name) {
+            return Err(PluginError::Duplicate(name.to_string()));
+        }
+        let entry = self.plugins.entry(name.to_string()).or_insert(Entry {
+            description: description.to_string(),
+            ..Entry::default()
+        });
+        Ok(Registration {
+            entry,
+            plugin: Rc::new(plugin),
+        })
+    }
+
+    pub fn unregister(&mut self, name: &str) -> bool {
+        self.plugins.remove(name).is_some()
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.plugins.keys().map(String::as_str).collect()
+    }
+
+    pub fn capabilities(&self, name: &str) -> Result<Vec<Capability>, PluginError> {
+        Ok(self.entry(name)?.capabilities())
+    }
+
+    // 所有具备某个能力的插件
+    pub fn with_capability(&self, capability: Capability) -> Vec<&str> {
+        self.plugins
+            .iter()
+            .filter(|(_, entry)| entry.has(capability))
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    fn entry(&self, name: &str) -> Result<&Entry, PluginError> {
+        self.plugins
+            .get(name)
+            .ok_or_else(|| PluginError::UnknownPlugin(name.to_string()))
+    }
+
+    // 找出命令对应的能力：带能力名的直接用，不带的在插件已有的能力里找唯一提供它的那个
+    pub fn resolve(
+        &self,
+        plugin: &str,
+        command: &str,
+    ) -> Result<(Capability, String), PluginError> {
+        let entry = self.entry(plugin)?;
+        let unknown = || PluginError::UnknownCommand {
+            plugin: plugin.to_string(),
+            command: command.to_string(),
+        };
+        if let Some((capability, method)) = command.split_once('.') {
+            let capability = Capability::from_name(capability).ok_or_else(unknown)?;
+            if !capability.methods().contains(&method) {
+                return Err(unknown());
+            }
+            if !entry.has(capability) {
+                return Err(PluginError::MissingCapability {
+                    plugin: plugin.to_string(),
+                    capability,
+                });
+            }
+            return Ok((capability, method.to_string()));
+        }
+        let candidates: Vec<Capability> = entry
+            .capabilities()
+            .into_iter()
+            .filter(|c| c.methods().contains(&command))
+            .collect();
+        match candidates.as_slice() {
+            [] => Err(unknown()),
+            [capability] => Ok((*capability, command.to_string())),
+            _ => Err(PluginError::Ambiguous {
+                plugin: plugin.to_string(),
+                method: command.to_string(),
+                candidates,
+            }),
+        }
+    }
+
+    pub fn invoke(
+        &self,
+        plugin: &str,
+        command: &str,
+        args: &[&str],
+    ) -> Result<String, PluginError> {
+        let (capability, method) = self.resolve(plugin, command)?;
+        let entry = self.entry(plugin)?;
+        let expect = |expected: usize| {
+            if args.len() == expected {
+                Ok(())
+            } else {
+                Err(PluginError::BadArguments {
+                    command: format!("{}.{}", capability.name(), method),
+                    expected,
+                    found: args.len(),
+                })
+            }
+        };
+        // resolve 已经检查过插件有这个能力，下面的 unwrap 不会失败
+        match (capability, method.as_str()) {
+            (Capability::Pilot, "fly") => {
+                expect(0)?;
+                Ok(entry.pilot.as_ref().unwrap().fly())
+            }
+            (Capability::Wizard, "fly") => {
+                expect(0)?;
+                Ok(entry.wizard.as_ref().unwrap().fly())
+            }
+            (Capability::Wizard, "cast") => {
+                expect(1)?;
+                Ok(entry.wizard.as_ref().unwrap().cast(args[0]))
+            }
+            (Capability::Animal, "baby_name") => {
+                expect(0)?;
+                Ok((entry.baby_name.unwrap())())
+            }
+            _ => unreachable!("method list and dispatch are out of sync"),
+        }
+    }
+
+    // 插件列表：名字、能力、可用的命令、说明
+    pub fn list(&self) -> Table {
+        let mut table = Table::new(
+            "plugins",
+            &["plugin", "capabilities", "commands", "description"],
+        );
+        for (name, entry) in &self.plugins {
+            let capabilities = entry.capabilities();
+            let commands: Vec<String> = capabilities
+                .iter()
+                .flat_map(|c| {
+                    c.methods()
+                        .iter()
+                        .map(move |m| format!("{}.{}", c.name(), m))
+                })
+                .collect();
+            let names: Vec<&str> = capabilities.iter().map(|c| c.name()).collect();
+            table.rows.push(vec![
+                Cell::Text(name.clone()),
+                Cell::Text(names.join(", ")),
+                Cell::Text(commands.join(" ")),
+                Cell::Text(entry.description.clone()),
+            ]);
+        }
+        table
+    }
+}
+
+struct Human;
+
+impl Pilot for Human {
+    fn fly(&self) -> String {
+        "This is your captain speaking".to_string()
+    }
+}
+
+impl Wizard for Human {
+    fn fly(&self) -> String {
+        "This is Harry Porter".to_string()
+    }
+    fn cast(&self, spell: &str) -> String {
+        format!("{}!", spell)
+    }
+}
+
+impl Human {
+    // 类型自己的同名方法不属于任何能力，注册表不会调用它
+    fn fly(&self) -> String {
+        "*waving arms".to_string()
+    }
+}
+
+struct Dog;
+
+impl Dog {
+    fn baby_name() -> String {
+        String::from("doggy")
+    }
+}
+
+impl Animal for Dog {
+    fn baby_name() -> String {
+        String::from("puppy")
+    }
+}
+
+// 有状态的插件
+struct Owl {
+    name: String,
+}
+
+impl Wizard for Owl {
+    fn fly(&self) -> String {
+        format!("{} delivers the mail", self.name)
+    }
+    fn cast(&self, spell: &str) -> String {
+        format!("{} hoots {:?}, nothing happens", self.name, spell)
+    }
+}
+
+impl Animal for Owl {
+    fn baby_name() -> String {
+        String::from("owlet")
+    }
+}
+
+pub fn default_registry() -> PluginRegistry {
+    let mut registry = PluginRegistry::new();
+    registry
+        .register("human", "a person who is both pilot and wizard", Human)
+        .unwrap()
+        .pilot()
+        .wizard();
+    registry.register("dog", "good boy", Dog).unwrap().animal();
+    registry
+        .register(
+            "hedwig",
+            "a snowy owl",
+            Owl {
+                name: "Hedwig".to_string(),
+            },
+        )
+        .unwrap()
+        .wizard()
+        .animal();
+    registry
+}
+
+// cargo run -- plugins [插件 命令 参数...]
+pub(crate) fn plugin_main(args: &[String]) {
+    let registry = default_registry();
+    match args {
+        [] => println!("{}", registry.list()),
+        [plugin, command, rest @ ..] => {
+            let rest: Vec<&str> = rest.iter().map(String::as_str).collect();
+            match registry.invoke(plugin, command, &rest) {
+                Ok(output) => println!("{}", output),
+                Err(error) => {
+                    eprintln!("error: {}", error);
+                    std::process::exit(1);
+                }
+            }
+        }
+        [_] => {
+            eprintln!("usage: plugins [<plugin> <command> [args...]]");
+            std::process::exit(1);
+        }
+    }
+}
+
+pub(crate) fn plugin() {
+    let mut registry = default_registry();
+    println!("{}", registry.list());
+    println!(
+        "插件: {:?}，human 的能力: {:?}，会魔法的: {:?}",
+        registry.names(),
+        registry.capabilities("human"),
+        registry.with_capability(Capability::Wizard)
+    );
+
+    // fly 在 human 身上有歧义，必须写清楚是哪个能力的 fly
+    for command in ["fly", "pilot.fly", "wizard.fly"] {
+        match registry.invoke("human", command, &[]) {
+            Ok(output) => println!("human {}: {}", command, output),
+            Err(error) => println!("human {}: {}", command, error),
+        }
+    }
+    println!(
+        "human cast: {:?}",
+        registry.invoke("human", "cast", &["Expelliarmus"])
+    );
+
+    // 类型自己的同名方法和注册表无关：注册表调用的是 <Dog as Animal>::baby_name
+    println!(
+        "Human.fly() = {}，Dog::baby_name() = {}，dog baby_name = {:?}",
+        Human.fly(),
+        Dog::baby_name(),
+        registry.invoke("dog", "baby_name", &[])
+    );
+
+    // 重新注册时没有声明能力，插件能找到但调不了命令
+    registry.unregister("dog");
+    if registry.register("dog", "registered again", Dog).is_ok() {
+        println!(
+            "重新注册的 dog: {:?}，{:?}",
+            registry.capabilities("dog"),
+            Capability::from_name("animal").map(|capability| registry.with_capability(capability))
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_lists_plugins_by_capability() {
+        let registry = default_registry();
+        assert_eq!(registry.names(), vec!["dog", "hedwig", "human"]);
+        assert_eq!(
+            registry.capabilities("human"),
+            Ok(vec![Capability::Pilot, Capability::Wizard])
+        );
+        assert_eq!(
+            registry.with_capability(Capability::Wizard),
+            vec!["hedwig", "human"]
+        );
+        assert_eq!(
+            registry.with_capability(Capability::Animal),
+            vec!["dog", "hedwig"]
+        );
+        let list = registry.list().to_string();
+        assert!(list.contains("human   pilot, wizard"));
+        assert!(list.contains("hedwig  wizard, animal  wizard.fly wizard.cast animal.baby_name"));
+    }
+
+    #[test]
+    fn ambiguous_method_needs_a_capability() {
+        let registry = default_registry();
+        assert_eq!(
+            registry.invoke("human", "fly", &[]),
+            Err(PluginError::Ambiguous {
+                plugin: "human".to_string(),
+                method: "fly".to_string(),
+                candidates: vec![Capability::Pilot, Capability::Wizard],
+            })
+        );
+        assert_eq!(
+            registry
+                .invoke("human", "fly", &[])
+                .unwrap_err()
+                .to_string(),
+            "\"fly\" is ambiguous for plugin \"human\", use one of: pilot.fly, wizard.fly"
+        );
+        assert_eq!(
+            registry.invoke("human", "pilot.fly", &[]).unwrap(),
+            "This is your captain speaking"
+        );
+        assert_eq!(
+            registry.invoke("human", "wizard.fly", &[]).unwrap(),
+            "This is Harry Porter"
+        );
+        // cast 只有 Wizard 提供，hedwig 只有一个 fly，都不需要写能力名
+        assert_eq!(
+            registry.invoke("human", "cast", &["Expelliarmus"]).unwrap(),
+            "Expelliarmus!"
+        );
+        assert_eq!(
+            registry.invoke("hedwig", "fly", &[]).unwrap(),
+            "Hedwig delivers the mail"
+        );
+    }
+
+    #[test]
+    fn associated_functions_use_the_trait_impl() {
+        let registry = default_registry();
+        assert_eq!(Human.fly(), "*waving arms");
+        assert_eq!(Dog::baby_name(), "doggy");
+        // 调用的是 <Dog as Animal>::baby_name，不是 Dog::baby_name
+        assert_eq!(registry.invoke("dog", "baby_name", &[]).unwrap(), "puppy");
+        assert_eq!(
+            registry.invoke("hedwig", "animal.baby_name", &[]).unwrap(),
+            "owlet"
+        );
+    }
+
+    #[test]
+    fn bad_commands_are_errors() {
+        let registry = default_registry();
+        assert_eq!(
+            registry.invoke("cat", "fly", &[]),
+            Err(PluginError::UnknownPlugin("cat".to_string()))
+        );
+        assert_eq!(
+            registry.invoke("dog", "fly", &[]),
+            Err(PluginError::UnknownCommand {
+                plugin: "dog".to_string(),
+                command: "fly".to_string()
+            })
+        );
+        assert_eq!(
+            registry.invoke("dog", "pilot.fly", &[]),
+            Err(PluginError::MissingCapability {
+                plugin: "dog".to_string(),
+                capability: Capability::Pilot
+            })
+        );
+        assert!(matches!(
+            registry.invoke("human", "pilot.cast", &[]),
+            Err(PluginError::UnknownCommand { .. })
+        ));
+        assert_eq!(
+            registry
+                .invoke("human", "cast", &[])
+                .unwrap_err()
+                .to_string(),
+            "wizard.cast takes 1 argument(s), 0 given"
+        );
+    }
+
+    #[test]
+    fn names_are_unique() {
+        let mut registry = default_registry();
+        assert!(matches!(
+            registry.register("dog", "another dog", Dog),
+            Err(PluginError::Duplicate(_))
+        ));
+        assert!(registry.unregister("dog"));
+        assert!(!registry.unregister("dog"));
+        registry.register("dog", "registered again", Dog).unwrap();
+        // 注册时没有声明能力的插件可以被找到，但不能调用任何命令
+        assert_eq!(registry.capabilities("dog"), Ok(vec![]));
+        assert_eq!(Capability::from_name("wizard"), Some(Capability::Wizard));
+        assert_eq!(Capability::from_name("muggle"), None);
+    }
+}
@@ -0,0 +1,448 @@
+// 泛型坐标点的向量运算
+// 只要 T 支持对应的运算符，Point<T> 就自动获得加减、取反、数乘、点积、叉积；
+// 长度、距离、插值、单位化需要开平方，只对浮点数 (Float) 提供
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+// 结构体中的字段类型也可以用泛型来定义，下面代码定义了一个坐标点 Point，它可以存放任何类型的坐标值
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Point<T> {
+    pub x: T,
+    pub y: T,
+}
+
+// 如果想让 x 和 y 即能类型相同，又能类型不同，需要使用不同的泛型参数
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PointT<T, U> {
+    pub x: T,
+    pub y: U,
+}
+
+// 三维的版本
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Point3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+// 标准库没有“浮点数”这个特征，这里自己定义一个，f32 和 f64 都实现它
+pub trait Float:
+    Copy
+    + PartialOrd
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+{
+    const ZERO: Self;
+    fn sqrt(self) -> Self;
+}
+
+impl Float for f32 {
+    const ZERO: Self = 0.0;
+    fn sqrt(self) -> Self {
+        f32::sqrt(self)
+    }
+}
+
+impl Float for f64 {
+    const ZERO: Self = 0.0;
+    fn sqrt(self) -> Self {
+        f64::sqrt(self)
+    }
+}
+
+// 使用泛型参数前，依然需要提前声明：impl<T>，只有提前声明了，我们才能在Point<T>中使用它，这样 Rust 就知道 Point 的尖括号中的类型是泛型而不是具体类型。需要注意的是，
+// 这里的 Point<T> 不再是泛型声明，而是一个完整的结构体类型，因为我们定义的结构体就是 Point<T> 而不再是 Point
+impl<T> Point<T> {
+    pub fn new(x: T, y: T) -> Self {
+        Point { x, y }
+    }
+    pub fn x(&self) -> &T {
+        &self.x
+    }
+    pub fn y(&self) -> &T {
+        &self.y
+    }
+}
+
+// 也可以只为满足特定约束的 T 实现方法
+impl<T> Point<T>
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    pub fn dot(self, other: Point<T>) -> T {
+        self.x * other.x + self.y * other.y
+    }
+    // 二维叉积是一个标量，等于三维叉积的 z 分量，正负号表示 other 在 self 的逆时针还是顺时针方向
+    pub fn cross(self, other: Point<T>) -> T {
+        self.x * other.y - self.y * other.x
+    }
+    pub fn length_squared(self) -> T {
+        self.dot(self)
+    }
+}
+
+impl<T: Float> Point<T> {
+    pub fn length(self) -> T {
+        self.length_squared().sqrt()
+    }
+    pub fn distance(self, other: Point<T>) -> T {
+        (other - self).length()
+    }
+    // 线性插值：t = 0 时是 self，t = 1 时是 other
+    pub fn lerp(self, other: Point<T>, t: T) -> Point<T> {
+        self + (other - self) * t
+    }
+    // 零向量没有方向，返回 None
+    pub fn normalize(self) -> Option<Point<T>> {
+        let length = self.length();
+        if length == T::ZERO {
+            None
+        } else {
+            Some(self / length)
+        }
+    }
+}
+
+impl<T: Add<Output = T>> Add for Point<T> {
+    type Output = Point<T>;
+    fn add(self, rhs: Point<T>) -> Point<T> {
+        Point::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Point<T> {
+    type Output = Point<T>;
+    fn sub(self, rhs: Point<T>) -> Point<T> {
+        Point::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl<T: Neg<Output = T>> Neg for Point<T> {
+    type Output = Point<T>;
+    fn neg(self) -> Point<T> {
+        Point::new(-self.x, -self.y)
+    }
+}
+
+// 数乘：Point<T> * T
+impl<T: Copy + Mul<Output = T>> Mul<T> for Point<T> {
+    type Output = Point<T>;
+    fn mul(self, rhs: T) -> Point<T> {
+        Point::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl<T: Copy + Div<Output = T>> Div<T> for Point<T> {
+    type Output = Point<T>;
+    fn div(self, rhs: T) -> Point<T> {
+        Point::new(self.x / rhs, self.y / rhs)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Point<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+// i32 转 f64 不会丢失精度，可以直接 From
+impl From<Point<i32>> for Point<f64> {
+    fn from(p: Point<i32>) -> Self {
+        Point::new(p.x as f64, p.y as f64)
+    }
+}
+
+// 反过来可能失败（NaN、无穷大、超出 i32 范围），所以是 TryFrom，成功时四舍五入
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OutOfRange(pub f64);
+
+impl fmt::Display for OutOfRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} can not be represented as i32", self.0)
+    }
+}
+
+impl TryFrom<Point<f64>> for Point<i32> {
+    type Error = OutOfRange;
+    fn try_from(p: Point<f64>) -> Result<Self, Self::Error> {
+        let convert = |v: f64| {
+            let rounded = v.round();
+            if rounded.is_finite() && rounded >= i32::MIN as f64 && rounded <= i32::MAX as f64 {
+                Ok(rounded as i32)
+            } else {
+                Err(OutOfRange(v))
+            }
+        };
+        Ok(Point::new(convert(p.x)?, convert(p.y)?))
+    }
+}
+
+// x 和 y 类型相同的 PointT 可以转成 Point
+impl<T> From<PointT<T, T>> for Point<T> {
+    fn from(p: PointT<T, T>) -> Self {
+        Point::new(p.x, p.y)
+    }
+}
+
+impl<T, U> PointT<T, U> {
+    pub fn new(x: T, y: U) -> Self {
+        PointT { x, y }
+    }
+    // 方法里还可以再声明自己的泛型参数：取 self 的 x 和 other 的 y 组成新的点
+    pub fn mixup<V, W>(self, other: PointT<V, W>) -> PointT<T, W> {
+        PointT {
+            x: self.x,
+            y: other.y,
+        }
+    }
+}
+
+// x 和 y 分别按各自的类型相加
+impl<T: Add<Output = T>, U: Add<Output = U>> Add for PointT<T, U> {
+    type Output = PointT<T, U>;
+    fn add(self, rhs: PointT<T, U>) -> PointT<T, U> {
+        PointT::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl<T: Sub<Output = T>, U: Sub<Output = U>> Sub for PointT<T, U> {
+    type Output = PointT<T, U>;
+    fn sub(self, rhs: PointT<T, U>) -> PointT<T, U> {
+        PointT::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl<T: Neg<Output = T>, U: Neg<Output = U>> Neg for PointT<T, U> {
+    type Output = PointT<T, U>;
+    fn neg(self) -> PointT<T, U> {
+        PointT::new(-self.x, -self.y)
+    }
+}
+
+impl<T> Point3<T> {
+    pub fn new(x: T, y: T, z: T) -> Self {
+        Point3 { x, y, z }
+    }
+}
+
+impl<T> Point3<T>
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    pub fn dot(self, other: Point3<T>) -> T {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+    // 三维叉积得到同时垂直于两个向量的向量
+    pub fn cross(self, other: Point3<T>) -> Point3<T> {
+        Point3::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+    pub fn length_squared(self) -> T {
+        self.dot(self)
+    }
+}
+
+impl<T: Float> Point3<T> {
+    pub fn length(self) -> T {
+        self.length_squared().sqrt()
+    }
+    pub fn distance(self, other: Point3<T>) -> T {
+        (other - self).length()
+    }
+    pub fn lerp(self, other: Point3<T>, t: T) -> Point3<T> {
+        self + (other - self) * t
+    }
+    pub fn normalize(self) -> Option<Point3<T>> {
+        let length = self.length();
+        if length == T::ZERO {
+            None
+        } else {
+            Some(self / length)
+        }
+    }
+}
+
+impl<T: Add<Output = T>> Add for Point3<T> {
+    type Output = Point3<T>;
+    fn add(self, rhs: Point3<T>) -> Point3<T> {
+        Point3::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Point3<T> {
+    type Output = Point3<T>;
+    fn sub(self, rhs: Point3<T>) -> Point3<T> {
+        Point3::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    }
+}
+
+impl<T: Neg<Output = T>> Neg for Point3<T> {
+    type Output = Point3<T>;
+    fn neg(self) -> Point3<T> {
+        Point3::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl<T: Copy + Mul<Output = T>> Mul<T> for Point3<T> {
+    type Output = Point3<T>;
+    fn mul(self, rhs: T) -> Point3<T> {
+        Point3::new(self.x * rhs, self.y * rhs, self.z * rhs)
+    }
+}
+
+impl<T: Copy + Div<Output = T>> Div<T> for Point3<T> {
+    type Output = Point3<T>;
+    fn div(self, rhs: T) -> Point3<T> {
+        Point3::new(self.x / rhs, self.y / rhs, self.z / rhs)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Point3<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {}, {})", self.x, self.y, self.z)
+    }
+}
+
+impl From<Point3<i32>> for Point3<f64> {
+    fn from(p: Point3<i32>) -> Self {
+        Point3::new(p.x as f64, p.y as f64, p.z as f64)
+    }
+}
+
+// 二维点补上 z 分量变成三维点
+impl<T> From<(Point<T>, T)> for Point3<T> {
+    fn from((p, z): (Point<T>, T)) -> Self {
+        Point3::new(p.x, p.y, z)
+    }
+}
+
+pub(crate) fn point() {
+    let a = Point::new(3, 4);
+    let b = Point::new(1, 2);
+    println!(
+        "a={} b={} a+b={} a-b={} -a={} a*2={} a/2={}",
+        a,
+        b,
+        a + b,
+        a - b,
+        -a,
+        a * 2,
+        a / 2
+    );
+    println!(
+        "x+y={} a.b={} a x b={}",
+        *a.x() + *a.y(),
+        a.dot(b),
+        a.cross(b)
+    );
+
+    // 整数点转成浮点点之后就能算长度了
+    let a: Point<f64> = a.into();
+    println!(
+        "|{}|={} distance={} lerp={} -> {}",
+        a,
+        a.length(),
+        a.distance(Point::new(0.0, 0.0)),
+        a.lerp(Point::new(5.0, 6.0), 0.5),
+        a.normalize().unwrap()
+    );
+    match Point::<i32>::try_from(Point::new(1e10, 0.0)) {
+        Ok(p) => println!("{}", p),
+        Err(e) => println!("{}", e),
+    }
+
+    let mixed = PointT::new(1.5, 2) + PointT::new(1.0, 3);
+    let mixup = (-mixed - PointT::new(0.5, 1)).mixup(PointT::new("hello", 'c'));
+    println!("mixup = ({}, {})", mixup.x, mixup.y);
+    println!("{}", Point::from(PointT::new(1, 2)));
+
+    let x = Point3::new(1.0, 0.0, 0.0);
+    let y = Point3::new(0.0, 1.0, 0.0);
+    let v: Point3<f64> = Point3::from((Point::new(2, 3), 6)).into();
+    println!(
+        "{} x {} = {}, x.y={}, |{}|={}",
+        x,
+        y,
+        x.cross(y),
+        x.dot(y),
+        v,
+        v.length()
+    );
+    println!(
+        "lerp={} distance={} {}",
+        x.lerp(y, 0.5) * 2.0,
+        (v / 7.0).distance(v.normalize().unwrap()),
+        -(x - y) + x
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integer_point_arithmetic() {
+        let a = Point::new(3, 4);
+        let b = Point::new(1, 2);
+        assert_eq!(*a.x() + *a.y(), 7);
+        assert_eq!(a + b, Point::new(4, 6));
+        assert_eq!(a - b, Point::new(2, 2));
+        assert_eq!(-a, Point::new(-3, -4));
+        assert_eq!(a * 2, Point::new(6, 8));
+        assert_eq!(a / 2, Point::new(1, 2));
+        assert_eq!(a.dot(b), 11);
+        assert_eq!(a.cross(b), 2);
+        assert_eq!(b.cross(a), -2);
+    }
+
+    #[test]
+    fn float_point_geometry() {
+        // 整数点转成浮点点之后就能算长度了
+        let a: Point<f64> = Point::new(3, 4).into();
+        assert_eq!(a.length(), 5.0);
+        assert_eq!(a.distance(Point::new(0.0, 0.0)), 5.0);
+        assert_eq!(a.lerp(Point::new(5.0, 6.0), 0.5), Point::new(4.0, 5.0));
+        assert_eq!(a.normalize(), Some(Point::new(0.6, 0.8)));
+        assert_eq!(Point::new(0.0f32, 0.0).normalize(), None);
+    }
+
+    #[test]
+    fn float_to_integer_rounds_and_checks_range() {
+        assert_eq!(
+            Point::<i32>::try_from(Point::new(2.5, -1.4)),
+            Ok(Point::new(3, -1))
+        );
+        assert!(Point::<i32>::try_from(Point::new(f64::NAN, 0.0)).is_err());
+        assert!(Point::<i32>::try_from(Point::new(1e10, 0.0)).is_err());
+    }
+
+    #[test]
+    fn mixed_point_types() {
+        let mixed = PointT::new(1.5, 2) + PointT::new(1.0, 3);
+        assert_eq!(mixed, PointT::new(2.5, 5));
+        assert_eq!(-mixed - PointT::new(0.5, 1), PointT::new(-3.0, -6));
+        let mixup = mixed.mixup(PointT::new("hello", 'c'));
+        assert_eq!((mixup.x, mixup.y), (2.5, 'c'));
+        assert_eq!(Point::from(PointT::new(1, 2)), Point::new(1, 2));
+    }
+
+    #[test]
+    fn point3_vector_math() {
+        let x = Point3::new(1.0, 0.0, 0.0);
+        let y = Point3::new(0.0, 1.0, 0.0);
+        assert_eq!(x.cross(y), Point3::new(0.0, 0.0, 1.0));
+        assert_eq!(x.dot(y), 0.0);
+        let v: Point3<f64> = Point3::new(2, 3, 6).into();
+        assert_eq!(v.length(), 7.0);
+        assert_eq!(Point3::from((Point::new(1, 2), 3)), Point3::new(1, 2, 3));
+        assert_eq!(x.lerp(y, 0.5) * 2.0, Point3::new(1.0, 1.0, 0.0));
+        assert_eq!((v / 7.0).distance(v.normalize().unwrap()), 0.0);
+        assert_eq!(-(x - y) + x, Point3::new(0.0, 1.0, 0.0));
+    }
+}
@@ -0,0 +1,457 @@
+// 定价规则引擎
+// 一张小票的原价由单价 x 数量得到，之后按固定的顺序套用各种规则，每条规则产生一行“调整”（折扣为负，税为正）：
+//   1. 阶梯价     买得越多单价越低
+//   2. 买 N 送 M  每买 N 件送 M 件
+//   3. 百分比折扣 针对某个商品或整单
+//   4. 固定金额折扣 针对某个商品或整单，最多减到 0
+//   5. 税         按折扣之后的金额计算，多条税率各自独立计算，不会“税上加税”
+// 同一阶段内按规则添加的顺序执行。顺序是固定的，所以同样的购物车和规则总能得到同样的结果。
+// 添加规则时就检查参数，负的立减金额、超过 100% 的折扣这类规则根本进不了规则表；
+// 金额计算全部用 checked 运算，溢出时返回 MoneyError::Overflow 而不是 panic。
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::money::{Currency, Money, MoneyError, Rate, Rounding};
+use crate::shop::ReceiptLine;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rule {
+    // (最少数量, 单价)，买的数量达到某一档就按那一档的单价
+    Tiered {
+        sku: String,
+        tiers: Vec<(u32, Money)>,
+    },
+    // 每 buy + free 件里有 free 件免费
+    BuyNGetM {
+        sku: String,
+        buy: u32,
+        free: u32,
+    },
+    // sku 为 None 时作用于整单
+    PercentOff {
+        sku: Option<String>,
+        rate: Rate,
+    },
+    FixedOff {
+        sku: Option<String>,
+        amount: Money,
+    },
+    Tax {
+        name: String,
+        rate: Rate,
+    },
+}
+
+impl Rule {
+    fn stage(&self) -> u8 {
+        match self {
+            Rule::Tiered { .. } => 1,
+            Rule::BuyNGetM { .. } => 2,
+            Rule::PercentOff { .. } => 3,
+            Rule::FixedOff { .. } => 4,
+            Rule::Tax { .. } => 5,
+        }
+    }
+    fn validate(&self) -> Result<(), RuleError> {
+        let rate_in_range = |rate: &Rate| {
+            if *rate < Rate::percent(0) || *rate > Rate::percent(100) {
+                Err(RuleError::RateOutOfRange(*rate))
+            } else {
+                Ok(())
+            }
+        };
+        match self {
+            Rule::Tiered { tiers, .. } => match tiers.iter().find(|(_, price)| price.minor() < 0) {
+                Some((_, price)) => Err(RuleError::NegativeAmount(*price)),
+                None => Ok(()),
+            },
+            Rule::BuyNGetM { sku, buy, free } => {
+                if *buy == 0 || *free == 0 || buy.checked_add(*free).is_none() {
+                    return Err(RuleError::InvalidGroup {
+                        sku: sku.clone(),
+                        buy: *buy,
+                        free: *free,
+                    });
+                }
+                Ok(())
+            }
+            Rule::PercentOff { rate, .. } | Rule::Tax { rate, .. } => rate_in_range(rate),
+            Rule::FixedOff { amount, .. } => {
+                if amount.minor() < 0 {
+                    return Err(RuleError::NegativeAmount(*amount));
+                }
+                Ok(())
+            }
+        }
+    }
+    fn money(&self) -> Vec<Money> {
+        match self {
+            Rule::Tiered { tiers, .. } => tiers.iter().map(|(_, price)| *price).collect(),
+            Rule::FixedOff { amount, .. } => vec![*amount],
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleError {
+    // 买 N 送 M 的 N 和 M 都必须大于 0，N + M 不能超出 u32
+    InvalidGroup { sku: String, buy: u32, free: u32 },
+    // 阶梯价的单价和立减的金额不能是负数
+    NegativeAmount(Money),
+    // 折扣和税率只能在 0 到 100% 之间
+    RateOutOfRange(Rate),
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RuleError::InvalidGroup { sku, buy, free } => {
+                write!(f, "invalid buy {} get {} rule for {}", buy, free, sku)
+            }
+            RuleError::NegativeAmount(amount) => write!(f, "negative amount {}", amount),
+            RuleError::RateOutOfRange(rate) => {
+                write!(f, "rate {} is not between 0% and 100%", rate)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RuleError {}
+
+// 小票上的一行调整
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Adjustment {
+    pub description: String,
+    // None 表示整单级别的调整
+    pub sku: Option<String>,
+    pub amount: Money,
+}
+
+#[derive(Debug, Clone)]
+pub struct PricingRules {
+    rules: Vec<Rule>,
+    rounding: Rounding,
+}
+
+impl Default for PricingRules {
+    fn default() -> Self {
+        PricingRules::new(Rounding::HalfEven)
+    }
+}
+
+impl PricingRules {
+    pub fn new(rounding: Rounding) -> Self {
+        PricingRules {
+            rules: Vec::new(),
+            rounding,
+        }
+    }
+
+    pub fn rule(mut self, rule: Rule) -> Result<Self, RuleError> {
+        rule.validate()?;
+        self.rules.push(rule);
+        Ok(self)
+    }
+
+    // 规则中的金额必须和商品的币种一致
+    pub fn apply(&self, lines: &[ReceiptLine]) -> Result<Vec<Adjustment>, MoneyError> {
+        let currency = match lines.first() {
+            Some(line) => line.unit_price.currency(),
+            None => return Ok(Vec::new()),
+        };
+        for money in self.rules.iter().flat_map(Rule::money) {
+            if money.currency() != currency {
+                return Err(MoneyError::CurrencyMismatch(currency, money.currency()));
+            }
+        }
+
+        // 每一行当前的金额，随着规则的套用不断变化
+        let mut line_totals: BTreeMap<&str, Money> = BTreeMap::new();
+        let mut quantities: BTreeMap<&str, u32> = BTreeMap::new();
+        for line in lines {
+            line_totals.insert(&line.sku, line.try_subtotal()?);
+            quantities.insert(&line.sku, line.quantity);
+        }
+        let mut order_adjustment = Money::zero(currency);
+        let mut adjustments = Vec::new();
+        let rounding = self.rounding;
+
+        let mut rules: Vec<&Rule> = self.rules.iter().collect();
+        // sort_by_key 是稳定排序，同一阶段内保持添加顺序
+        rules.sort_by_key(|rule| rule.stage());
+
+        let mut pre_tax: Option<Money> = None;
+        for rule in rules {
+            let mut push = |description: String, sku: Option<&str>, amount: Money| {
+                if !amount.is_zero() {
+                    adjustments.push(Adjustment {
+                        description,
+                        sku: sku.map(str::to_string),
+                        amount,
+                    });
+                }
+            };
+            match rule {
+                Rule::Tiered { sku, tiers } => {
+                    let Some(&quantity) = quantities.get(sku.as_str()) else {
+                        continue;
+                    };
+                    let tier = tiers
+                        .iter()
+                        .filter(|(min, _)| quantity >= *min)
+                        .max_by_key(|(min, _)| *min);
+                    if let Some((min, price)) = tier {
+                        let total = line_totals.get_mut(sku.as_str()).unwrap();
+                        let tiered = price.try_mul(quantity)?;
+                        let amount = tiered.try_add(-*total)?;
+                        *total = tiered;
+                        push(
+                            format!("{} 满 {} 件单价 {}", sku, min, price),
+                            Some(sku),
+                            amount,
+                        );
+                    }
+                }
+                Rule::BuyNGetM { sku, buy, free } => {
+                    let Some(&quantity) = quantities.get(sku.as_str()) else {
+                        continue;
+                    };
+                    // 添加规则时已经检查过 buy + free 不会溢出，free_items 也不会超过 quantity
+                    let free_items = quantity / (buy + free) * free;
+                    let total = line_totals.get_mut(sku.as_str()).unwrap();
+                    // 按当前的平均单价计算送出去的那几件值多少钱
+                    let amount = -total.mul_ratio(free_items as i64, quantity as i64, rounding);
+                    *total = *total + amount;
+                    push(format!("{} 买 {} 送 {}", sku, buy, free), Some(sku), amount);
+                }
+                Rule::PercentOff {
+                    sku: Some(sku),
+                    rate,
+                } => {
+                    if let Some(total) = line_totals.get_mut(sku.as_str()) {
+                        let amount = -total.apply_rate(*rate, rounding);
+                        *total = *total + amount;
+                        push(format!("{} 减 {}", sku, rate), Some(sku), amount);
+                    }
+                }
+                Rule::PercentOff { sku: None, rate } => {
+                    let current = sum(&line_totals, currency)?.try_add(order_adjustment)?;
+                    let amount = -current.apply_rate(*rate, rounding);
+                    order_adjustment = order_adjustment + amount;
+                    push(format!("整单减 {}", rate), None, amount);
+                }
+                Rule::FixedOff {
+                    sku: Some(sku),
+                    amount,
+                } => {
+                    if let Some(total) = line_totals.get_mut(sku.as_str()) {
+                        let amount = -(*amount).min(*total);
+                        *total = *total + amount;
+                        push(format!("{} 立减", sku), Some(sku), amount);
+                    }
+                }
+                Rule::FixedOff { sku: None, amount } => {
+                    let current = sum(&line_totals, currency)?.try_add(order_adjustment)?;
+                    let amount = -(*amount).min(current);
+                    order_adjustment = order_adjustment + amount;
+                    push("整单立减".to_string(), None, amount);
+                }
+                Rule::Tax { name, rate } => {
+                    let base = match pre_tax {
+                        Some(base) => base,
+                        None => {
+                            *pre_tax.insert(sum(&line_totals, currency)?.try_add(order_adjustment)?)
+                        }
+                    };
+                    push(
+                        format!("{} {}", name, rate),
+                        None,
+                        base.apply_rate(*rate, rounding),
+                    );
+                }
+            }
+        }
+        Ok(adjustments)
+    }
+}
+
+fn sum(totals: &BTreeMap<&str, Money>, currency: Currency) -> Result<Money, MoneyError> {
+    totals
+        .values()
+        .try_fold(Money::zero(currency), |acc, money| acc.try_add(*money))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(sku: &str, yuan: i64, quantity: u32) -> ReceiptLine {
+        ReceiptLine {
+            sku: sku.to_string(),
+            name: sku.to_string(),
+            unit_price: Money::from_major(yuan, Currency::Cny),
+            quantity,
+        }
+    }
+
+    fn build(rules: Vec<Rule>) -> Result<PricingRules, RuleError> {
+        rules
+            .into_iter()
+            .try_fold(PricingRules::default(), PricingRules::rule)
+    }
+
+    fn amounts(rules: &PricingRules, lines: &[ReceiptLine]) -> Vec<i64> {
+        rules
+            .apply(lines)
+            .unwrap()
+            .iter()
+            .map(|adjustment| adjustment.amount.minor())
+            .collect()
+    }
+
+    #[test]
+    fn fixed_discount_stops_at_zero() {
+        let rules = build(vec![
+            Rule::FixedOff {
+                sku: Some("A".to_string()),
+                amount: Money::from_major(50, Currency::Cny),
+            },
+            Rule::FixedOff {
+                sku: None,
+                amount: Money::from_major(100, Currency::Cny),
+            },
+        ])
+        .unwrap();
+        // A 只值 30 元，最多减 30；整单剩下 20 元，最多再减 20
+        assert_eq!(
+            amounts(&rules, &[line("A", 10, 3), line("B", 20, 1)]),
+            vec![-3000, -2000]
+        );
+    }
+
+    #[test]
+    fn taxes_do_not_compound() {
+        let rules = build(vec![
+            Rule::Tax {
+                name: "a".to_string(),
+                rate: Rate::percent(10),
+            },
+            Rule::Tax {
+                name: "b".to_string(),
+                rate: Rate::percent(5),
+            },
+        ])
+        .unwrap();
+        assert_eq!(amounts(&rules, &[line("A", 100, 1)]), vec![1000, 500]);
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected() {
+        let group = |buy, free| Rule::BuyNGetM {
+            sku: "A".to_string(),
+            buy,
+            free,
+        };
+        for (buy, free) in [(0, 0), (0, 1), (2, 0), (u32::MAX, 1)] {
+            assert_eq!(
+                PricingRules::default().rule(group(buy, free)).unwrap_err(),
+                RuleError::InvalidGroup {
+                    sku: "A".to_string(),
+                    buy,
+                    free
+                }
+            );
+        }
+        let minus_one = Money::from_major(-1, Currency::Cny);
+        assert_eq!(
+            build(vec![Rule::FixedOff {
+                sku: None,
+                amount: minus_one,
+            }])
+            .unwrap_err(),
+            RuleError::NegativeAmount(minus_one)
+        );
+        assert_eq!(
+            build(vec![Rule::Tiered {
+                sku: "A".to_string(),
+                tiers: vec![(1, Money::from_major(5, Currency::Cny)), (10, minus_one)],
+            }])
+            .unwrap_err(),
+            RuleError::NegativeAmount(minus_one)
+        );
+        for rate in [Rate::percent(101), Rate::basis_points(-1)] {
+            assert_eq!(
+                build(vec![Rule::PercentOff { sku: None, rate }]).unwrap_err(),
+                RuleError::RateOutOfRange(rate)
+            );
+            assert_eq!(
+                build(vec![Rule::Tax {
+                    name: "t".to_string(),
+                    rate,
+                }])
+                .unwrap_err(),
+                RuleError::RateOutOfRange(rate)
+            );
+        }
+        // 边界值本身是合法的
+        assert!(build(vec![
+            group(u32::MAX - 1, 1),
+            Rule::PercentOff {
+                sku: None,
+                rate: Rate::percent(100),
+            },
+            Rule::FixedOff {
+                sku: None,
+                amount: Money::zero(Currency::Cny),
+            },
+        ])
+        .is_ok());
+    }
+
+    #[test]
+    fn overflowing_totals_are_errors() {
+        let huge = ReceiptLine {
+            sku: "A".to_string(),
+            name: "A".to_string(),
+            unit_price: Money::new(i64::MAX / 2, Currency::Cny),
+            quantity: 3,
+        };
+        assert_eq!(
+            PricingRules::default().apply(std::slice::from_ref(&huge)),
+            Err(MoneyError::Overflow)
+        );
+        // 每一行不溢出，但整单合计溢出
+        let half = ReceiptLine {
+            sku: "B".to_string(),
+            quantity: 1,
+            ..huge.clone()
+        };
+        let whole_order = build(vec![Rule::PercentOff {
+            sku: None,
+            rate: Rate::percent(10),
+        }])
+        .unwrap();
+        assert_eq!(
+            whole_order.apply(&[
+                half.clone(),
+                ReceiptLine {
+                    sku: "C".to_string(),
+                    ..half.clone()
+                },
+                ReceiptLine {
+                    sku: "D".to_string(),
+                    ..half
+                }
+            ]),
+            Err(MoneyError::Overflow)
+        );
+        // 阶梯价的单价乘数量溢出
+        let tiered = build(vec![Rule::Tiered {
+            sku: "A".to_string(),
+            tiers: vec![(2, Money::new(i64::MAX, Currency::Cny))],
+        }])
+        .unwrap();
+        assert_eq!(tiered.apply(&[line("A", 1, 2)]), Err(MoneyError::Overflow));
+    }
+}
@@ -0,0 +1,403 @@
+// 销售报表
+// 从订单历史里汇总出几张表：各商店的营业额、各商品的营业额、畅销商品排行、每天的营业额。
+// 每张表都可以输出成对齐的文本（直接打印）或者 CSV（导入表格软件）。
+// 不同币种的金额不能相加，所以所有汇总都按币种分开统计。
+// SKU 只在一个商店内唯一，所以商品按 (商店, SKU) 区分。
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::history::Sale;
+use crate::money::{cny, Currency, Money};
+use crate::widget::display_width;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cell {
+    Text(String),
+    Count(u64),
+    Money(Money),
+}
+
+impl Cell {
+    // 数字靠右对齐
+    fn is_numeric(&self) -> bool {
+        !matches!(self, Cell::Text(_))
+    }
+    // CSV 里的金额不带货币符号，币种单独放一列
+    fn csv(&self) -> String {
+        match self {
+            Cell::Text(text) => text.clone(),
+            Cell::Count(count) => count.to_string(),
+            Cell::Money(money) => money.amount_string(),
+        }
+    }
+}
+
+impl fmt::Display for Cell {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Cell::Text(text) => write!(f, "{}", text),
+            Cell::Count(count) => write!(f, "{}", count),
+            Cell::Money(money) => write!(f, "{}", money),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table {
+    pub title: String,
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<Cell>>,
+}
+
+impl Table {
+    pub(crate) fn new(title: &str, headers: &[&str]) -> Self {
+        Table {
+            title: title.to_string(),
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        let headers: Vec<String> = self.headers.iter().map(|h| csv_field(h)).collect();
+        csv.push_str(&headers.join(","));
+        csv.push('\n');
+        for row in &self.rows {
+            let fields: Vec<String> = row.iter().map(|cell| csv_field(&cell.csv())).collect();
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+// 含有逗号、引号或换行的字段要用引号括起来，引号本身写两次（RFC 4180）
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+fn pad(text: &str, width: usize, right: bool) -> String {
+    let fill = " ".repeat(width.saturating_sub(display_width(text)));
+    if right {
+        format!("{}{}", fill, text)
+    } else {
+        format!("{}{}", text, fill)
+    }
+}
+
+impl fmt::Display for Table {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rows: Vec<Vec<String>> = self
+            .rows
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+            .collect();
+        let widths: Vec<usize> = (0..self.headers.len())
+            .map(|column| {
+                rows.iter()
+                    .filter_map(|row| row.get(column))
+                    .chain(Some(&self.headers[column]))
+                    .map(|text| display_width(text))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        // 一列里只要有数字就整列靠右
+        let right: Vec<bool> = (0..self.headers.len())
+            .map(|column| {
+                self.rows
+                    .iter()
+                    .any(|row| row.get(column).is_some_and(Cell::is_numeric))
+            })
+            .collect();
+        let line = |cells: Vec<&str>| {
+            cells
+                .iter()
+                .enumerate()
+                .map(|(i, text)| pad(text, widths[i], right[i]))
+                .collect::<Vec<_>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        };
+        writeln!(f, "=== {} ===", self.title)?;
+        writeln!(
+            f,
+            "{}",
+            line(self.headers.iter().map(String::as_str).collect())
+        )?;
+        let rule: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+        write!(f, "{}", line(rule.iter().map(String::as_str).collect()))?;
+        for row in &rows {
+            write!(f, "\n{}", line(row.iter().map(String::as_str).collect()))?;
+        }
+        Ok(())
+    }
+}
+
+// 订单数和金额的累加器
+#[derive(Debug, Clone, Copy)]
+struct Totals {
+    count: u64,
+    amount: Money,
+}
+
+impl Totals {
+    fn new(currency: Currency) -> Self {
+        Totals {
+            count: 0,
+            amount: Money::zero(currency),
+        }
+    }
+    fn add(&mut self, count: u64, amount: Money) {
+        self.count += count;
+        self.amount = self.amount + amount;
+    }
+}
+
+// 各商店的营业额（实付金额，包含整单折扣和税），按商店名排序
+pub fn revenue_by_shop(sales: &[Sale]) -> Table {
+    let mut totals: BTreeMap<(&str, Currency), Totals> = BTreeMap::new();
+    for sale in sales {
+        totals
+            .entry((&sale.shop, sale.currency))
+            .or_insert_with(|| Totals::new(sale.currency))
+            .add(1, sale.total);
+    }
+    let mut table = Table::new(
+        "revenue by shop",
+        &["shop", "currency", "orders", "revenue"],
+    );
+    for ((shop, currency), totals) in totals {
+        table.rows.push(vec![
+            Cell::Text(shop.to_string()),
+            Cell::Text(currency.code().to_string()),
+            Cell::Count(totals.count),
+            Cell::Money(totals.amount),
+        ]);
+    }
+    table
+}
+
+// 各商品的销量和营业额，营业额从高到低排列。
+// 商品的营业额只包含针对这个商品的折扣，所以同一个商店所有商品加起来不一定等于商店的营业额
+pub fn revenue_by_product(sales: &[Sale]) -> Table {
+    let mut table = Table::new(
+        "revenue by product",
+        &["shop", "sku", "name", "currency", "quantity", "revenue"],
+    );
+    let mut products: Vec<_> = product_totals(sales).into_iter().collect();
+    products.sort_by(|(a_key, a), (b_key, b)| {
+        (a.amount.currency(), b.amount.minor(), a_key).cmp(&(
+            b.amount.currency(),
+            a.amount.minor(),
+            b_key,
+        ))
+    });
+    for ((shop, sku, currency), totals) in products {
+        table.rows.push(vec![
+            Cell::Text(shop.to_string()),
+            Cell::Text(sku.to_string()),
+            Cell::Text(totals.name.to_string()),
+            Cell::Text(currency.code().to_string()),
+            Cell::Count(totals.quantity),
+            Cell::Money(totals.amount),
+        ]);
+    }
+    table
+}
+
+// 销量最高的 k 个商品，销量相同时按商店和 SKU 排序
+pub fn best_sellers(sales: &[Sale], k: usize) -> Table {
+    let mut quantities: BTreeMap<(&str, &str), (&str, u64)> = BTreeMap::new();
+    for sale in sales {
+        for line in &sale.lines {
+            let entry = quantities
+                .entry((&sale.shop, &line.sku))
+                .or_insert((&line.name, 0));
+            // 名称以最近一次卖出时为准
+            entry.0 = &line.name;
+            entry.1 += line.quantity as u64;
+        }
+    }
+    let mut ranked: Vec<_> = quantities.into_iter().collect();
+    // 稳定排序，BTreeMap 已经按商店和 SKU 排好了
+    ranked.sort_by(|(_, (_, a)), (_, (_, b))| b.cmp(a));
+    let mut table = Table::new("best sellers", &["rank", "shop", "sku", "name", "quantity"]);
+    for (rank, ((shop, sku), (name, quantity))) in ranked.into_iter().take(k).enumerate() {
+        table.rows.push(vec![
+            Cell::Count(rank as u64 + 1),
+            Cell::Text(shop.to_string()),
+            Cell::Text(sku.to_string()),
+            Cell::Text(name.to_string()),
+            Cell::Count(quantity),
+        ]);
+    }
+    table
+}
+
+// 每天（UTC）的订单数和营业额，按日期排序
+pub fn daily_totals(sales: &[Sale]) -> Table {
+    let mut totals: BTreeMap<(String, Currency), Totals> = BTreeMap::new();
+    for sale in sales {
+        totals
+            .entry((sale.date(), sale.currency))
+            .or_insert_with(|| Totals::new(sale.currency))
+            .add(1, sale.total);
+    }
+    let mut table = Table::new("daily totals", &["date", "currency", "orders", "revenue"]);
+    for ((date, currency), totals) in totals {
+        table.rows.push(vec![
+            Cell::Text(date),
+            Cell::Text(currency.code().to_string()),
+            Cell::Count(totals.count),
+            Cell::Money(totals.amount),
+        ]);
+    }
+    table
+}
+
+struct ProductTotals<'a> {
+    name: &'a str,
+    quantity: u64,
+    amount: Money,
+}
+
+fn product_totals(sales: &[Sale]) -> BTreeMap<(&str, &str, Currency), ProductTotals<'_>> {
+    let mut products = BTreeMap::new();
+    for sale in sales {
+        for line in &sale.lines {
+            let totals = products
+                .entry((sale.shop.as_str(), line.sku.as_str(), sale.currency))
+                .or_insert_with(|| ProductTotals {
+                    name: &line.name,
+                    quantity: 0,
+                    amount: Money::zero(sale.currency),
+                });
+            totals.name = &line.name;
+            totals.quantity += line.quantity as u64;
+            totals.amount = totals.amount + line.revenue;
+        }
+    }
+    products
+}
+
+fn sample_sales() -> Vec<Sale> {
+    use crate::history::SaleLine;
+
+    let line = |sku: &str, name: &str, quantity: u32, revenue: Money| SaleLine {
+        sku: sku.to_string(),
+        name: name.to_string(),
+        quantity,
+        revenue,
+    };
+    // 2024-03-01 00:00:00 UTC
+    let day = 1_709_251_200;
+    vec![
+        Sale {
+            timestamp: day + 3_600,
+            shop: "壹间万物".to_string(),
+            currency: Currency::Cny,
+            lines: vec![
+                line("C001", "瑰夏手冲", 2, cny("136")),
+                line("C002", "拿铁", 3, cny("96")),
+            ],
+            total: cny("212"),
+        },
+        Sale {
+            timestamp: day + 86_400 + 60,
+            shop: "壹间万物".to_string(),
+            currency: Currency::Cny,
+            lines: vec![line("C002", "拿铁", 4, cny("100"))],
+            total: cny("106"),
+        },
+        Sale {
+            timestamp: day + 7_200,
+            shop: "宝渝".to_string(),
+            currency: Currency::Cny,
+            lines: vec![line("X5", "BMW X5", 1, cny("699000"))],
+            total: cny("699000"),
+        },
+        Sale {
+            timestamp: day + 86_400 * 2,
+            shop: "Corner, \"Cafe\"".to_string(),
+            currency: Currency::Usd,
+            lines: vec![line("C002", "Latte", 1, Money::new(450, Currency::Usd))],
+            total: Money::new(450, Currency::Usd),
+        },
+    ]
+}
+
+pub(crate) fn report() {
+    let sales = sample_sales();
+    for table in [
+        revenue_by_shop(&sales),
+        revenue_by_product(&sales),
+        best_sellers(&sales, 2),
+        daily_totals(&sales),
+    ] {
+        println!("{}\n", table);
+    }
+    print!("{}", revenue_by_shop(&sales).to_csv());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_aggregate_sales() {
+        let sales = sample_sales();
+        let by_shop = revenue_by_shop(&sales);
+        assert_eq!(by_shop.rows.len(), 3);
+        assert_eq!(by_shop.rows[2][0], Cell::Text("宝渝".to_string()));
+        assert_eq!(by_shop.rows[1][2], Cell::Count(2));
+        assert_eq!(by_shop.rows[1][3], Cell::Money(cny("318")));
+
+        let by_product = revenue_by_product(&sales);
+        let skus: Vec<String> = by_product.rows.iter().map(|r| r[1].to_string()).collect();
+        assert_eq!(skus, vec!["X5", "C002", "C001", "C002"]);
+        assert_eq!(by_product.rows[1][4], Cell::Count(7));
+        assert_eq!(by_product.rows[1][5], Cell::Money(cny("196")));
+
+        let best = best_sellers(&sales, 2);
+        assert_eq!(best.rows.len(), 2);
+        assert_eq!(best.rows[0][3], Cell::Text("拿铁".to_string()));
+        assert_eq!(best.rows[1][3], Cell::Text("瑰夏手冲".to_string()));
+
+        let daily = daily_totals(&sales);
+        let dates: Vec<String> = daily.rows.iter().map(|r| r[0].to_string()).collect();
+        assert_eq!(dates, vec!["2024-03-01", "2024-03-02", "2024-03-03"]);
+        assert_eq!(daily.rows[0][3], Cell::Money(cny("699212")));
+    }
+
+    #[test]
+    fn csv_and_text_output() {
+        let sales = sample_sales();
+        let by_shop = revenue_by_shop(&sales);
+        // CSV：金额不带符号，特殊字符加引号
+        let csv = by_shop.to_csv();
+        assert_eq!(
+            csv,
+            "shop,currency,orders,revenue\n\
+             \"Corner, \"\"Cafe\"\"\",USD,1,4.50\n\
+             壹间万物,CNY,2,318.00\n\
+             宝渝,CNY,1,699000.00\n"
+        );
+
+        // 文本：数字列靠右，中文按两列宽度对齐
+        let text = by_shop.to_string();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[1], "shop            currency  orders     revenue");
+        assert_eq!(lines[4], "壹间万物        CNY            2     ¥318.00");
+        assert!(lines
+            .iter()
+            .skip(1)
+            .all(|line| display_width(line) == display_width(lines[1])));
+    }
+}
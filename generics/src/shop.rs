@@ -0,0 +1,779 @@
+// 商店和库存
+// 每个商店都有一份库存 Inventory（商品有 SKU、价格、库存数量），Goods 特征在此基础上提供目录查询和结账。
+// 结账要么整单成功、库存全部扣减，要么整单失败、库存一件都不动。
+// 价格用定点数 Money 表示，结账时可以传入 PricingRules 套用折扣和税。
+// 商店配置了订单历史 OrderHistory 时，每笔结账都先写入历史记录再扣库存，写入失败则整单失败。
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+
+use crate::history::OrderHistory;
+use crate::money::{cny, Currency, Money, MoneyError, Rate};
+use crate::pricing::{Adjustment, PricingRules, Rule};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Product {
+    pub sku: String,
+    pub name: String,
+    pub price: Money,
+    pub stock: u32,
+}
+
+impl Product {
+    pub fn new(sku: &str, name: &str, price: Money, stock: u32) -> Self {
+        Product {
+            sku: sku.to_string(),
+            name: name.to_string(),
+            price,
+            stock,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShopError {
+    // 商店里没有这个 SKU
+    UnknownSku(String),
+    // 库存不够
+    OutOfStock {
+        sku: String,
+        requested: u32,
+        available: u32,
+    },
+    // 购物车是空的
+    EmptyCart,
+    // 补货之后库存数量超出了 u32
+    StockOverflow {
+        sku: String,
+        stock: u32,
+        added: u32,
+    },
+    // 购物车里同一个 SKU 的数量超出了 u32
+    QuantityOverflow {
+        sku: String,
+        in_cart: u32,
+        added: u32,
+    },
+    // 购物车里的商品或定价规则用了不同的币种
+    Money(MoneyError),
+    // 订单历史写入失败，这笔交易没有发生
+    History(String),
+}
+
+impl fmt::Display for ShopError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShopError::UnknownSku(sku) => write!(f, "unknown sku {}", sku),
+            ShopError::OutOfStock {
+                sku,
+                requested,
+                available,
+            } => write!(
+                f,
+                "{} is out of stock: requested {}, available {}",
+                sku, requested, available
+            ),
+            ShopError::EmptyCart => write!(f, "the cart is empty"),
+            ShopError::StockOverflow { sku, stock, added } => write!(
+                f,
+                "stock of {} overflows: {} in stock, adding {}",
+                sku, stock, added
+            ),
+            ShopError::QuantityOverflow {
+                sku,
+                in_cart,
+                added,
+            } => write!(
+                f,
+                "quantity of {} overflows: {} in the cart, adding {}",
+                sku, in_cart, added
+            ),
+            ShopError::Money(e) => write!(f, "{}", e),
+            ShopError::History(e) => write!(f, "failed to record the order: {}", e),
+        }
+    }
+}
+
+impl Error for ShopError {}
+
+impl From<MoneyError> for ShopError {
+    fn from(e: MoneyError) -> Self {
+        ShopError::Money(e)
+    }
+}
+
+// 按 SKU 排序保存，目录输出的顺序是确定的
+#[derive(Debug, Default)]
+pub struct Inventory {
+    products: BTreeMap<String, Product>,
+}
+
+impl Inventory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    // SKU 已存在时只增加库存，价格和名称以新的为准；库存溢出时返回错误，原来的商品保持不变
+    pub fn add(&mut self, product: Product) -> Result<(), ShopError> {
+        match self.products.get_mut(&product.sku) {
+            Some(existing) => {
+                existing.stock = existing.stock.checked_add(product.stock).ok_or_else(|| {
+                    ShopError::StockOverflow {
+                        sku: product.sku.clone(),
+                        stock: existing.stock,
+                        added: product.stock,
+                    }
+                })?;
+                existing.name = product.name;
+                existing.price = product.price;
+            }
+            None => {
+                self.products.insert(product.sku.clone(), product);
+            }
+        }
+        Ok(())
+    }
+    pub fn get(&self, sku: &str) -> Option<&Product> {
+        self.products.get(sku)
+    }
+    pub fn products(&self) -> impl Iterator<Item = &Product> {
+        self.products.values()
+    }
+    // 按小票扣减库存，调用之前 quote_with 已经检查过库存足够
+    fn take(&mut self, lines: &[ReceiptLine]) {
+        for line in lines {
+            if let Some(product) = self.products.get_mut(&line.sku) {
+                product.stock -= line.quantity;
+            }
+        }
+    }
+}
+
+// 购物车只记录 SKU 和数量，结账时才去库存里查价格
+#[derive(Debug, Default, Clone)]
+pub struct Cart {
+    lines: BTreeMap<String, u32>,
+}
+
+impl Cart {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    // 同一个 SKU 加多次会合并数量，合并后超出 u32 时购物车不变
+    pub fn add(&mut self, sku: &str, quantity: u32) -> Result<&mut Self, ShopError> {
+        if quantity > 0 {
+            let in_cart = self.lines.get(sku).copied().unwrap_or(0);
+            let total =
+                in_cart
+                    .checked_add(quantity)
+                    .ok_or_else(|| ShopError::QuantityOverflow {
+                        sku: sku.to_string(),
+                        in_cart,
+                        added: quantity,
+                    })?;
+            self.lines.insert(sku.to_string(), total);
+        }
+        Ok(self)
+    }
+    // 减少数量，减到 0 就从购物车里移除
+    pub fn remove(&mut self, sku: &str, quantity: u32) -> &mut Self {
+        if let Some(current) = self.lines.get_mut(sku) {
+            *current = current.saturating_sub(quantity);
+            if *current == 0 {
+                self.lines.remove(sku);
+            }
+        }
+        self
+    }
+    pub fn lines(&self) -> impl Iterator<Item = (&str, u32)> {
+        self.lines
+            .iter()
+            .map(|(sku, quantity)| (sku.as_str(), *quantity))
+    }
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReceiptLine {
+    pub sku: String,
+    pub name: String,
+    pub unit_price: Money,
+    pub quantity: u32,
+}
+
+impl ReceiptLine {
+    pub fn subtotal(&self) -> Money {
+        self.unit_price * self.quantity
+    }
+    pub fn try_subtotal(&self) -> Result<Money, MoneyError> {
+        self.unit_price.try_mul(self.quantity)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Receipt {
+    pub shop: String,
+    pub currency: Currency,
+    pub lines: Vec<ReceiptLine>,
+    // 定价规则产生的折扣和税，按套用的顺序排列
+    pub adjustments: Vec<Adjustment>,
+}
+
+impl Receipt {
+    // 原价合计，结账时已经用 checked_total 检查过不会溢出
+    pub fn subtotal(&self) -> Money {
+        self.lines
+            .iter()
+            .fold(Money::zero(self.currency), |acc, line| {
+                acc + line.subtotal()
+            })
+    }
+    // 套用所有调整之后的应付金额
+    pub fn total(&self) -> Money {
+        self.adjustments
+            .iter()
+            .fold(self.subtotal(), |acc, adjustment| acc + adjustment.amount)
+    }
+    fn checked_total(&self) -> Result<Money, MoneyError> {
+        let subtotal = self
+            .lines
+            .iter()
+            .try_fold(Money::zero(self.currency), |acc, line| {
+                acc.try_add(line.try_subtotal()?)
+            })?;
+        self.adjustments
+            .iter()
+            .try_fold(subtotal, |acc, adjustment| acc.try_add(adjustment.amount))
+    }
+}
+
+// 打印出来的小票，列宽按内容对齐
+impl fmt::Display for Receipt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name_width = self
+            .lines
+            .iter()
+            .map(|line| line.name.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(4);
+        writeln!(f, "=== {} ===", self.shop)?;
+        for line in &self.lines {
+            // {:<width$} 按 char 计数填充，中文名字也能对齐字符数
+            writeln!(
+                f,
+                "{:<8} {:<width$} {:>3} x {:>10} = {:>10}",
+                line.sku,
+                line.name,
+                line.quantity,
+                line.unit_price,
+                line.subtotal(),
+                width = name_width
+            )?;
+        }
+        if !self.adjustments.is_empty() {
+            writeln!(f, "SUBTOTAL {:>27}", self.subtotal())?;
+            for adjustment in &self.adjustments {
+                writeln!(
+                    f,
+                    "  {:<24} {:>10}",
+                    adjustment.description, adjustment.amount
+                )?;
+            }
+        }
+        write!(f, "TOTAL {:>30}", self.total())
+    }
+}
+
+// 定义特征
+
+pub trait Goods {
+    // 实现者只需要告诉 Goods 商店叫什么、库存在哪里
+    fn name(&self) -> &str;
+    fn inventory(&self) -> &Inventory;
+    fn inventory_mut(&mut self) -> &mut Inventory;
+
+    fn sale(&self) -> String {
+        String::from("this is what you sale")
+    }
+    #[allow(non_snake_case)]
+    fn Buy(&self) -> String {
+        String::from("that is what you buy")
+    }
+
+    // 下面的目录查询和结账都有默认实现，全部建立在 inventory() 之上
+    fn catalog(&self) -> Vec<&Product> {
+        self.inventory().products().collect()
+    }
+    fn find(&self, sku: &str) -> Option<&Product> {
+        self.inventory().get(sku)
+    }
+    // 按名称关键字搜索
+    fn search(&self, keyword: &str) -> Vec<&Product> {
+        self.inventory()
+            .products()
+            .filter(|product| product.name.contains(keyword))
+            .collect()
+    }
+    fn in_stock(&self) -> Vec<&Product> {
+        self.inventory()
+            .products()
+            .filter(|product| product.stock > 0)
+            .collect()
+    }
+    // 订单历史，默认不记录
+    fn order_history(&self) -> Option<&OrderHistory> {
+        None
+    }
+    // 按原价结账
+    fn checkout(&mut self, cart: &Cart) -> Result<Receipt, ShopError> {
+        self.checkout_with(cart, &PricingRules::default())
+    }
+    // 先算出小票，写入订单历史之后才扣减库存，保证不会只卖出一半，也不会卖出去了却没有记录
+    fn checkout_with(&mut self, cart: &Cart, rules: &PricingRules) -> Result<Receipt, ShopError> {
+        let receipt = self.quote_with(cart, rules)?;
+        if let Some(history) = self.order_history() {
+            history
+                .record(&receipt)
+                .map_err(|e| ShopError::History(e.to_string()))?;
+        }
+        self.inventory_mut().take(&receipt.lines);
+        Ok(receipt)
+    }
+    // 检查购物车里每一行并计算价格，不改动库存
+    fn quote_with(&self, cart: &Cart, rules: &PricingRules) -> Result<Receipt, ShopError> {
+        if cart.is_empty() {
+            return Err(ShopError::EmptyCart);
+        }
+        let mut lines = Vec::new();
+        for (sku, quantity) in cart.lines() {
+            let product = self
+                .find(sku)
+                .ok_or_else(|| ShopError::UnknownSku(sku.to_string()))?;
+            if product.stock < quantity {
+                return Err(ShopError::OutOfStock {
+                    sku: sku.to_string(),
+                    requested: quantity,
+                    available: product.stock,
+                });
+            }
+            lines.push(ReceiptLine {
+                sku: product.sku.clone(),
+                name: product.name.clone(),
+                unit_price: product.price,
+                quantity,
+            });
+        }
+        let currency = lines[0].unit_price.currency();
+        if let Some(line) = lines.iter().find(|l| l.unit_price.currency() != currency) {
+            return Err(MoneyError::CurrencyMismatch(currency, line.unit_price.currency()).into());
+        }
+        let adjustments = rules.apply(&lines)?;
+        let receipt = Receipt {
+            shop: self.name().to_string(),
+            currency,
+            lines,
+            adjustments,
+        };
+        receipt.checked_total()?;
+        Ok(receipt)
+    }
+}
+
+pub struct CoffeeStore {
+    pub name: String,
+    pub coffee_type: String,
+    pub inventory: Inventory,
+    pub history: Option<OrderHistory>,
+}
+
+pub struct CarShop {
+    pub name: String,
+    pub car_brand: String,
+    pub inventory: Inventory,
+    pub history: Option<OrderHistory>,
+}
+
+pub struct DefaultShop {
+    pub name: String,
+    pub inventory: Inventory,
+    pub history: Option<OrderHistory>,
+}
+
+// 特征。和golang中的interface很像
+// 实现特征的语法与为结构体、枚举实现方法很像
+// 关于特征实现与定义的位置，有一条非常重要的原则：如果你想要为类型 A 实现特征 T，那么 A 或者 T 至少有一个是在当前作用域中定义的！
+// 例如我们可以为上面的 Post 类型实现标准库中的 Display 特征，这是因为 Post 类型定义在当前的作用域中。同时，我们也可以在当前包中为
+//  String 类型实现 Summary 特征，因为 Summary 定义在当前作用域中。
+// 但是你无法在当前作用域中，为 String 类型实现 Display 特征，因为它们俩都定义在标准库中，其定义所在的位置都不在当前作用域，跟你半毛钱关系都没有，看看就行了。
+// 该规则被称为孤儿规则，可以确保其它人编写的代码不会破坏你的代码，也确保了你不会莫名其妙就破坏了风马牛不相及的代码。
+// 默认实现允许调用相同特征中的其他方法，哪怕这些方法没有默认实现。如此，特征可以提供很多有用的功能而只需要实现指定的一小部分内容。
+impl Goods for CoffeeStore {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn inventory(&self) -> &Inventory {
+        &self.inventory
+    }
+    fn inventory_mut(&mut self) -> &mut Inventory {
+        &mut self.inventory
+    }
+    fn order_history(&self) -> Option<&OrderHistory> {
+        self.history.as_ref()
+    }
+    fn sale(&self) -> String {
+        format!(
+            "{} has some type of coffee,such as {}",
+            self.name, self.coffee_type
+        )
+    }
+}
+
+impl Goods for CarShop {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn inventory(&self) -> &Inventory {
+        &self.inventory
+    }
+    fn inventory_mut(&mut self) -> &mut Inventory {
+        &mut self.inventory
+    }
+    fn order_history(&self) -> Option<&OrderHistory> {
+        self.history.as_ref()
+    }
+    fn sale(&self) -> String {
+        format!(
+            "{} has some type of car, such as {}",
+            self.name, self.car_brand
+        )
+    }
+}
+
+impl Goods for DefaultShop {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn inventory(&self) -> &Inventory {
+        &self.inventory
+    }
+    fn inventory_mut(&mut self) -> &mut Inventory {
+        &mut self.inventory
+    }
+    fn order_history(&self) -> Option<&OrderHistory> {
+        self.history.as_ref()
+    }
+}
+
+// 演示和测试共用的咖啡店：C002 分两次补货，C003 没有库存
+fn coffee_store() -> CoffeeStore {
+    let mut coffee_store = CoffeeStore {
+        name: "壹间万物".to_string(),
+        coffee_type: "瑰夏".to_string(),
+        inventory: Inventory::new(),
+        history: None,
+    };
+    for product in [
+        Product::new("C001", "瑰夏手冲", cny("68"), 10),
+        Product::new("C002", "拿铁", cny("32"), 2),
+        Product::new("C003", "冷萃", cny("35"), 0),
+        Product::new("C002", "拿铁", cny("32"), 3),
+        Product::new("B001", "咖啡豆", cny("98"), 50),
+    ] {
+        coffee_store.inventory.add(product).unwrap();
+    }
+    coffee_store
+}
+
+// 定价规则：阶梯价 -> 买 N 送 M -> 百分比折扣 -> 固定折扣 -> 税
+fn coffee_rules() -> PricingRules {
+    // 规则添加的顺序不影响套用的顺序
+    [
+        Rule::Tax {
+            name: "增值税".to_string(),
+            rate: Rate::percent(6),
+        },
+        Rule::FixedOff {
+            sku: None,
+            amount: cny("20"),
+        },
+        Rule::PercentOff {
+            sku: Some("B001".to_string()),
+            rate: Rate::percent(10),
+        },
+        Rule::BuyNGetM {
+            sku: "C002".to_string(),
+            buy: 2,
+            free: 1,
+        },
+        Rule::Tiered {
+            sku: "B001".to_string(),
+            tiers: vec![(5, cny("88")), (10, cny("80"))],
+        },
+    ]
+    .into_iter()
+    .try_fold(PricingRules::default(), PricingRules::rule)
+    .unwrap()
+}
+
+pub(crate) fn shop() {
+    let mut coffee_store = coffee_store();
+    println!(
+        "{}: {} products, {} in stock, search 瑰夏 -> {}",
+        coffee_store.sale(),
+        coffee_store.catalog().len(),
+        coffee_store.in_stock().len(),
+        coffee_store.search("瑰夏")[0].sku
+    );
+
+    let mut cart = Cart::new();
+    cart.add("C001", 2).unwrap();
+    cart.add("C002", 1).unwrap();
+    cart.add("C002", 2).unwrap();
+    println!("{}", coffee_store.checkout(&cart).unwrap());
+
+    // 有一行库存不够，整单失败，其它行的库存也不会被扣
+    cart.add("C002", 1).unwrap();
+    println!("{}", coffee_store.checkout(&cart).unwrap_err());
+    cart.remove("C002", 4);
+    println!(
+        "without C002: {}",
+        coffee_store.checkout(&cart).unwrap().total()
+    );
+
+    let mut cart = Cart::new();
+    cart.add("B001", 6).unwrap();
+    cart.add("C002", 2).unwrap();
+    let receipt = coffee_store.checkout_with(&cart, &coffee_rules()).unwrap();
+    println!("{}", receipt);
+    // 不合法的规则在添加时就被拒绝
+    let error = PricingRules::default()
+        .rule(Rule::PercentOff {
+            sku: None,
+            rate: Rate::percent(120),
+        })
+        .unwrap_err();
+    println!("{}", error);
+
+    let mut car_shop = CarShop {
+        name: "宝渝".to_string(),
+        car_brand: "BMW".to_string(),
+        inventory: Inventory::new(),
+        history: None,
+    };
+    car_shop
+        .inventory
+        .add(Product::new("X5", "BMW X5", cny("699000"), 1))
+        .unwrap();
+    let mut cart = Cart::new();
+    cart.add("M3", 1).unwrap();
+    println!(
+        "{}: {}",
+        car_shop.sale(),
+        car_shop.checkout(&cart).unwrap_err()
+    );
+    let error = car_shop
+        .inventory
+        .add(Product::new("X5", "BMW X5", cny("699000"), u32::MAX))
+        .unwrap_err();
+    println!("{}", error);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catalog_queries() {
+        let coffee_store = coffee_store();
+        assert_eq!(coffee_store.catalog().len(), 4);
+        assert_eq!(coffee_store.find("C002").map(|p| p.stock), Some(5));
+        assert_eq!(coffee_store.in_stock().len(), 3);
+        assert_eq!(coffee_store.search("瑰夏")[0].sku, "C001");
+        let default = DefaultShop {
+            name: "default".to_string(),
+            inventory: Inventory::new(),
+            history: None,
+        };
+        assert!(default.catalog().is_empty());
+    }
+
+    #[test]
+    fn restocking_past_u32_max_is_rejected() {
+        let mut inventory = Inventory::new();
+        inventory
+            .add(Product::new("C001", "瑰夏手冲", cny("68"), u32::MAX - 1))
+            .unwrap();
+        assert_eq!(
+            inventory.add(Product::new("C001", "瑰夏", cny("1"), 2)),
+            Err(ShopError::StockOverflow {
+                sku: "C001".to_string(),
+                stock: u32::MAX - 1,
+                added: 2,
+            })
+        );
+        // 失败时名称、价格和库存都不变
+        let product = inventory.get("C001").unwrap();
+        assert_eq!(
+            (product.name.as_str(), product.stock),
+            ("瑰夏手冲", u32::MAX - 1)
+        );
+        assert_eq!(product.price, cny("68"));
+        inventory
+            .add(Product::new("C001", "瑰夏手冲", cny("68"), 1))
+            .unwrap();
+        assert_eq!(inventory.get("C001").unwrap().stock, u32::MAX);
+    }
+
+    #[test]
+    fn cart_quantity_past_u32_max_is_rejected() {
+        let mut cart = Cart::new();
+        cart.add("C001", u32::MAX - 1).unwrap();
+        assert_eq!(
+            cart.add("C001", 2).map(|cart| cart.lines().count()),
+            Err(ShopError::QuantityOverflow {
+                sku: "C001".to_string(),
+                in_cart: u32::MAX - 1,
+                added: 2,
+            })
+        );
+        // 失败时购物车不变，正好加到 u32::MAX 是可以的
+        assert_eq!(
+            cart.lines().collect::<Vec<_>>(),
+            vec![("C001", u32::MAX - 1)]
+        );
+        cart.add("C001", 1).unwrap();
+        assert_eq!(cart.lines().collect::<Vec<_>>(), vec![("C001", u32::MAX)]);
+    }
+
+    #[test]
+    fn checkout_deducts_stock() {
+        let mut coffee_store = coffee_store();
+        let mut cart = Cart::new();
+        cart.add("C001", 2).unwrap();
+        cart.add("C002", 1).unwrap();
+        cart.add("C002", 2).unwrap();
+        let receipt = coffee_store.checkout(&cart).unwrap();
+        assert_eq!(receipt.total(), cny("68") * 2 + cny("32") * 3);
+        assert_eq!(coffee_store.find("C001").unwrap().stock, 8);
+        assert_eq!(coffee_store.find("C002").unwrap().stock, 2);
+    }
+
+    #[test]
+    fn failed_checkout_leaves_stock_alone() {
+        // 有一行库存不够，整单失败，其它行的库存也不会被扣
+        let mut coffee_store = coffee_store();
+        let mut cart = Cart::new();
+        cart.add("C001", 1).unwrap();
+        cart.add("C002", 6).unwrap();
+        assert_eq!(
+            coffee_store.checkout(&cart),
+            Err(ShopError::OutOfStock {
+                sku: "C002".to_string(),
+                requested: 6,
+                available: 5,
+            })
+        );
+        assert_eq!(coffee_store.find("C001").unwrap().stock, 10);
+        cart.remove("C002", 1);
+        assert!(coffee_store.checkout(&cart).is_ok());
+
+        let mut car_shop = CarShop {
+            name: "宝渝".to_string(),
+            car_brand: "BMW".to_string(),
+            inventory: Inventory::new(),
+            history: None,
+        };
+        let mut cart = Cart::new();
+        cart.add("M3", 1).unwrap();
+        assert_eq!(
+            car_shop.checkout(&cart),
+            Err(ShopError::UnknownSku("M3".to_string()))
+        );
+        cart.remove("M3", 1);
+        assert_eq!(car_shop.checkout(&cart), Err(ShopError::EmptyCart));
+    }
+
+    #[test]
+    fn pricing_rules_apply_in_order() {
+        let mut coffee_store = coffee_store();
+        coffee_store
+            .inventory
+            .add(Product::new("C002", "拿铁", cny("32"), 10))
+            .unwrap();
+        let mut cart = Cart::new();
+        cart.add("B001", 6).unwrap();
+        cart.add("C002", 7).unwrap();
+        let receipt = coffee_store.checkout_with(&cart, &coffee_rules()).unwrap();
+        let amounts: Vec<Money> = receipt.adjustments.iter().map(|a| a.amount).collect();
+        // 阶梯价：6 件按 88 元：-60；拿铁 7 杯送 2 杯：-64；咖啡豆 528 打九折：-52.80；整单减 20；
+        // 税基 528 + 160 - 52.80 - 20 = 615.20，税 36.912 银行家舍入为 36.91
+        assert_eq!(
+            amounts,
+            vec![
+                cny("-60"),
+                cny("-64"),
+                cny("-52.8"),
+                cny("-20"),
+                cny("36.91")
+            ]
+        );
+        assert_eq!(receipt.subtotal(), cny("812"));
+        assert_eq!(receipt.total(), cny("652.11"));
+    }
+
+    #[test]
+    fn mismatched_currency_is_rejected() {
+        // 币种不一致的规则会被拒绝，库存不变
+        let mut coffee_store = coffee_store();
+        let usd = PricingRules::default()
+            .rule(Rule::FixedOff {
+                sku: None,
+                amount: Money::from_major(1, Currency::Usd),
+            })
+            .unwrap();
+        let mut cart = Cart::new();
+        cart.add("B001", 1).unwrap();
+        assert_eq!(
+            coffee_store.checkout_with(&cart, &usd),
+            Err(ShopError::Money(MoneyError::CurrencyMismatch(
+                Currency::Cny,
+                Currency::Usd
+            )))
+        );
+        assert_eq!(coffee_store.find("B001").unwrap().stock, 50);
+    }
+
+    #[test]
+    fn overflowing_receipt_is_rejected() {
+        // 单价乘数量、整单合计溢出都会被拒绝，库存不变
+        let mut car_shop = CarShop {
+            name: "宝渝".to_string(),
+            car_brand: "BMW".to_string(),
+            inventory: Inventory::new(),
+            history: None,
+        };
+        let price = Money::new(i64::MAX / 2, Currency::Cny);
+        for sku in ["M3", "M5"] {
+            car_shop
+                .inventory
+                .add(Product::new(sku, sku, price, 3))
+                .unwrap();
+        }
+        let mut cart = Cart::new();
+        cart.add("M3", 3).unwrap();
+        assert_eq!(
+            car_shop.checkout(&cart),
+            Err(ShopError::Money(MoneyError::Overflow))
+        );
+        let mut cart = Cart::new();
+        cart.add("M3", 1).unwrap();
+        cart.add("M5", 2).unwrap();
+        assert_eq!(
+            car_shop.checkout(&cart),
+            Err(ShopError::Money(MoneyError::Overflow))
+        );
+        assert_eq!(car_shop.find("M5").unwrap().stock, 3);
+    }
+}
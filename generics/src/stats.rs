@@ -0,0 +1,371 @@
+// 泛型统计函数
+// 原来的 largest 直接取 list[0]，空切片会 panic。这里的函数都接受任意 IntoIterator（切片、Vec、迭代器都行），
+// 结果一律用 Option 表示，输入为空时返回 None。
+//
+// 关于 NaN：f64 只实现了 PartialOrd，NaN 和任何值（包括它自己）都比较不出大小。
+// 这里的约定是：和自己都比较不出大小的值（x.partial_cmp(&x) 为 None）一律跳过，不参与统计；
+// 如果需要把 NaN 也排进去，可以用 *_by 版本并传入 f64::total_cmp 这样的全序比较函数。
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+// 实际上，泛型就是一种多态。泛型主要目的是为程序员提供编程的便利，减少代码的臃肿，同时可以极大地丰富语言本身的表达能力
+// 原来的写法：
+// fn largest<T: std::cmp::PartialOrd>(list: &[T]) -> &T {
+//     let mut largest = &list[0];
+//     for item in list.iter() {
+//         if item > largest {
+//             largest = item;
+//         }
+//     }
+//     largest
+// }
+
+//以下代码的问题
+// 主要是返回的T，目前的rust会判定T没有实现Copy,所以，无法返回一个完整所有权，只能返回一个借用
+
+// fn largest<T: std::cmp::PartialOrd>(list: &[T]) -> T {
+//     let mut largest = list[0];
+
+//     for &item in list.iter() {
+//         if item > largest {
+//             largest = item;
+//         }
+//     }
+
+//     largest
+// }
+
+// 改成 IntoIterator 之后，传 &Vec<T> 得到的是 Option<&T>，传 Vec<T> 得到的是 Option<T>，所有权问题自然就没有了
+
+fn is_ordered<T: PartialOrd>(x: &T) -> bool {
+    x.partial_cmp(x).is_some()
+}
+
+// 最大值，有多个相同的最大值时返回第一个
+pub fn largest<I>(iter: I) -> Option<I::Item>
+where
+    I: IntoIterator,
+    I::Item: PartialOrd,
+{
+    largest_by(iter.into_iter().filter(is_ordered), |a, b| {
+        a.partial_cmp(b).unwrap_or(Ordering::Equal)
+    })
+}
+
+pub fn smallest<I>(iter: I) -> Option<I::Item>
+where
+    I: IntoIterator,
+    I::Item: PartialOrd,
+{
+    smallest_by(iter.into_iter().filter(is_ordered), |a, b| {
+        a.partial_cmp(b).unwrap_or(Ordering::Equal)
+    })
+}
+
+// 用自定义的比较函数求最大值
+pub fn largest_by<I, F>(iter: I, mut compare: F) -> Option<I::Item>
+where
+    I: IntoIterator,
+    F: FnMut(&I::Item, &I::Item) -> Ordering,
+{
+    iter.into_iter().fold(None, |best, item| match best {
+        Some(best) if compare(&item, &best) != Ordering::Greater => Some(best),
+        _ => Some(item),
+    })
+}
+
+pub fn smallest_by<I, F>(iter: I, mut compare: F) -> Option<I::Item>
+where
+    I: IntoIterator,
+    F: FnMut(&I::Item, &I::Item) -> Ordering,
+{
+    largest_by(iter, |a, b| compare(b, a))
+}
+
+// 按 key 求最大值，key 比较不出大小的元素（比如 key 是 NaN）会被跳过
+pub fn largest_by_key<I, K, F>(iter: I, mut key: F) -> Option<I::Item>
+where
+    I: IntoIterator,
+    K: PartialOrd,
+    F: FnMut(&I::Item) -> K,
+{
+    let keyed = iter
+        .into_iter()
+        .map(|item| (key(&item), item))
+        .filter(|(k, _)| is_ordered(k));
+    largest(keyed.map(ByKey)).map(|ByKey((_, item))| item)
+}
+
+pub fn smallest_by_key<I, K, F>(iter: I, mut key: F) -> Option<I::Item>
+where
+    I: IntoIterator,
+    K: PartialOrd,
+    F: FnMut(&I::Item) -> K,
+{
+    let keyed = iter
+        .into_iter()
+        .map(|item| (key(&item), item))
+        .filter(|(k, _)| is_ordered(k));
+    smallest(keyed.map(ByKey)).map(|ByKey((_, item))| item)
+}
+
+// 只按元组的第一个分量比较，元素本身不需要可比较
+struct ByKey<K, T>((K, T));
+
+impl<K: PartialEq, T> PartialEq for ByKey<K, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 .0 == other.0 .0
+    }
+}
+
+impl<K: PartialOrd, T> PartialOrd for ByKey<K, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.0 .0.partial_cmp(&other.0 .0)
+    }
+}
+
+// 一次遍历同时求最小值和最大值
+pub fn min_max<I>(iter: I) -> Option<(I::Item, I::Item)>
+where
+    I: IntoIterator,
+    I::Item: PartialOrd + Clone,
+{
+    iter.into_iter()
+        .filter(is_ordered)
+        .fold(None, |acc, item| match acc {
+            None => Some((item.clone(), item)),
+            Some((min, max)) => {
+                if item < min {
+                    Some((item, max))
+                } else if item > max {
+                    Some((min, item))
+                } else {
+                    Some((min, max))
+                }
+            }
+        })
+}
+
+// 最大的 k 个元素，从大到小排列；相等的元素保持原来的先后顺序
+pub fn top_k<I>(iter: I, k: usize) -> Vec<I::Item>
+where
+    I: IntoIterator,
+    I::Item: PartialOrd,
+{
+    top_k_by(iter.into_iter().filter(is_ordered), k, |a, b| {
+        a.partial_cmp(b).unwrap_or(Ordering::Equal)
+    })
+}
+
+pub fn top_k_by<I, F>(iter: I, k: usize, mut compare: F) -> Vec<I::Item>
+where
+    I: IntoIterator,
+    F: FnMut(&I::Item, &I::Item) -> Ordering,
+{
+    let mut items: Vec<I::Item> = iter.into_iter().collect();
+    // sort_by 是稳定排序
+    items.sort_by(|a, b| compare(b, a));
+    items.truncate(k);
+    items
+}
+
+// 中位数。元素个数为偶数时取中间两个数的平均值，所以结果统一是 f64
+pub fn median<I>(iter: I) -> Option<f64>
+where
+    I: IntoIterator,
+    I::Item: Into<f64>,
+{
+    let mut values: Vec<f64> = iter
+        .into_iter()
+        .map(Into::into)
+        .filter(|v: &f64| !v.is_nan())
+        .collect();
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    if values.len() % 2 == 1 {
+        Some(values[mid])
+    } else {
+        Some((values[mid - 1] + values[mid]) / 2.0)
+    }
+}
+
+// 众数，出现次数相同时返回最先出现的那个
+pub fn mode<I>(iter: I) -> Option<I::Item>
+where
+    I: IntoIterator,
+    I::Item: Eq + Hash,
+{
+    // 值 -> (出现次数, 第一次出现的位置)
+    let mut counts: HashMap<I::Item, (usize, usize)> = HashMap::new();
+    for (position, item) in iter.into_iter().enumerate() {
+        counts.entry(item).or_insert((0, position)).0 += 1;
+    }
+    counts
+        .into_iter()
+        .max_by(|(_, (count_a, pos_a)), (_, (count_b, pos_b))| {
+            count_a.cmp(count_b).then(pos_b.cmp(pos_a))
+        })
+        .map(|(item, _)| item)
+}
+
+// 增量计算平均值，不需要保存所有数据（Welford 算法，比先求和再相除更不容易溢出和丢精度）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RunningMean {
+    count: usize,
+    mean: f64,
+}
+
+impl RunningMean {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    // NaN 同样会被跳过，否则之后的平均值全都会变成 NaN
+    pub fn push<T: Into<f64>>(&mut self, value: T) {
+        let value = value.into();
+        if value.is_nan() {
+            return;
+        }
+        self.count += 1;
+        self.mean += (value - self.mean) / self.count as f64;
+    }
+    pub fn count(&self) -> usize {
+        self.count
+    }
+    pub fn mean(&self) -> Option<f64> {
+        if self.count == 0 {
+            None
+        } else {
+            Some(self.mean)
+        }
+    }
+}
+
+// 迭代器版本：依次产出截至当前元素的平均值
+pub fn running_mean<I>(iter: I) -> impl Iterator<Item = f64>
+where
+    I: IntoIterator,
+    I::Item: Into<f64>,
+{
+    let mut state = RunningMean::new();
+    iter.into_iter().filter_map(move |value| {
+        state.push(value);
+        state.mean()
+    })
+}
+
+pub(crate) fn stats() {
+    let number_list = vec![34, 50, 25, 100, 65];
+    println!(
+        "{:?}: largest={:?} smallest={:?} min_max={:?} top3={:?} median={:?}",
+        number_list,
+        largest(&number_list),
+        smallest(number_list.iter().copied()),
+        min_max(&number_list),
+        top_k(&number_list, 3),
+        median(number_list.iter().copied())
+    );
+
+    // 空输入返回 None，NaN 被跳过
+    let empty: Vec<i32> = Vec::new();
+    let floats = [2.5, f64::NAN, -1.0, 7.25, f64::NAN];
+    println!(
+        "empty largest={:?}, {:?}: min_max={:?} total_cmp largest={:?}",
+        largest(&empty),
+        floats,
+        min_max(floats),
+        largest_by(floats, f64::total_cmp)
+    );
+
+    // 自定义 key 和比较函数
+    let words = ["apple", "fig", "banana", "kiwi", "cherry"];
+    println!(
+        "longest={:?} shortest={:?} last={:?} top2 by length={:?}",
+        largest_by_key(words, |w| w.len()),
+        smallest_by_key(words, |w| w.len()),
+        smallest_by(words, |a, b| b.cmp(a)),
+        top_k_by(words, 2, |a, b| a.len().cmp(&b.len()))
+    );
+    println!("most common letter: {:?}", mode("hello world".chars()));
+
+    let mut mean = RunningMean::new();
+    for value in [10.0f32, 20.0, f32::NAN, 30.0] {
+        mean.push(value);
+    }
+    println!(
+        "mean of {} values: {:?}; running mean of {:?}: {:?}",
+        mean.count(),
+        mean.mean(),
+        number_list,
+        running_mean(number_list.clone()).collect::<Vec<_>>()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integer_statistics() {
+        let number_list = vec![34, 50, 25, 100, 65];
+        assert_eq!(largest(&number_list), Some(&100));
+        assert_eq!(smallest(number_list.iter().copied()), Some(25));
+        assert_eq!(min_max(&number_list), Some((&25, &100)));
+        assert_eq!(top_k(&number_list, 3), vec![&100, &65, &50]);
+        assert_eq!(median(number_list.iter().copied()), Some(50.0));
+    }
+
+    #[test]
+    fn empty_input_does_not_panic() {
+        let empty: Vec<i32> = Vec::new();
+        assert_eq!(largest(&empty), None);
+        assert_eq!(min_max(&empty), None);
+        assert_eq!(median(empty.clone()), None);
+        assert!(top_k(&empty, 3).is_empty());
+        assert_eq!(RunningMean::new().mean(), None);
+    }
+
+    #[test]
+    fn nan_is_skipped() {
+        let floats = [2.5, f64::NAN, -1.0, 7.25, f64::NAN];
+        assert_eq!(largest(floats), Some(7.25));
+        assert_eq!(smallest(floats), Some(-1.0));
+        assert_eq!(min_max(floats), Some((-1.0, 7.25)));
+        assert_eq!(median(floats), Some(2.5));
+        assert_eq!(largest([f64::NAN]), None);
+        // total_cmp 认为正的 NaN 比所有数都大
+        assert!(largest_by(floats, f64::total_cmp).unwrap().is_nan());
+    }
+
+    #[test]
+    fn custom_keys_and_comparators() {
+        let words = ["apple", "fig", "banana", "kiwi", "cherry"];
+        assert_eq!(largest_by_key(words, |w| w.len()), Some("banana"));
+        assert_eq!(smallest_by_key(words, |w| w.len()), Some("fig"));
+        assert_eq!(smallest_by(words, |a, b| b.cmp(a)), Some("kiwi"));
+        assert_eq!(
+            top_k_by(words, 2, |a, b| a.len().cmp(&b.len())),
+            vec!["banana", "cherry"]
+        );
+        let scores = [("a", 0.5), ("b", f64::NAN), ("c", 0.9)];
+        assert_eq!(largest_by_key(scores, |s| s.1), Some(("c", 0.9)));
+    }
+
+    #[test]
+    fn mode_and_running_mean() {
+        assert_eq!(mode([3, 1, 3, 2, 1]), Some(3));
+        assert_eq!(mode("hello world".chars()), Some('l'));
+        assert_eq!(mode(Vec::<u8>::new()), None);
+
+        let means: Vec<f64> = running_mean([1, 2, 3, 4]).collect();
+        assert_eq!(means, vec![1.0, 1.5, 2.0, 2.5]);
+        let mut mean = RunningMean::new();
+        for value in [10.0f32, 20.0, f32::NAN, 30.0] {
+            mean.push(value);
+        }
+        assert_eq!((mean.count(), mean.mean()), (3, Some(20.0)));
+    }
+}
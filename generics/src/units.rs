@@ -0,0 +1,531 @@
+// 量纲分析
+// main.rs 里的 Millimeters + Meters 只能处理一种组合。这里把“量纲”做成类型：
+//   - Quantity<Length>、Quantity<Time> 是不同的类型，米加秒编译不过；
+//   - 乘除法的结果类型由 DimMul / DimDiv 决定，长度乘长度得到面积，长度除以时间得到速度；
+//   - 数值统一按国际单位制（米、千克、秒）保存，Unit 只负责换算和显示，
+//     所以毫米加米不需要为每一对单位单独实现 Add。
+//
+// 下面这行编译不过，因为 Add 只对同一种量纲实现（测试 meters_plus_seconds_does_not_compile 会检查）：
+//     let wrong = Quantity::new(1.0, METER) + Quantity::new(1.0, SECOND);
+use std::fmt;
+use std::iter::Sum;
+use std::marker::PhantomData;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+// 量纲只在类型层面存在，不占空间
+pub trait Dimension: Copy {
+    const NAME: &'static str;
+    // 国际单位制下的符号，显示时默认用它
+    const SI_SYMBOL: &'static str;
+}
+
+// 两个量纲相乘、相除得到什么量纲
+pub trait DimMul<Rhs> {
+    type Output: Dimension;
+}
+pub trait DimDiv<Rhs> {
+    type Output: Dimension;
+}
+
+macro_rules! dimensions {
+    ($($name:ident => $desc:expr, $symbol:expr;)*) => {
+        $(
+            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            pub struct $name;
+            impl Dimension for $name {
+                const NAME: &'static str = $desc;
+                const SI_SYMBOL: &'static str = $symbol;
+            }
+        )*
+    };
+}
+
+dimensions! {
+    Scalar => "scalar", "";
+    Length => "length", "m";
+    Mass => "mass", "kg";
+    Time => "time", "s";
+    Area => "area", "m²";
+    Volume => "volume", "m³";
+    Speed => "speed", "m/s";
+    Acceleration => "acceleration", "m/s²";
+    Force => "force", "N";
+    Energy => "energy", "J";
+    Power => "power", "W";
+}
+
+// 乘法表，交换律需要两个方向都写
+macro_rules! dim_mul {
+    ($($a:ident * $b:ident = $out:ident;)*) => {
+        $(
+            impl DimMul<$b> for $a {
+                type Output = $out;
+            }
+        )*
+    };
+}
+
+macro_rules! dim_div {
+    ($($a:ident / $b:ident = $out:ident;)*) => {
+        $(
+            impl DimDiv<$b> for $a {
+                type Output = $out;
+            }
+        )*
+    };
+}
+
+dim_mul! {
+    Length * Length = Area;
+    Length * Area = Volume;
+    Area * Length = Volume;
+    Speed * Time = Length;
+    Time * Speed = Length;
+    Acceleration * Time = Speed;
+    Time * Acceleration = Speed;
+    Mass * Acceleration = Force;
+    Acceleration * Mass = Force;
+    Force * Length = Energy;
+    Length * Force = Energy;
+    Power * Time = Energy;
+    Time * Power = Energy;
+    Force * Speed = Power;
+    Speed * Force = Power;
+}
+
+dim_div! {
+    Length / Time = Speed;
+    Length / Speed = Time;
+    Speed / Time = Acceleration;
+    Speed / Acceleration = Time;
+    Area / Length = Length;
+    Volume / Length = Area;
+    Volume / Area = Length;
+    Force / Mass = Acceleration;
+    Force / Acceleration = Mass;
+    Energy / Length = Force;
+    Energy / Force = Length;
+    Energy / Time = Power;
+    Energy / Power = Time;
+}
+
+// 同一种量纲相除得到纯数
+impl<D: Dimension> DimDiv<D> for D {
+    type Output = Scalar;
+}
+
+// 单位：显示用的符号和换算到国际单位的倍数
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Unit<D> {
+    pub symbol: &'static str,
+    pub factor: f64,
+    dimension: PhantomData<D>,
+}
+
+impl<D: Dimension> Unit<D> {
+    pub const fn new(symbol: &'static str, factor: f64) -> Self {
+        Unit {
+            symbol,
+            factor,
+            dimension: PhantomData,
+        }
+    }
+}
+
+pub const MILLIMETER: Unit<Length> = Unit::new("mm", 0.001);
+pub const CENTIMETER: Unit<Length> = Unit::new("cm", 0.01);
+pub const METER: Unit<Length> = Unit::new("m", 1.0);
+pub const KILOMETER: Unit<Length> = Unit::new("km", 1000.0);
+pub const INCH: Unit<Length> = Unit::new("in", 0.0254);
+pub const FOOT: Unit<Length> = Unit::new("ft", 0.3048);
+pub const MILE: Unit<Length> = Unit::new("mi", 1609.344);
+
+pub const GRAM: Unit<Mass> = Unit::new("g", 0.001);
+pub const KILOGRAM: Unit<Mass> = Unit::new("kg", 1.0);
+pub const TONNE: Unit<Mass> = Unit::new("t", 1000.0);
+pub const POUND: Unit<Mass> = Unit::new("lb", 0.453_592_37);
+
+pub const MILLISECOND: Unit<Time> = Unit::new("ms", 0.001);
+pub const SECOND: Unit<Time> = Unit::new("s", 1.0);
+pub const MINUTE: Unit<Time> = Unit::new("min", 60.0);
+pub const HOUR: Unit<Time> = Unit::new("h", 3600.0);
+
+pub const SQUARE_METER: Unit<Area> = Unit::new("m²", 1.0);
+pub const HECTARE: Unit<Area> = Unit::new("ha", 10_000.0);
+pub const SQUARE_KILOMETER: Unit<Area> = Unit::new("km²", 1_000_000.0);
+
+pub const LITER: Unit<Volume> = Unit::new("L", 0.001);
+pub const CUBIC_METER: Unit<Volume> = Unit::new("m³", 1.0);
+
+pub const METER_PER_SECOND: Unit<Speed> = Unit::new("m/s", 1.0);
+pub const KILOMETER_PER_HOUR: Unit<Speed> = Unit::new("km/h", 1000.0 / 3600.0);
+pub const MILE_PER_HOUR: Unit<Speed> = Unit::new("mph", 1609.344 / 3600.0);
+
+pub const METER_PER_SECOND_SQUARED: Unit<Acceleration> = Unit::new("m/s²", 1.0);
+pub const NEWTON: Unit<Force> = Unit::new("N", 1.0);
+pub const JOULE: Unit<Energy> = Unit::new("J", 1.0);
+pub const KILOWATT_HOUR: Unit<Energy> = Unit::new("kWh", 3_600_000.0);
+pub const WATT: Unit<Power> = Unit::new("W", 1.0);
+pub const KILOWATT: Unit<Power> = Unit::new("kW", 1000.0);
+
+// 一个物理量，值按国际单位保存
+#[derive(Debug, Clone, Copy)]
+pub struct Quantity<D> {
+    si: f64,
+    dimension: PhantomData<D>,
+}
+
+impl<D: Dimension> Quantity<D> {
+    pub fn new(value: f64, unit: Unit<D>) -> Self {
+        Quantity::from_si(value * unit.factor)
+    }
+    pub fn from_si(si: f64) -> Self {
+        Quantity {
+            si,
+            dimension: PhantomData,
+        }
+    }
+    pub fn zero() -> Self {
+        Quantity::from_si(0.0)
+    }
+    pub fn si(self) -> f64 {
+        self.si
+    }
+    // 换算成指定单位下的数值
+    pub fn value_in(self, unit: Unit<D>) -> f64 {
+        self.si / unit.factor
+    }
+    // 按指定单位显示，精度用格式化参数控制，例如 format!("{:.1}", q.display_in(KILOMETER))
+    pub fn display_in(self, unit: Unit<D>) -> InUnit<D> {
+        InUnit {
+            quantity: self,
+            unit,
+        }
+    }
+    pub fn abs(self) -> Self {
+        Quantity::from_si(self.si.abs())
+    }
+    // 两个量在 tolerance 以内算相等，浮点数换算后很少能精确相等
+    pub fn approx_eq(self, other: Self, tolerance: Self) -> bool {
+        (self - other).abs() <= tolerance
+    }
+}
+
+impl Quantity<Scalar> {
+    pub fn value(self) -> f64 {
+        self.si
+    }
+}
+
+// 面积开方得到长度
+impl Quantity<Area> {
+    pub fn sqrt(self) -> Quantity<Length> {
+        Quantity::from_si(self.si.sqrt())
+    }
+}
+
+// 手写而不是 derive，derive 会要求量纲标记类型本身也能比较
+impl<D> PartialEq for Quantity<D> {
+    fn eq(&self, other: &Self) -> bool {
+        self.si == other.si
+    }
+}
+
+impl<D> PartialOrd for Quantity<D> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.si.partial_cmp(&other.si)
+    }
+}
+
+impl<D: Dimension> Add for Quantity<D> {
+    type Output = Quantity<D>;
+    fn add(self, rhs: Self) -> Self::Output {
+        Quantity::from_si(self.si + rhs.si)
+    }
+}
+
+impl<D: Dimension> Sub for Quantity<D> {
+    type Output = Quantity<D>;
+    fn sub(self, rhs: Self) -> Self::Output {
+        Quantity::from_si(self.si - rhs.si)
+    }
+}
+
+impl<D: Dimension> Neg for Quantity<D> {
+    type Output = Quantity<D>;
+    fn neg(self) -> Self::Output {
+        Quantity::from_si(-self.si)
+    }
+}
+
+impl<A: DimMul<B>, B> Mul<Quantity<B>> for Quantity<A> {
+    type Output = Quantity<A::Output>;
+    fn mul(self, rhs: Quantity<B>) -> Self::Output {
+        Quantity::from_si(self.si * rhs.si)
+    }
+}
+
+impl<A: DimDiv<B>, B> Div<Quantity<B>> for Quantity<A> {
+    type Output = Quantity<A::Output>;
+    fn div(self, rhs: Quantity<B>) -> Self::Output {
+        Quantity::from_si(self.si / rhs.si)
+    }
+}
+
+// 乘除纯数不改变量纲
+impl<D: Dimension> Mul<f64> for Quantity<D> {
+    type Output = Quantity<D>;
+    fn mul(self, rhs: f64) -> Self::Output {
+        Quantity::from_si(self.si * rhs)
+    }
+}
+
+impl<D: Dimension> Mul<Quantity<D>> for f64 {
+    type Output = Quantity<D>;
+    fn mul(self, rhs: Quantity<D>) -> Self::Output {
+        rhs * self
+    }
+}
+
+impl<D: Dimension> Div<f64> for Quantity<D> {
+    type Output = Quantity<D>;
+    fn div(self, rhs: f64) -> Self::Output {
+        Quantity::from_si(self.si / rhs)
+    }
+}
+
+impl<D: Dimension> Sum for Quantity<D> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Quantity::zero(), Add::add)
+    }
+}
+
+// 默认按国际单位显示
+impl<D: Dimension> fmt::Display for Quantity<D> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_value(f, self.si, D::SI_SYMBOL)
+    }
+}
+
+pub struct InUnit<D> {
+    quantity: Quantity<D>,
+    unit: Unit<D>,
+}
+
+impl<D: Dimension> fmt::Display for InUnit<D> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_value(f, self.quantity.value_in(self.unit), self.unit.symbol)
+    }
+}
+
+fn write_value(f: &mut fmt::Formatter, value: f64, symbol: &str) -> fmt::Result {
+    match f.precision() {
+        Some(precision) => write!(f, "{:.*}", precision, value)?,
+        None => write!(f, "{}", value)?,
+    }
+    if !symbol.is_empty() {
+        write!(f, " {}", symbol)?;
+    }
+    Ok(())
+}
+
+// 泛型函数同样受量纲约束：只要距离除以时间，就一定得到速度
+fn average_speed<I: IntoIterator<Item = (Quantity<Length>, Quantity<Time>)>>(
+    legs: I,
+) -> Quantity<Speed> {
+    let (distance, time) = legs.into_iter().fold(
+        (Quantity::zero(), Quantity::zero()),
+        |(d, t), (leg_d, leg_t)| (d + leg_d, t + leg_t),
+    );
+    distance / time
+}
+
+pub(crate) fn units() {
+    // Millimeters + Meters 的通用版本
+    let total = Quantity::new(500.0, MILLIMETER) + Quantity::new(2.0, METER);
+    println!(
+        "500 mm + 2 m = {} = {:.1}, 1 mile = {:.0} ft, 12 in = {:.3} ft",
+        total,
+        total.display_in(CENTIMETER),
+        Quantity::new(1.0, MILE).value_in(FOOT),
+        Quantity::new(12.0, INCH).value_in(FOOT)
+    );
+
+    // 长度 x 长度 = 面积，再乘长度 = 体积
+    let side = Quantity::new(100.0, METER);
+    let field: Quantity<Area> = side * side;
+    let tank: Quantity<Volume> = Quantity::new(1.0, METER)
+        * Quantity::new(50.0, CENTIMETER)
+        * Quantity::new(20.0, CENTIMETER);
+    println!(
+        "field {} ha (side {}), tank {:.1} L = {} m³, {} km² = {}",
+        field.value_in(HECTARE),
+        field.sqrt(),
+        tank.value_in(LITER),
+        tank.value_in(CUBIC_METER),
+        Quantity::new(1.0, SQUARE_KILOMETER).value_in(SQUARE_KILOMETER),
+        Quantity::new(1.0, SQUARE_KILOMETER).display_in(SQUARE_METER)
+    );
+
+    // 长度 / 时间 = 速度，速度 x 时间 = 距离
+    let speed: Quantity<Speed> = Quantity::new(100.0, KILOMETER) / Quantity::new(2.0, HOUR);
+    let distance: Quantity<Length> = speed * Quantity::new(30.0, MINUTE);
+    let trip = [
+        (Quantity::new(60.0, KILOMETER), Quantity::new(1.0, HOUR)),
+        (Quantity::new(20.0, KILOMETER), Quantity::new(30.0, MINUTE)),
+    ];
+    println!(
+        "{:.2} = {:.1} = {:.1}, 30 min -> {:.1}, trip average {:.1}",
+        speed,
+        speed.display_in(KILOMETER_PER_HOUR),
+        speed.display_in(MILE_PER_HOUR),
+        distance.display_in(KILOMETER),
+        average_speed(trip).display_in(KILOMETER_PER_HOUR)
+    );
+
+    // 牛顿第二定律：质量 x 加速度 = 力；力 x 距离 = 能量，能量 / 时间 = 功率
+    let acceleration: Quantity<Acceleration> =
+        Quantity::new(27.0, METER_PER_SECOND) / Quantity::new(9.0, SECOND);
+    let force: Quantity<Force> = Quantity::new(1.5, TONNE) * acceleration;
+    let work: Quantity<Energy> = force * Quantity::new(100.0, METER);
+    let power: Quantity<Power> = work / Quantity::new(9.0, SECOND);
+    let used: Quantity<Energy> = Quantity::new(2.0, KILOWATT) * Quantity::new(90.0, MINUTE);
+    println!(
+        "{} = {:.2}, {:.0} = {:.0}, 2 kW x 90 min = {} kWh = {}",
+        force,
+        force.display_in(NEWTON),
+        power.display_in(KILOWATT),
+        power.display_in(WATT),
+        used.value_in(KILOWATT_HOUR),
+        used.display_in(JOULE)
+    );
+    println!(
+        "{:.3} m/s², 1 lb = {} g, {} / {} = {}",
+        acceleration.value_in(METER_PER_SECOND_SQUARED),
+        Quantity::new(1.0, POUND).value_in(GRAM),
+        force,
+        acceleration,
+        (force / acceleration).display_in(KILOGRAM)
+    );
+
+    // 同量纲相除得到纯数，纯数乘除、求和、取反
+    let ticks = Quantity::new(1.0, SECOND) / Quantity::new(20.0, MILLISECOND);
+    let laps: Quantity<Length> = (0..4).map(|_| Quantity::new(400.0, METER)).sum();
+    println!(
+        "{} ticks, {} laps, {}, {} {} {}",
+        ticks,
+        laps,
+        (laps / 2.0 - laps).display_in(KILOMETER),
+        Scalar::NAME,
+        Length::NAME,
+        Power::NAME
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+
+    use super::*;
+
+    #[test]
+    fn area_and_volume_symbols() {
+        // 长度相乘得到的量默认按国际单位显示，符号带上标
+        let side = Quantity::new(3.0, METER);
+        assert_eq!((side * side).to_string(), "9 m²");
+        assert_eq!((side * side * side).to_string(), "27 m³");
+        let plot = Quantity::new(2.5, HECTARE);
+        assert_eq!(
+            format!("{:.1}", plot.display_in(SQUARE_METER)),
+            "25000.0 m²"
+        );
+        assert_eq!(
+            format!("{:.3}", plot.display_in(SQUARE_KILOMETER)),
+            "0.025 km²"
+        );
+        let bottle = Quantity::new(1.5, LITER);
+        assert_eq!(
+            format!("{:.4}", bottle.display_in(CUBIC_METER)),
+            "0.0015 m³"
+        );
+        assert_eq!(format!("{}", bottle.display_in(LITER)), "1.5 L");
+    }
+
+    #[test]
+    fn dividing_by_zero_follows_f64() {
+        // Div<f64> 不检查除数，结果和 f64 一样是无穷大或 NaN
+        let distance = Quantity::new(5.0, KILOMETER);
+        assert_eq!((distance / 0.0).si(), f64::INFINITY);
+        assert_eq!((-distance / 0.0).si(), f64::NEG_INFINITY);
+        assert_eq!((distance / 0.0).display_in(METER).to_string(), "inf m");
+        let nothing = Quantity::<Length>::zero() / 0.0;
+        assert!(nothing.si().is_nan());
+        // NaN 和谁都不相等，也没有大小
+        assert_ne!(nothing, nothing);
+        assert_eq!(nothing.partial_cmp(&distance), None);
+        assert!(!nothing.approx_eq(nothing, Quantity::new(1.0, METER)));
+    }
+
+    #[test]
+    fn mixed_units_and_empty_sums() {
+        // 不同单位相加按国际单位算，结果可以换成任意同量纲的单位
+        let total = Quantity::new(1.0, FOOT) + Quantity::new(6.0, INCH);
+        assert!(total.approx_eq(Quantity::new(45.72, CENTIMETER), Quantity::from_si(1e-12)));
+        assert!(Quantity::new(1.0, MINUTE) < Quantity::new(61_000.0, MILLISECOND));
+        let none: Quantity<Mass> = std::iter::empty().sum();
+        assert_eq!(none, Quantity::zero());
+        assert_eq!(
+            (Quantity::new(2.0, HOUR) / Quantity::new(30.0, MINUTE)).to_string(),
+            "4"
+        );
+    }
+
+    // 把 main 当成一个单独的 crate 编译，units.rs 以模块的形式引进来
+    fn compiles(name: &str, main: &str) -> bool {
+        let dir =
+            std::env::temp_dir().join(format!("generics-units-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("main.rs");
+        std::fs::write(
+            &source,
+            format!(
+                "#[path = {:?}]\nmod units;\nuse units::*;\nfn main() {{\n{}\n}}\n",
+                concat!(env!("CARGO_MANIFEST_DIR"), "/src/units.rs"),
+                main
+            ),
+        )
+        .unwrap();
+        let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+        let status = Command::new(rustc)
+            .args([
+                "--edition",
+                "2021",
+                "--emit=metadata",
+                "-A",
+                "warnings",
+                "--out-dir",
+            ])
+            .arg(&dir)
+            .arg(&source)
+            .output()
+            .unwrap()
+            .status;
+        std::fs::remove_dir_all(&dir).unwrap();
+        status.success()
+    }
+
+    #[test]
+    fn meters_plus_seconds_does_not_compile() {
+        // bin crate 不跑文档测试，只能自己调用 rustc 检查“编译不过”。同量纲相加的版本必须能编译，
+        // 这样失败的原因只可能是量纲不同
+        let add = |unit: &str| {
+            format!(
+                "let _ = Quantity::new(1.0, METER) + Quantity::new(1.0, {});",
+                unit
+            )
+        };
+        assert!(compiles("same", &add("MILLIMETER")));
+        assert!(!compiles("mixed", &add("SECOND")));
+    }
+}
@@ -0,0 +1,1088 @@
+// 终端组件
+// 原来的 Draw 只是 println! 一下组件的名字，这里让每个组件真正画到一块字符画布 Canvas 上：
+// 每个组件有自己的位置 (x, y) 和大小 (width, height)，Screen::run 按顺序把所有组件画到同一块画布上（后画的盖住先画的），
+// 然后整帧交给输出后端（纯文本、ANSI 彩色终端或 HTML，见 backend.rs）输出。
+// 组件只能画在自己的区域里，超出的部分会被裁掉；中日韩文字在终端里占两格，画布也按两格处理。
+use std::fmt;
+
+use crate::backend::{Backend, TextBackend};
+use crate::event::{Event, Handler, Key, Response};
+
+// 一个字符在终端里占几格
+pub(crate) fn char_width(c: char) -> usize {
+    match c as u32 {
+        0x1100..=0x115F
+        | 0x2E80..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6 => 2,
+        _ => 1,
+    }
+}
+
+pub(crate) fn display_width(text: &str) -> usize {
+    text.chars().map(char_width).sum()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+    pub fn right(&self) -> u32 {
+        self.x + self.width
+    }
+    pub fn bottom(&self) -> u32 {
+        self.y + self.height
+    }
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        x >= self.x && x < self.right() && y >= self.y && y < self.bottom()
+    }
+    // 两个矩形的重叠部分，不重叠时返回一个空矩形
+    pub fn intersect(&self, other: &Rect) -> Rect {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+        if right <= x || bottom <= y {
+            return Rect::new(x, y, 0, 0);
+        }
+        Rect::new(x, y, right - x, bottom - y)
+    }
+}
+
+// 宽字符占两格，第二格用 WIDE_TAIL 占位，输出时跳过
+const WIDE_TAIL: char = '\0';
+
+// 每一格的样式。这里只记录语义，具体是什么颜色、什么标签由输出后端决定，见 backend.rs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Style {
+    #[default]
+    Plain,
+    // 边框
+    Border,
+    // 需要突出的内容，比如按钮的文字
+    Accent,
+    // 不重要的内容，比如输入框的占位文字
+    Muted,
+    // 选中的项
+    Selected,
+    // 焦点标记
+    Focus,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Canvas {
+    width: u32,
+    height: u32,
+    cells: Vec<char>,
+    styles: Vec<Style>,
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32) -> Self {
+        // 在 usize 里相乘，u32 相乘在大画布上会溢出
+        let len = (width as usize)
+            .checked_mul(height as usize)
+            .expect("canvas is too large");
+        Canvas {
+            width,
+            height,
+            cells: vec![' '; len],
+            styles: vec![Style::Plain; len],
+        }
+    }
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+    pub fn bounds(&self) -> Rect {
+        Rect::new(0, 0, self.width, self.height)
+    }
+    fn index(&self, x: u32, y: u32) -> usize {
+        y as usize * self.width as usize + x as usize
+    }
+    pub fn get(&self, x: u32, y: u32) -> Option<char> {
+        if x < self.width && y < self.height {
+            Some(self.cells[self.index(x, y)])
+        } else {
+            None
+        }
+    }
+    pub fn style(&self, x: u32, y: u32) -> Option<Style> {
+        if x < self.width && y < self.height {
+            Some(self.styles[self.index(x, y)])
+        } else {
+            None
+        }
+    }
+    // 覆盖一格时，如果破坏了原来的宽字符，把它剩下的那一半清成空格
+    fn clear_cell(&mut self, x: u32, y: u32) {
+        let index = self.index(x, y);
+        if self.cells[index] == WIDE_TAIL && x > 0 {
+            self.cells[index - 1] = ' ';
+            self.styles[index - 1] = Style::Plain;
+        } else if char_width(self.cells[index]) == 2 && x + 1 < self.width {
+            self.cells[index + 1] = ' ';
+            self.styles[index + 1] = Style::Plain;
+        }
+        self.cells[index] = ' ';
+        self.styles[index] = Style::Plain;
+    }
+    fn set(&mut self, x: u32, y: u32, c: char, style: Style) {
+        self.clear_cell(x, y);
+        let index = self.index(x, y);
+        if char_width(c) == 2 {
+            self.clear_cell(x + 1, y);
+            self.cells[index + 1] = WIDE_TAIL;
+            self.styles[index + 1] = style;
+        }
+        self.cells[index] = c;
+        self.styles[index] = style;
+    }
+    // 第 y 行的文本
+    pub fn row(&self, y: u32) -> String {
+        let start = self.index(0, y);
+        self.cells[start..start + self.width as usize]
+            .iter()
+            .filter(|&&c| c != WIDE_TAIL)
+            .collect()
+    }
+    // 第 y 行按样式切成几段，相邻的同样式字符合并在一起，输出后端按段输出
+    pub fn runs(&self, y: u32) -> Vec<(Style, String)> {
+        let mut runs: Vec<(Style, String)> = Vec::new();
+        let start = self.index(0, y);
+        for index in start..start + self.width as usize {
+            let (c, style) = (self.cells[index], self.styles[index]);
+            if c == WIDE_TAIL {
+                continue;
+            }
+            match runs.last_mut() {
+                Some((last, text)) if *last == style => text.push(c),
+                _ => runs.push((style, c.to_string())),
+            }
+        }
+        runs
+    }
+    // 画布上的一块区域，坐标从区域左上角开始，超出区域的内容会被裁掉
+    pub fn region(&mut self, area: Rect) -> Region<'_> {
+        let area = area.intersect(&self.bounds());
+        Region {
+            canvas: self,
+            area,
+            style: Style::Plain,
+        }
+    }
+}
+
+impl fmt::Display for Canvas {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for y in 0..self.height {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", self.row(y))?;
+        }
+        Ok(())
+    }
+}
+
+pub struct Region<'a> {
+    canvas: &'a mut Canvas,
+    area: Rect,
+    // 之后画的字符都用这个样式
+    style: Style,
+}
+
+impl Region<'_> {
+    pub fn width(&self) -> u32 {
+        self.area.width
+    }
+    pub fn height(&self) -> u32 {
+        self.area.height
+    }
+    // 在 (x, y) 画一个字符，返回占用的格数；宽字符只剩一格放不下时什么都不画
+    pub fn put(&mut self, x: u32, y: u32, c: char) -> u32 {
+        let width = char_width(c) as u32;
+        if y >= self.area.height || x + width > self.area.width {
+            return 0;
+        }
+        self.canvas
+            .set(self.area.x + x, self.area.y + y, c, self.style);
+        width
+    }
+    // 从 (x, y) 开始写一行文字，到区域右边界为止，返回写了多少格
+    pub fn text(&mut self, x: u32, y: u32, text: &str) -> u32 {
+        let mut column = x;
+        for c in text.chars() {
+            let width = self.put(column, y, c);
+            if width == 0 {
+                break;
+            }
+            column += width;
+        }
+        column - x
+    }
+    pub fn fill(&mut self, c: char) {
+        for y in 0..self.area.height {
+            let mut x = 0;
+            while x < self.area.width {
+                x += self.put(x, y, c).max(1);
+            }
+        }
+    }
+    // 用 +、-、| 画一圈边框
+    pub fn border(&mut self) {
+        let (width, height) = (self.area.width, self.area.height);
+        if width == 0 || height == 0 {
+            return;
+        }
+        for x in 0..width {
+            self.put(x, 0, '-');
+            self.put(x, height - 1, '-');
+        }
+        for y in 0..height {
+            self.put(0, y, '|');
+            self.put(width - 1, y, '|');
+        }
+        for (x, y) in [
+            (0, 0),
+            (width - 1, 0),
+            (0, height - 1),
+            (width - 1, height - 1),
+        ] {
+            self.put(x, y, '+');
+        }
+    }
+    // 区域中的一块子区域，rect 是相对于当前区域的坐标，样式和当前区域一样
+    pub fn sub(&mut self, rect: Rect) -> Region<'_> {
+        let local = Rect::new(0, 0, self.area.width, self.area.height).intersect(&rect);
+        Region {
+            area: Rect::new(
+                self.area.x + local.x,
+                self.area.y + local.y,
+                local.width,
+                local.height,
+            ),
+            canvas: self.canvas,
+            style: self.style,
+        }
+    }
+    // 同一块区域，换一个样式来画
+    pub fn styled(&mut self, style: Style) -> Region<'_> {
+        Region {
+            canvas: self.canvas,
+            area: self.area,
+            style,
+        }
+    }
+}
+
+// 把文字按宽度折行：优先在空格处断开，一个词比整行还长时硬断开；\n 强制换行
+pub(crate) fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    if width == 0 {
+        return lines;
+    }
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+        for word in paragraph.split(' ') {
+            let needed = display_width(word) + if line.is_empty() { 0 } else { 1 };
+            if display_width(&line) + needed > width && !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            for c in word.chars() {
+                if display_width(&line) + char_width(c) > width {
+                    lines.push(std::mem::take(&mut line));
+                }
+                line.push(c);
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+// 组件都用 x、y、width、height 四个字段保存自己的位置和大小
+macro_rules! area_fields {
+    () => {
+        fn area(&self) -> Rect {
+            Rect::new(self.x, self.y, self.width, self.height)
+        }
+        fn set_area(&mut self, area: Rect) {
+            self.x = area.x;
+            self.y = area.y;
+            self.width = area.width;
+            self.height = area.height;
+        }
+    };
+}
+
+// 特征对象
+pub trait Draw {
+    // 组件在屏幕上的位置和大小
+    fn area(&self) -> Rect;
+    // 由布局容器调用，把组件放到计算好的位置上
+    fn set_area(&mut self, area: Rect);
+    // 在自己的区域里绘制，region 的坐标从组件左上角开始
+    fn draw(&self, region: &mut Region);
+
+    // 下面几个方法和事件有关，都有默认实现，不处理事件的组件（比如 Label、Image）不用管
+    // 能否通过 Tab 或点击获得焦点
+    fn focusable(&self) -> bool {
+        false
+    }
+    // 处理一个事件，点击的坐标是屏幕坐标。visible 是组件没被屏幕和容器裁掉的部分，
+    // 也是屏幕坐标，大小和 draw 拿到的区域一样
+    fn handle(&mut self, _event: &Event, _visible: Rect) -> Response {
+        Response::Ignored
+    }
+    // 获得焦点时，在 draw 画完之后再叠加一层标记
+    fn draw_focus(&self, _region: &mut Region) {}
+    // 容器的子组件，按绘制顺序排列
+    fn children(&self) -> Vec<&dyn Draw> {
+        Vec::new()
+    }
+    fn child_mut(&mut self, _index: usize) -> Option<&mut dyn Draw> {
+        None
+    }
+}
+
+// 回车和空格都相当于点击
+fn is_press(event: &Event) -> bool {
+    matches!(
+        event,
+        Event::Click { .. } | Event::Key(Key::Enter) | Event::Key(Key::Char(' '))
+    )
+}
+
+pub struct Button {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub label: String,
+}
+
+impl Button {
+    // 没有边框时 [ label ] 的起始列
+    fn bracket_x(&self, width: u32) -> u32 {
+        width.saturating_sub(display_width(&self.label) as u32 + 4) / 2
+    }
+}
+
+impl Draw for Button {
+    area_fields!();
+    // 高度够 3 行时画成带边框的按钮，否则画成 [ label ]
+    fn draw(&self, region: &mut Region) {
+        let label_width = display_width(&self.label) as u32;
+        if region.height() >= 3 {
+            region.styled(Style::Border).border();
+            let x = region.width().saturating_sub(label_width) / 2;
+            let y = region.height() / 2;
+            let inner = Rect::new(1, 1, region.width().saturating_sub(2), region.height() - 2);
+            region
+                .sub(inner)
+                .styled(Style::Accent)
+                .text(x.saturating_sub(1), y - 1, &self.label);
+        } else {
+            let text = format!("[ {} ]", self.label);
+            let (x, y) = (self.bracket_x(region.width()), region.height() / 2);
+            region.styled(Style::Accent).text(x, y, &text);
+        }
+    }
+    fn focusable(&self) -> bool {
+        true
+    }
+    fn handle(&mut self, event: &Event, _visible: Rect) -> Response {
+        if is_press(event) {
+            Response::Clicked
+        } else {
+            Response::Ignored
+        }
+    }
+    // 有边框时四个角换成 #，没有边框时 [ ] 换成 < >
+    fn draw_focus(&self, region: &mut Region) {
+        let region = &mut region.styled(Style::Focus);
+        let (width, height) = (region.width(), region.height());
+        if width == 0 || height == 0 {
+            return;
+        }
+        if height >= 3 {
+            for (x, y) in [
+                (0, 0),
+                (width - 1, 0),
+                (0, height - 1),
+                (width - 1, height - 1),
+            ] {
+                region.put(x, y, '#');
+            }
+        } else {
+            let x = self.bracket_x(width);
+            let y = height / 2;
+            region.put(x, y, '<');
+            region.put(x + display_width(&self.label) as u32 + 3, y, '>');
+        }
+    }
+}
+
+// pixel 按行排列，每个字符是一个像素：'1' 是实心，其它是空白，一行 width 个像素
+pub struct Image {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub pixel: String,
+}
+
+impl Draw for Image {
+    area_fields!();
+    fn draw(&self, region: &mut Region) {
+        if self.width == 0 {
+            return;
+        }
+        for (i, pixel) in self.pixel.chars().enumerate() {
+            let (x, y) = (i as u32 % self.width, i as u32 / self.width);
+            region.put(x, y, if pixel == '1' { '█' } else { ' ' });
+        }
+    }
+}
+
+// 多行文字，超出宽度自动折行，超出高度的部分被裁掉
+pub struct Label {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub text: String,
+}
+
+impl Draw for Label {
+    area_fields!();
+    fn draw(&self, region: &mut Region) {
+        // 按组件自己的宽度折行，被屏幕边缘裁掉时不会重新折行
+        for (y, line) in wrap(&self.text, self.width as usize).iter().enumerate() {
+            region.text(0, y as u32, line);
+        }
+    }
+}
+
+// 单行输入框，cursor 是光标前面的字符数。内容太长时向左滚动，保证光标所在的位置可见
+pub struct TextInput {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub value: String,
+    pub placeholder: String,
+    pub cursor: usize,
+}
+
+impl TextInput {
+    pub fn new(x: u32, y: u32, width: u32, placeholder: &str) -> Self {
+        TextInput {
+            x,
+            y,
+            width,
+            height: 1,
+            value: String::new(),
+            placeholder: placeholder.to_string(),
+            cursor: 0,
+        }
+    }
+    fn byte_index(&self, chars: usize) -> usize {
+        self.value
+            .char_indices()
+            .nth(chars)
+            .map_or(self.value.len(), |(i, _)| i)
+    }
+    pub fn insert(&mut self, c: char) {
+        let index = self.byte_index(self.cursor);
+        self.value.insert(index, c);
+        self.cursor += 1;
+    }
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            let index = self.byte_index(self.cursor);
+            self.value.remove(index);
+        }
+    }
+    pub fn left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+    pub fn right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.value.chars().count());
+    }
+    fn edited(&self) -> Response {
+        Response::Edited(self.value.clone())
+    }
+    // 为了让光标可见，第一个显示的字符的位置
+    fn scroll(&self, width: usize) -> usize {
+        let chars: Vec<char> = self.value.chars().collect();
+        let cursor = self.cursor.min(chars.len());
+        let mut start = 0;
+        // 光标本身也要占一格
+        while start < cursor
+            && chars[start..cursor]
+                .iter()
+                .map(|&c| char_width(c))
+                .sum::<usize>()
+                >= width
+        {
+            start += 1;
+        }
+        start
+    }
+}
+
+impl Draw for TextInput {
+    area_fields!();
+    fn draw(&self, region: &mut Region) {
+        let mut line = region.sub(Rect::new(0, 0, region.width(), 1));
+        line.styled(Style::Muted).fill('_');
+        if self.value.is_empty() {
+            line.styled(Style::Muted).text(0, 0, &self.placeholder);
+        } else {
+            let visible: String = self
+                .value
+                .chars()
+                .skip(self.scroll(line.width() as usize))
+                .collect();
+            line.text(0, 0, &visible);
+        }
+    }
+    fn focusable(&self) -> bool {
+        true
+    }
+    fn handle(&mut self, event: &Event, visible: Rect) -> Response {
+        match event {
+            Event::Key(Key::Char(c)) if !c.is_control() => {
+                self.insert(*c);
+                self.edited()
+            }
+            Event::Key(Key::Backspace) if self.cursor > 0 => {
+                self.backspace();
+                self.edited()
+            }
+            Event::Key(Key::Left) => {
+                self.left();
+                Response::Handled
+            }
+            Event::Key(Key::Right) => {
+                self.right();
+                Response::Handled
+            }
+            // 光标移到点击的位置，点在文字后面时移到末尾。滚动位置和 draw 一样按可见的宽度算
+            Event::Click { x, .. } => {
+                let start = self.scroll(visible.width as usize);
+                let target = x.saturating_sub(self.x) as usize;
+                let mut column = 0;
+                let mut cursor = start;
+                for c in self.value.chars().skip(start) {
+                    if column + char_width(c) > target {
+                        break;
+                    }
+                    column += char_width(c);
+                    cursor += 1;
+                }
+                self.cursor = cursor;
+                Response::Handled
+            }
+            _ => Response::Ignored,
+        }
+    }
+    // 光标在文字末尾时画成 |；在文字中间时一格放不下两个字符，只能保留文字
+    fn draw_focus(&self, region: &mut Region) {
+        let start = self.scroll(region.width() as usize);
+        let chars: Vec<char> = self.value.chars().collect();
+        if self.cursor >= chars.len() {
+            let column: usize = chars[start.min(chars.len())..]
+                .iter()
+                .map(|&c| char_width(c))
+                .sum();
+            region.styled(Style::Focus).put(column as u32, 0, '|');
+        }
+    }
+}
+
+pub struct Checkbox {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub label: String,
+    pub checked: bool,
+}
+
+impl Checkbox {
+    pub fn toggle(&mut self) {
+        self.checked = !self.checked;
+    }
+}
+
+impl Draw for Checkbox {
+    area_fields!();
+    fn draw(&self, region: &mut Region) {
+        let mark = if self.checked { 'x' } else { ' ' };
+        region.text(0, 0, &format!("[{}] {}", mark, self.label));
+        if self.checked {
+            region.styled(Style::Accent).put(1, 0, mark);
+        }
+    }
+    fn focusable(&self) -> bool {
+        true
+    }
+    fn handle(&mut self, event: &Event, _visible: Rect) -> Response {
+        if is_press(event) {
+            self.toggle();
+            Response::Toggled(self.checked)
+        } else {
+            Response::Ignored
+        }
+    }
+    fn draw_focus(&self, region: &mut Region) {
+        let mut region = region.styled(Style::Focus);
+        region.put(0, 0, '<');
+        region.put(2, 0, '>');
+    }
+}
+
+// 列表，选中的一项前面有 >，选中项总是在可见范围内
+pub struct List {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub items: Vec<String>,
+    pub selected: Option<usize>,
+}
+
+impl List {
+    pub fn select_next(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
+        self.selected = Some(match self.selected {
+            Some(i) => (i + 1).min(self.items.len() - 1),
+            None => 0,
+        });
+    }
+    pub fn select_previous(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
+        self.selected = Some(self.selected.map_or(0, |i| i.saturating_sub(1)));
+    }
+    fn offset(&self, height: usize) -> usize {
+        match self.selected {
+            Some(i) if height > 0 => (i + 1).saturating_sub(height),
+            _ => 0,
+        }
+    }
+}
+
+impl Draw for List {
+    area_fields!();
+    fn draw(&self, region: &mut Region) {
+        let height = region.height() as usize;
+        let offset = self.offset(height);
+        for (row, (i, item)) in self
+            .items
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(height)
+            .enumerate()
+        {
+            if self.selected == Some(i) {
+                region
+                    .styled(Style::Selected)
+                    .text(0, row as u32, &format!("> {}", item));
+            } else {
+                region.text(0, row as u32, &format!("  {}", item));
+            }
+        }
+    }
+    fn focusable(&self) -> bool {
+        true
+    }
+    fn handle(&mut self, event: &Event, visible: Rect) -> Response {
+        let before = self.selected;
+        match event {
+            Event::Key(Key::Up) => self.select_previous(),
+            Event::Key(Key::Down) => self.select_next(),
+            // 和 draw 一样按可见的行数计算滚动位置，列表被裁掉一截时点到的才是画出来的那一项
+            Event::Click { y, .. } => {
+                let offset = self.offset(visible.height as usize);
+                let index = offset + y.saturating_sub(self.y) as usize;
+                if index < self.items.len() {
+                    self.selected = Some(index);
+                }
+            }
+            _ => return Response::Ignored,
+        }
+        match self.selected {
+            Some(i) if self.selected != before => Response::Selected(i),
+            _ => Response::Handled,
+        }
+    }
+    // 获得焦点时选中项的标记换成 »
+    fn draw_focus(&self, region: &mut Region) {
+        if let Some(i) = self.selected {
+            let row = i - self.offset(region.height() as usize);
+            region.styled(Style::Focus).put(0, row as u32, '»');
+        }
+    }
+}
+
+// 按顺序把组件画到一块新画布上
+// T 是具体类型时 draw 是静态分发，T 是 dyn Draw 时是动态分发
+pub(crate) fn compose<'a, T, I>(width: u32, height: u32, components: I) -> Canvas
+where
+    T: Draw + ?Sized + 'a,
+    I: IntoIterator<Item = &'a T>,
+{
+    let mut canvas = Canvas::new(width, height);
+    for component in components {
+        component.draw(&mut canvas.region(component.area()));
+    }
+    canvas
+}
+
+// 还需要一个动态数组来存储这些 UI 对象
+// 特征对象指向实现了 Draw 特征的类型的实例，也就是指向了 Button 或者 Image 的实例，
+// 这种映射关系是存储在一张表中，可以在运行时通过特征对象找到具体调用的类型方法。
+// 可以通过&引用或者用Box<>智能指针
+pub struct Screen {
+    pub width: u32,
+    pub height: u32,
+    pub components: Vec<Box<dyn Draw>>,
+    // 获得焦点的组件的路径，见 event.rs
+    pub(crate) focus: Option<Vec<usize>>,
+    pub(crate) handlers: Vec<(Vec<usize>, Handler)>,
+}
+
+impl Screen {
+    pub fn new(width: u32, height: u32) -> Self {
+        Screen {
+            width,
+            height,
+            components: Vec::new(),
+            focus: None,
+            handlers: Vec::new(),
+        }
+    }
+    pub fn add<D: Draw + 'static>(&mut self, component: D) -> &mut Self {
+        self.components.push(Box::new(component));
+        self
+    }
+    pub fn render(&self) -> Canvas {
+        let mut canvas = compose(
+            self.width,
+            self.height,
+            self.components.iter().map(|c| c.as_ref()),
+        );
+        if let Some(path) = self.focus.as_deref() {
+            if let (Some(widget), Some(visible)) = (self.widget(path), self.visible(path)) {
+                widget.draw_focus(&mut canvas.region(visible));
+            }
+        }
+        canvas
+    }
+    // 默认输出纯文本，四周加一圈边框
+    pub fn run(&self) {
+        self.run_with(&TextBackend { frame: true });
+    }
+    // 输出格式在运行时选择，见 backend.rs
+    pub fn run_with(&self, backend: &dyn Backend) {
+        println!("{}", backend.render(&self.render()));
+    }
+}
+
+// 下面的 Screen1 的列表中，存储了类型为 T 的元素，然后在 Screen 中使用特征约束让 T 实现了 Draw 特征，进而可以调用 draw 方法。
+// 但是这种写法限制了 Screen 实例的 Vec<T> 中的每个元素必须是 Button 类型或者全是 SelectBox 类型。如果只需要同质（相同类型）集合，
+// 更倾向于这种写法：使用泛型和 特征约束，因为实现更清晰，且性能更好(特征对象，需要在运行时从 vtable 动态查找需要调用的方法)。
+// 两种写法在本机上差多少，用 cargo run --release -- bench 实测（见 bench.rs）。
+pub struct Screen1<T: Draw> {
+    pub width: u32,
+    pub height: u32,
+    pub components: Vec<T>,
+}
+
+impl<T> Screen1<T>
+where
+    T: Draw,
+{
+    pub fn render(&self) -> Canvas {
+        compose(self.width, self.height, self.components.iter())
+    }
+    pub fn run(&self) {
+        self.run_with(&TextBackend { frame: true });
+    }
+    pub fn run_with(&self, backend: &dyn Backend) {
+        println!("{}", backend.render(&self.render()));
+    }
+}
+
+// 演示和测试共用的登录界面
+fn login_screen() -> Screen {
+    let mut screen = Screen::new(32, 9);
+    screen
+        .add(Label {
+            x: 0,
+            y: 0,
+            width: 16,
+            height: 2,
+            text: "登录 to continue please".to_string(),
+        })
+        .add(TextInput::new(0, 3, 12, "username"))
+        .add(Checkbox {
+            x: 0,
+            y: 5,
+            width: 16,
+            height: 1,
+            label: "remember me".to_string(),
+            checked: true,
+        })
+        .add(List {
+            x: 18,
+            y: 0,
+            width: 14,
+            height: 3,
+            items: vec![
+                "apple".into(),
+                "banana".into(),
+                "cherry".into(),
+                "kiwi".into(),
+            ],
+            selected: Some(3),
+        })
+        .add(Button {
+            x: 18,
+            y: 4,
+            width: 12,
+            height: 3,
+            label: "OK".to_string(),
+        })
+        .add(Image {
+            x: 0,
+            y: 7,
+            width: 4,
+            height: 2,
+            pixel: "10010110".to_string(),
+        })
+        .add(Button {
+            x: 18,
+            y: 8,
+            width: 12,
+            height: 1,
+            label: "Cancel".to_string(),
+        });
+    screen
+}
+
+pub(crate) fn widget() {
+    // 输入框内容太长时向左滚动，光标停在末尾
+    let mut input = TextInput::new(0, 0, 5, "name");
+    for c in "rustacean".chars() {
+        input.insert(c);
+    }
+    let screen1 = Screen1 {
+        width: 5,
+        height: 1,
+        components: vec![input],
+    };
+    screen1.run();
+
+    // 组件超出自己的区域或屏幕的部分会被裁掉
+    let mut small = Screen::new(6, 2);
+    small.add(Label {
+        x: 3,
+        y: 1,
+        width: 10,
+        height: 5,
+        text: "clipped".to_string(),
+    });
+    let canvas = small.render();
+    let visible = Rect::new(3, 1, 10, 5).intersect(&canvas.bounds());
+    println!(
+        "{:?} last cell {:?}, visible {}x{} (empty: {})",
+        canvas.row(1),
+        canvas.get(5, 1),
+        visible.width,
+        visible.height,
+        visible.is_empty()
+    );
+
+    login_screen().run();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn login_screen_layout() {
+        let screen = login_screen();
+        let canvas = screen.render();
+        let rows: Vec<String> = (0..canvas.height()).map(|y| canvas.row(y)).collect();
+        // 中文占两格，折行按显示宽度计算
+        assert_eq!(rows[0].trim_end(), "登录 to continue    banana");
+        assert_eq!(rows[1].trim_end(), "please              cherry");
+        // 选中第 4 项，列表向下滚动一行
+        assert_eq!(&rows[2][18..], "> kiwi        ");
+        assert_eq!(rows[3].trim_end(), "username____");
+        assert_eq!(
+            rows[4].trim_end(),
+            format!("{}+----------+", " ".repeat(18))
+        );
+        assert_eq!(rows[5].trim_end(), "[x] remember me   |    OK    |");
+        assert_eq!(rows[7].trim_end(), "█  █");
+        assert_eq!(rows[8].trim_end(), " ██                [ Cancel ]");
+    }
+
+    #[test]
+    fn components_are_clipped() {
+        // 组件超出自己的区域或屏幕的部分会被裁掉
+        let mut small = Screen::new(6, 2);
+        small.add(Label {
+            x: 3,
+            y: 1,
+            width: 10,
+            height: 5,
+            text: "clipped".to_string(),
+        });
+        assert_eq!(small.render().to_string(), "      \n   cli");
+    }
+
+    #[test]
+    fn clicks_use_the_clipped_size() {
+        // 列表和输入框都被屏幕裁掉一截，点击按画出来的内容计算位置
+        let mut screen = Screen::new(4, 3);
+        screen.add(List {
+            x: 0,
+            y: 0,
+            width: 4,
+            height: 5,
+            items: ["a", "b", "c", "d", "e"].map(String::from).to_vec(),
+            selected: Some(3),
+        });
+        assert_eq!(screen.render().row(0), "  b ");
+        assert_eq!(
+            screen.dispatch(&Event::Click { x: 0, y: 0 }),
+            Response::Selected(1)
+        );
+
+        let mut input = TextInput::new(0, 2, 8, "");
+        for c in "abcdefgh".chars() {
+            input.insert(c);
+        }
+        screen.add(input);
+        assert_eq!(screen.visible(&[1]), Some(Rect::new(0, 2, 4, 1)));
+        assert_eq!(screen.render().row(2), "fgh_");
+        screen.dispatch(&Event::Click { x: 0, y: 2 });
+        assert_eq!(
+            screen.dispatch(&Event::Key(Key::Char('!'))),
+            Response::Edited("abcde!fgh".to_string())
+        );
+    }
+
+    #[test]
+    fn wide_characters() {
+        // 宽字符被覆盖一半时，剩下的一半变成空格
+        let mut canvas = Canvas::new(4, 1);
+        canvas.region(canvas.bounds()).text(0, 0, "中文");
+        canvas.region(Rect::new(1, 0, 1, 1)).put(0, 0, 'x');
+        assert_eq!(canvas.row(0), " x文");
+        // 宽字符在区域边缘放不下时不画
+        canvas.region(Rect::new(0, 0, 1, 1)).put(0, 0, '字');
+        assert_eq!(canvas.get(0, 0), Some(' '));
+    }
+
+    #[test]
+    fn text_input_scrolls() {
+        // 输入框内容太长时向左滚动
+        let mut input = TextInput::new(0, 0, 5, "name");
+        for c in "rustacean".chars() {
+            input.insert(c);
+        }
+        let mut screen1 = Screen1 {
+            width: 5,
+            height: 1,
+            components: vec![input],
+        };
+        assert_eq!(screen1.render().row(0), "cean_");
+        screen1.components[0].backspace();
+        screen1.components[0].left();
+        screen1.components[0].left();
+        screen1.components[0].left();
+        screen1.components[0].left();
+        screen1.components[0].left();
+        assert_eq!(screen1.render().row(0), "rusta");
+        screen1.components[0].right();
+        screen1.components[0].insert('-');
+        assert_eq!(screen1.components[0].value, "rust-acea");
+    }
+
+    #[test]
+    fn checkbox_and_list_state() {
+        let mut checkbox = Checkbox {
+            x: 0,
+            y: 0,
+            width: 8,
+            height: 1,
+            label: "ok".to_string(),
+            checked: false,
+        };
+        checkbox.toggle();
+        assert!(checkbox.checked);
+
+        let mut list = List {
+            x: 0,
+            y: 0,
+            width: 8,
+            height: 2,
+            items: vec!["a".into(), "b".into()],
+            selected: None,
+        };
+        list.select_previous();
+        assert_eq!(list.selected, Some(0));
+        list.select_next();
+        list.select_next();
+        assert_eq!(list.selected, Some(1));
+    }
+
+    #[test]
+    fn rect_geometry() {
+        let area = Rect::new(2, 1, 3, 2);
+        assert!(area.contains(4, 2) && !area.contains(5, 2));
+        assert!(area.intersect(&Rect::new(5, 0, 2, 2)).is_empty());
+        assert_eq!(
+            area.intersect(&Rect::new(3, 0, 9, 2)),
+            Rect::new(3, 1, 2, 1)
+        );
+    }
+}
@@ -2,21 +2,32 @@ use std::io;
 
 use rand::Rng;
 
-fn main() {
+// 读输入失败直接返回错误，输入的不是数字就提示一下重新输入，都不再 panic
+// guessing_game 是单独的 crate，用不了 generics 里的 error 模块，所以直接用标准库的 io::Result，
+// 解析失败也不算错误，提示之后回到循环开头重新读一次
+fn main() -> io::Result<()> {
     println!("Guessing a number");
     println!("Input your guess");
     let secret_num = rand::thread_rng().gen_range(1..=500);
     loop {
         let mut guess = String::new();
-        io::stdin()
-            .read_line(&mut guess)
-            .expect("Failed to read line");
+        // 读到 0 个字节说明输入已经结束（比如按了 Ctrl-D）
+        if io::stdin().read_line(&mut guess)? == 0 {
+            println!("bye");
+            return Ok(());
+        }
         println!("your guess {guess}");
-        let guess: u32 = guess.trim().parse().expect("please type a number");
+        let guess: u32 = match guess.trim().parse() {
+            Ok(number) => number,
+            Err(error) => {
+                println!("please type a number ({error})");
+                continue;
+            }
+        };
         match guess.cmp(&secret_num) {
             std::cmp::Ordering::Equal => {
                 println!("you win ");
-                break;
+                return Ok(());
             }
             std::cmp::Ordering::Greater => println!("too big"),
             std::cmp::Ordering::Less => println!("too small"),
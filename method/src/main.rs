@@ -1,3 +1,7 @@
+mod packing;
+mod physics;
+mod units;
+
 fn main() {
     let circle = Circle::new(1.0, 2.1, 5.34);
     println!("{:?}", circle.area());
@@ -6,11 +10,15 @@ fn main() {
         height: 10,
     };
     println!("the area of the rec1 is {:?} square pixels", rec1.area());
+    packing::packing();
+    units::units();
+    physics::physics();
 }
 
 // 定义方法
 // rust使用impl来定义方法
 
+#[derive(Debug)]
 struct Circle {
     x: f64,
     y: f64,
@@ -0,0 +1,802 @@
+// 矩形装箱：把一批 Rectangle 放进若干个固定尺寸的箱子，或者放进一条定宽的长条里并让长条尽量矮
+// 三种启发式算法都只依赖 Rectangle 的 width()/height() 访问器，不关心矩形从哪里来
+use crate::Rectangle;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Heuristic {
+    // 货架算法：一行一行地摆，当前行放不下就在上面另起一行，最简单也最快
+    Shelf,
+    // 断头台算法：每放进一个矩形，就把剩下的空间“一刀切”成两个互不重叠的空闲矩形
+    Guillotine,
+    // 最大矩形算法：维护所有极大的空闲矩形（它们之间可以重叠），利用率通常最好
+    MaxRects,
+}
+
+// 一个矩形最终摆放的位置，index 是它在输入切片中的下标
+// rotated 为 true 时，width/height 已经是旋转 90° 之后的尺寸
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Placement {
+    pub index: usize,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub rotated: bool,
+}
+
+impl Placement {
+    fn area(&self) -> u64 {
+        self.width as u64 * self.height as u64
+    }
+    // 两个摆放结果是否有重叠，贴边不算重叠
+    pub fn overlaps(&self, other: &Placement) -> bool {
+        self.x < other.x + other.width
+            && other.x < self.x + self.width
+            && self.y < other.y + other.height
+            && other.y < self.y + self.height
+    }
+}
+
+#[derive(Debug)]
+pub struct Bin {
+    pub width: u32,
+    pub height: u32,
+    pub placements: Vec<Placement>,
+}
+
+impl Bin {
+    pub fn used_area(&self) -> u64 {
+        self.placements.iter().map(Placement::area).sum()
+    }
+    // 利用率 = 已用面积 / 箱子面积，取值范围 [0, 1]
+    pub fn utilization(&self) -> f64 {
+        ratio(self.used_area(), self.width as u64 * self.height as u64)
+    }
+}
+
+// 固定尺寸装箱的结果，unplaced 中是连空箱子都装不下的矩形下标
+#[derive(Debug)]
+pub struct Packing {
+    pub bins: Vec<Bin>,
+    pub unplaced: Vec<usize>,
+}
+
+impl Packing {
+    pub fn utilization(&self) -> f64 {
+        let used: u64 = self.bins.iter().map(Bin::used_area).sum();
+        let total: u64 = self
+            .bins
+            .iter()
+            .map(|bin| bin.width as u64 * bin.height as u64)
+            .sum();
+        ratio(used, total)
+    }
+}
+
+// 长条装箱的结果，height 是实际用到的高度
+#[derive(Debug)]
+pub struct Strip {
+    pub width: u32,
+    pub height: u32,
+    pub placements: Vec<Placement>,
+    pub unplaced: Vec<usize>,
+}
+
+impl Strip {
+    pub fn utilization(&self) -> f64 {
+        let used: u64 = self.placements.iter().map(Placement::area).sum();
+        ratio(used, self.width as u64 * self.height as u64)
+    }
+}
+
+fn ratio(used: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        used as f64 / total as f64
+    }
+}
+
+// 把矩形装进尺寸为 bin 的箱子里，一个箱子装不下就开新箱子
+pub fn pack_bins(
+    items: &[Rectangle],
+    bin: &Rectangle,
+    heuristic: Heuristic,
+    allow_rotation: bool,
+) -> Packing {
+    let mut packers: Vec<Box<dyn Packer>> = Vec::new();
+    let mut bins: Vec<Bin> = Vec::new();
+    let mut unplaced = Vec::new();
+    for index in sorted_order(items, allow_rotation) {
+        let item = &items[index];
+        let mut placed = false;
+        for (packer, bin) in packers.iter_mut().zip(bins.iter_mut()) {
+            if let Some(placement) = packer.insert(index, item, allow_rotation) {
+                bin.placements.push(placement);
+                placed = true;
+                break;
+            }
+        }
+        if placed {
+            continue;
+        }
+        let mut packer = new_packer(heuristic, bin.width(), bin.height());
+        match packer.insert(index, item, allow_rotation) {
+            Some(placement) => {
+                packers.push(packer);
+                bins.push(Bin {
+                    width: bin.width(),
+                    height: bin.height(),
+                    placements: vec![placement],
+                });
+            }
+            None => unplaced.push(index),
+        }
+    }
+    Packing { bins, unplaced }
+}
+
+// 把矩形装进宽度为 width、高度不限的长条里，尽量让用到的高度最小
+// 做法是对长条高度做二分查找：每次把长条当作一个固定尺寸的箱子，看能不能一次装下所有矩形
+pub fn pack_strip(
+    items: &[Rectangle],
+    width: u32,
+    heuristic: Heuristic,
+    allow_rotation: bool,
+) -> Strip {
+    let fits_width =
+        |item: &Rectangle| item.width() <= width || (allow_rotation && item.height() <= width);
+    let mut placements = Vec::new();
+    let mut candidates = Vec::new();
+    let mut unplaced = Vec::new();
+    for (index, item) in items.iter().enumerate() {
+        if !fits_width(item) {
+            unplaced.push(index);
+        } else if item.width() == 0 || item.height() == 0 {
+            // 面积为 0 的矩形不占地方，直接放在原点，不参与下面的二分查找
+            let rotated = item.width() > width;
+            let (w, h) = if rotated {
+                (item.height(), item.width())
+            } else {
+                (item.width(), item.height())
+            };
+            placements.push(Placement {
+                index,
+                x: 0,
+                y: 0,
+                width: w,
+                height: h,
+                rotated,
+            });
+        } else {
+            candidates.push(index);
+        }
+    }
+
+    // 下界：面积下界和单个矩形的最小可行高度；上界：每个矩形独占一行
+    let min_height = |item: &Rectangle| {
+        orientations(item, allow_rotation)
+            .into_iter()
+            .filter(|&(w, _, _)| w <= width)
+            .map(|(_, h, _)| h)
+            .min()
+            .unwrap_or(0)
+    };
+    let total_area: u64 = candidates
+        .iter()
+        .map(|&i| items[i].width() as u64 * items[i].height() as u64)
+        .sum();
+    let mut low = candidates
+        .iter()
+        .map(|&i| min_height(&items[i]))
+        .max()
+        .unwrap_or(0)
+        .max(if width == 0 {
+            0
+        } else {
+            total_area.div_ceil(width as u64).min(u32::MAX as u64) as u32
+        });
+    let stacked: u64 = candidates
+        .iter()
+        .map(|&i| min_height(&items[i]) as u64)
+        .sum();
+    let mut high = (stacked.min(u32::MAX as u64) as u32).max(low).max(1);
+
+    let order = sorted_subset(items, &candidates, allow_rotation);
+    let attempt = |height: u32| -> Option<Vec<Placement>> {
+        let mut packer = new_packer(heuristic, width, height);
+        let mut placements = Vec::with_capacity(candidates.len());
+        for &index in &order {
+            placements.push(packer.insert(index, &items[index], allow_rotation)?);
+        }
+        Some(placements)
+    };
+
+    // 启发式算法不保证在上界内一定成功，装不下就把上界翻倍
+    // 翻倍到 u32::MAX 还装不下时就不再二分，能放的先放，剩下的记进 unplaced
+    let mut best = loop {
+        if let Some(placements) = attempt(high) {
+            break placements;
+        }
+        let next = high.saturating_mul(2);
+        if next == high {
+            let mut packer = new_packer(heuristic, width, high);
+            for &index in &order {
+                match packer.insert(index, &items[index], allow_rotation) {
+                    Some(placement) => placements.push(placement),
+                    None => unplaced.push(index),
+                }
+            }
+            unplaced.sort_unstable();
+            return finish_strip(width, placements, unplaced);
+        }
+        high = next;
+    };
+    while low < high {
+        let mid = low + (high - low) / 2;
+        match attempt(mid) {
+            Some(placements) => {
+                best = placements;
+                high = mid;
+            }
+            None => low = mid + 1,
+        }
+    }
+
+    placements.append(&mut best);
+    finish_strip(width, placements, unplaced)
+}
+
+fn finish_strip(width: u32, placements: Vec<Placement>, unplaced: Vec<usize>) -> Strip {
+    let height = placements.iter().map(|p| p.y + p.height).max().unwrap_or(0);
+    Strip {
+        width,
+        height,
+        placements,
+        unplaced,
+    }
+}
+
+// 先放大的再放小的，几种启发式算法的效果都会好很多
+fn sorted_order(items: &[Rectangle], allow_rotation: bool) -> Vec<usize> {
+    let all: Vec<usize> = (0..items.len()).collect();
+    sorted_subset(items, &all, allow_rotation)
+}
+
+fn sorted_subset(items: &[Rectangle], subset: &[usize], allow_rotation: bool) -> Vec<usize> {
+    let mut order = subset.to_vec();
+    order.sort_by_key(|&index| {
+        let item = &items[index];
+        let key = if allow_rotation {
+            (
+                item.width().max(item.height()),
+                item.width().min(item.height()),
+            )
+        } else {
+            (item.height(), item.width())
+        };
+        std::cmp::Reverse(key)
+    });
+    order
+}
+
+// 箱子内部的一块空闲区域
+#[derive(Debug, Clone, Copy)]
+struct FreeRect {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+impl FreeRect {
+    fn right(&self) -> u32 {
+        self.x + self.width
+    }
+    fn top(&self) -> u32 {
+        self.y + self.height
+    }
+    fn contains(&self, other: &FreeRect) -> bool {
+        other.x >= self.x
+            && other.y >= self.y
+            && other.right() <= self.right()
+            && other.top() <= self.top()
+    }
+    fn intersects(&self, other: &FreeRect) -> bool {
+        self.x < other.right()
+            && other.x < self.right()
+            && self.y < other.top()
+            && other.y < self.top()
+    }
+}
+
+// 每种启发式算法都是一个 Packer，负责在单个箱子里找位置
+trait Packer {
+    fn insert(&mut self, index: usize, item: &Rectangle, allow_rotation: bool)
+        -> Option<Placement>;
+}
+
+fn new_packer(heuristic: Heuristic, width: u32, height: u32) -> Box<dyn Packer> {
+    match heuristic {
+        Heuristic::Shelf => Box::new(ShelfPacker {
+            width,
+            height,
+            shelves: Vec::new(),
+        }),
+        Heuristic::Guillotine => Box::new(GuillotinePacker {
+            free: vec![FreeRect {
+                x: 0,
+                y: 0,
+                width,
+                height,
+            }],
+        }),
+        Heuristic::MaxRects => Box::new(MaxRectsPacker {
+            free: vec![FreeRect {
+                x: 0,
+                y: 0,
+                width,
+                height,
+            }],
+        }),
+    }
+}
+
+// 一种摆法：(宽, 高, 是否旋转)
+type Orientation = (u32, u32, bool);
+
+// 允许旋转时，一个矩形有两种摆法（正方形只有一种）
+fn orientations(item: &Rectangle, allow_rotation: bool) -> Vec<Orientation> {
+    let mut result = vec![(item.width(), item.height(), false)];
+    if allow_rotation && item.width() != item.height() {
+        result.push((item.height(), item.width(), true));
+    }
+    result
+}
+
+struct Shelf {
+    y: u32,
+    height: u32,
+    used_width: u32,
+}
+
+struct ShelfPacker {
+    width: u32,
+    height: u32,
+    shelves: Vec<Shelf>,
+}
+
+impl Packer for ShelfPacker {
+    fn insert(
+        &mut self,
+        index: usize,
+        item: &Rectangle,
+        allow_rotation: bool,
+    ) -> Option<Placement> {
+        // 评分越小越好：优先放进已有的货架（剩余高度越少越好），其次才开新货架（新货架越矮越好）
+        let mut best: Option<((u32, u32), Option<usize>, Orientation)> = None;
+        let next_y = self
+            .shelves
+            .last()
+            .map_or(0, |shelf| shelf.y + shelf.height);
+        for (width, height, rotated) in orientations(item, allow_rotation) {
+            for (i, shelf) in self.shelves.iter().enumerate() {
+                // 和剩下的空间比，不做加法，尺寸接近 u32::MAX 时也不会溢出
+                if width <= self.width - shelf.used_width && height <= shelf.height {
+                    let score = (0, shelf.height - height);
+                    if best.is_none_or(|(s, _, _)| score < s) {
+                        best = Some((score, Some(i), (width, height, rotated)));
+                    }
+                }
+            }
+            if width <= self.width && height <= self.height - next_y {
+                let score = (1, height);
+                if best.is_none_or(|(s, _, _)| score < s) {
+                    best = Some((score, None, (width, height, rotated)));
+                }
+            }
+        }
+        let (_, shelf, (width, height, rotated)) = best?;
+        let shelf = match shelf {
+            Some(i) => &mut self.shelves[i],
+            None => {
+                self.shelves.push(Shelf {
+                    y: next_y,
+                    height,
+                    used_width: 0,
+                });
+                self.shelves.last_mut().unwrap()
+            }
+        };
+        let placement = Placement {
+            index,
+            x: shelf.used_width,
+            y: shelf.y,
+            width,
+            height,
+            rotated,
+        };
+        shelf.used_width += width;
+        Some(placement)
+    }
+}
+
+struct GuillotinePacker {
+    free: Vec<FreeRect>,
+}
+
+impl Packer for GuillotinePacker {
+    fn insert(
+        &mut self,
+        index: usize,
+        item: &Rectangle,
+        allow_rotation: bool,
+    ) -> Option<Placement> {
+        // 面积最贴合优先(best area fit)，面积相同再看短边剩余
+        let mut best: Option<((u64, u32), usize, Orientation)> = None;
+        for (i, free) in self.free.iter().enumerate() {
+            for (width, height, rotated) in orientations(item, allow_rotation) {
+                if width <= free.width && height <= free.height {
+                    let score = (
+                        free.width as u64 * free.height as u64 - width as u64 * height as u64,
+                        (free.width - width).min(free.height - height),
+                    );
+                    if best.is_none_or(|(s, _, _)| score < s) {
+                        best = Some((score, i, (width, height, rotated)));
+                    }
+                }
+            }
+        }
+        let (_, i, (width, height, rotated)) = best?;
+        let free = self.free.swap_remove(i);
+        // 沿剩余较短的那条轴切，让切出来的大块尽量完整
+        let leftover_x = free.width - width;
+        let leftover_y = free.height - height;
+        let (right, top) = if leftover_x <= leftover_y {
+            (
+                FreeRect {
+                    x: free.x + width,
+                    y: free.y,
+                    width: leftover_x,
+                    height,
+                },
+                FreeRect {
+                    x: free.x,
+                    y: free.y + height,
+                    width: free.width,
+                    height: leftover_y,
+                },
+            )
+        } else {
+            (
+                FreeRect {
+                    x: free.x + width,
+                    y: free.y,
+                    width: leftover_x,
+                    height: free.height,
+                },
+                FreeRect {
+                    x: free.x,
+                    y: free.y + height,
+                    width,
+                    height: leftover_y,
+                },
+            )
+        };
+        for piece in [right, top] {
+            if piece.width > 0 && piece.height > 0 {
+                self.free.push(piece);
+            }
+        }
+        Some(Placement {
+            index,
+            x: free.x,
+            y: free.y,
+            width,
+            height,
+            rotated,
+        })
+    }
+}
+
+struct MaxRectsPacker {
+    free: Vec<FreeRect>,
+}
+
+impl Packer for MaxRectsPacker {
+    fn insert(
+        &mut self,
+        index: usize,
+        item: &Rectangle,
+        allow_rotation: bool,
+    ) -> Option<Placement> {
+        // 短边最贴合优先(best short side fit)，再比长边，最后比位置，越靠左下越好
+        let mut best: Option<([u32; 4], FreeRect, bool)> = None;
+        for free in self.free.iter() {
+            for (width, height, rotated) in orientations(item, allow_rotation) {
+                if width <= free.width && height <= free.height {
+                    let short = (free.width - width).min(free.height - height);
+                    let long = (free.width - width).max(free.height - height);
+                    let score = [short, long, free.y, free.x];
+                    if best.is_none_or(|(s, _, _)| score < s) {
+                        let used = FreeRect {
+                            x: free.x,
+                            y: free.y,
+                            width,
+                            height,
+                        };
+                        best = Some((score, used, rotated));
+                    }
+                }
+            }
+        }
+        let (_, used, rotated) = best?;
+
+        // 所有与新矩形相交的空闲矩形都要拆成最多四块（左、右、下、上）
+        let mut next = Vec::with_capacity(self.free.len() + 4);
+        for free in self.free.drain(..) {
+            if !free.intersects(&used) {
+                next.push(free);
+                continue;
+            }
+            if used.x > free.x {
+                next.push(FreeRect {
+                    width: used.x - free.x,
+                    ..free
+                });
+            }
+            if used.right() < free.right() {
+                next.push(FreeRect {
+                    x: used.right(),
+                    width: free.right() - used.right(),
+                    ..free
+                });
+            }
+            if used.y > free.y {
+                next.push(FreeRect {
+                    height: used.y - free.y,
+                    ..free
+                });
+            }
+            if used.top() < free.top() {
+                next.push(FreeRect {
+                    y: used.top(),
+                    height: free.top() - used.top(),
+                    ..free
+                });
+            }
+        }
+        // 被其它空闲矩形完全包含的不是“最大”矩形，去掉
+        let mut i = 0;
+        while i < next.len() {
+            let contained = (0..next.len()).any(|j| {
+                j != i && next[j].contains(&next[i]) && (!next[i].contains(&next[j]) || j < i)
+            });
+            if contained {
+                next.swap_remove(i);
+            } else {
+                i += 1;
+            }
+        }
+        self.free = next;
+
+        Some(Placement {
+            index,
+            x: used.x,
+            y: used.y,
+            width: used.width,
+            height: used.height,
+            rotated,
+        })
+    }
+}
+
+fn sample_items() -> Vec<Rectangle> {
+    [
+        (30, 10),
+        (20, 20),
+        (10, 40),
+        (25, 15),
+        (15, 25),
+        (40, 10),
+        (10, 10),
+        (35, 20),
+        (5, 30),
+        (20, 5),
+        (50, 50),
+        (12, 18),
+    ]
+    .iter()
+    .map(|&(width, height)| Rectangle::new(width, height))
+    .collect()
+}
+
+pub(crate) fn packing() {
+    let items = sample_items();
+    let bin = Rectangle::new(60, 60);
+
+    for heuristic in [Heuristic::Shelf, Heuristic::Guillotine, Heuristic::MaxRects] {
+        for allow_rotation in [false, true] {
+            let packing = pack_bins(&items, &bin, heuristic, allow_rotation);
+            println!(
+                "{:?} rotation={} bins={} utilization={:.1}%",
+                heuristic,
+                allow_rotation,
+                packing.bins.len(),
+                packing.utilization() * 100.0
+            );
+            for (i, b) in packing.bins.iter().enumerate() {
+                println!("  bin {} utilization={:.1}%", i, b.utilization() * 100.0);
+            }
+
+            let strip = pack_strip(&items, 60, heuristic, allow_rotation);
+            let rotated = strip.placements.iter().filter(|p| p.rotated).count();
+            let overlapping = strip
+                .placements
+                .iter()
+                .enumerate()
+                .flat_map(|(i, p)| strip.placements[i + 1..].iter().map(move |q| (p, q)))
+                .filter(|(p, q)| p.overlaps(q))
+                .count();
+            println!(
+                "{:?} rotation={} strip height={} rotated={} overlapping={} utilization={:.1}%",
+                heuristic,
+                allow_rotation,
+                strip.height,
+                rotated,
+                overlapping,
+                strip.utilization() * 100.0
+            );
+        }
+    }
+
+    // 比箱子还宽的矩形会被记进 unplaced，不影响其它矩形
+    let oversized = [Rectangle::new(70, 10), Rectangle::new(10, 10)];
+    let packing = pack_bins(&oversized, &bin, Heuristic::MaxRects, false);
+    let strip = pack_strip(&oversized, 60, Heuristic::MaxRects, false);
+    println!(
+        "70x10 in 60x60: bins unplaced={:?}, strip unplaced={:?}",
+        packing.unplaced, strip.unplaced
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [Heuristic; 3] = [Heuristic::Shelf, Heuristic::Guillotine, Heuristic::MaxRects];
+
+    // 检查装箱结果：没有越界、没有重叠，每个矩形都按原尺寸（或旋转后的尺寸）摆放
+    fn check(items: &[Rectangle], width: u32, height: u32, placements: &[Placement]) {
+        for (i, p) in placements.iter().enumerate() {
+            let item = &items[p.index];
+            let expected = if p.rotated {
+                (item.height(), item.width())
+            } else {
+                (item.width(), item.height())
+            };
+            assert_eq!((p.width, p.height), expected);
+            assert!(p.x + p.width <= width && p.y + p.height <= height);
+            for q in &placements[i + 1..] {
+                assert!(!p.overlaps(q), "{:?} overlaps {:?}", p, q);
+            }
+        }
+    }
+
+    #[test]
+    fn every_heuristic_packs_without_overlap() {
+        let items = sample_items();
+        let bin = Rectangle::new(60, 60);
+        for heuristic in ALL {
+            for allow_rotation in [false, true] {
+                let packing = pack_bins(&items, &bin, heuristic, allow_rotation);
+                let placed: usize = packing.bins.iter().map(|b| b.placements.len()).sum();
+                assert_eq!(placed + packing.unplaced.len(), items.len());
+                for b in &packing.bins {
+                    check(&items, b.width, b.height, &b.placements);
+                }
+
+                let strip = pack_strip(&items, 60, heuristic, allow_rotation);
+                assert!(strip.unplaced.is_empty());
+                assert_eq!(strip.placements.len(), items.len());
+                check(&items, strip.width, strip.height, &strip.placements);
+            }
+        }
+    }
+
+    #[test]
+    fn oversized_items_are_unplaced() {
+        let bin = Rectangle::new(60, 60);
+        // 比箱子还大的矩形放不进去，但不影响其它矩形
+        let packing = pack_bins(
+            &[Rectangle::new(70, 10), Rectangle::new(10, 10)],
+            &bin,
+            Heuristic::MaxRects,
+            false,
+        );
+        assert_eq!(packing.unplaced, vec![0]);
+        // 横着放不进瘦高的箱子，允许旋转之后就能放下了
+        let tall = Rectangle::new(20, 80);
+        let packing = pack_bins(&[Rectangle::new(70, 10)], &tall, Heuristic::Shelf, false);
+        assert_eq!(packing.unplaced, vec![0]);
+        let packing = pack_bins(&[Rectangle::new(70, 10)], &tall, Heuristic::Shelf, true);
+        assert!(packing.unplaced.is_empty() && packing.bins[0].placements[0].rotated);
+    }
+
+    #[test]
+    fn full_bin_has_full_utilization() {
+        let bin = Rectangle::new(60, 60);
+        // 正好铺满一个箱子时利用率是 100%
+        let quarters: Vec<Rectangle> = (0..4).map(|_| Rectangle::new(30, 30)).collect();
+        for heuristic in ALL {
+            let packing = pack_bins(&quarters, &bin, heuristic, false);
+            assert_eq!(packing.bins.len(), 1);
+            assert_eq!(packing.utilization(), 1.0);
+        }
+    }
+
+    #[test]
+    fn zero_area_items_do_not_hang_the_strip() {
+        let items = [Rectangle::new(5, 0), Rectangle::new(5, 0)];
+        let strip = pack_strip(&items, 10, Heuristic::Guillotine, false);
+        assert!(strip.unplaced.is_empty());
+        assert_eq!(strip.placements.len(), 2);
+        assert_eq!(strip.height, 0);
+
+        // 面积为 0 但比长条还宽的矩形仍然放不下
+        let strip = pack_strip(&[Rectangle::new(20, 0)], 10, Heuristic::Shelf, false);
+        assert_eq!(strip.unplaced, vec![0]);
+        let strip = pack_strip(&[Rectangle::new(20, 0)], 10, Heuristic::Shelf, true);
+        assert!(strip.unplaced.is_empty() && strip.placements[0].rotated);
+
+        let items = [
+            Rectangle::new(5, 0),
+            Rectangle::new(4, 3),
+            Rectangle::new(0, 2),
+        ];
+        for heuristic in ALL {
+            let strip = pack_strip(&items, 10, heuristic, false);
+            assert!(strip.unplaced.is_empty());
+            assert_eq!(strip.height, 3);
+            check(&items, strip.width, strip.height, &strip.placements);
+        }
+    }
+
+    #[test]
+    fn strip_area_does_not_overflow_u32() {
+        // 单个矩形面积 70000 * 70000 超出了 u32
+        let items = [
+            Rectangle::new(70_000, 70_000),
+            Rectangle::new(70_000, 70_000),
+        ];
+        let strip = pack_strip(&items, 70_000, Heuristic::Shelf, false);
+        assert!(strip.unplaced.is_empty());
+        assert_eq!(strip.height, 140_000);
+    }
+
+    #[test]
+    fn huge_items_do_not_overflow_the_shelf() {
+        let bin = Rectangle::new(60, 60);
+        for heuristic in ALL {
+            let packing = pack_bins(
+                &[Rectangle::new(10, 20), Rectangle::new(u32::MAX, 5)],
+                &bin,
+                heuristic,
+                false,
+            );
+            assert_eq!(packing.unplaced, vec![1], "{:?}", heuristic);
+            let strip = pack_strip(
+                &[Rectangle::new(1, u32::MAX), Rectangle::new(1, 5)],
+                1,
+                heuristic,
+                false,
+            );
+            assert_eq!(
+                strip.placements.len() + strip.unplaced.len(),
+                2,
+                "{:?}",
+                heuristic
+            );
+        }
+    }
+}
@@ -0,0 +1,408 @@
+// 简单的二维刚体物理：会动的圆（Circle 已经有圆心 x、y 和半径）和静止不动的矩形障碍物
+// 支持速度、质量、重力、完全弹性碰撞，World::step 每次前进一个固定的时间步长
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+use crate::{Circle, Rectangle};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Vec2 {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Vec2 {
+    pub fn new(x: f64, y: f64) -> Self {
+        Vec2 { x, y }
+    }
+    pub fn dot(self, other: Vec2) -> f64 {
+        self.x * other.x + self.y * other.y
+    }
+    pub fn length(self) -> f64 {
+        self.dot(self).sqrt()
+    }
+}
+
+impl Add for Vec2 {
+    type Output = Vec2;
+    fn add(self, rhs: Vec2) -> Vec2 {
+        Vec2::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Vec2;
+    fn sub(self, rhs: Vec2) -> Vec2 {
+        Vec2::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl Neg for Vec2 {
+    type Output = Vec2;
+    fn neg(self) -> Vec2 {
+        Vec2::new(-self.x, -self.y)
+    }
+}
+
+impl Mul<f64> for Vec2 {
+    type Output = Vec2;
+    fn mul(self, rhs: f64) -> Vec2 {
+        Vec2::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl AddAssign for Vec2 {
+    fn add_assign(&mut self, rhs: Vec2) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Vec2 {
+    fn sub_assign(&mut self, rhs: Vec2) {
+        *self = *self - rhs;
+    }
+}
+
+impl Circle {
+    pub fn center(&self) -> Vec2 {
+        Vec2::new(self.x, self.y)
+    }
+    fn move_by(&mut self, offset: Vec2) {
+        self.x += offset.x;
+        self.y += offset.y;
+    }
+}
+
+// 会运动的物体：一个带速度和质量的圆
+#[derive(Debug)]
+pub struct Body {
+    pub shape: Circle,
+    pub velocity: Vec2,
+    pub mass: f64,
+}
+
+impl Body {
+    pub fn new(shape: Circle, velocity: Vec2, mass: f64) -> Self {
+        assert!(mass > 0.0, "mass must be positive");
+        Body {
+            shape,
+            velocity,
+            mass,
+        }
+    }
+    pub fn kinetic_energy(&self) -> f64 {
+        0.5 * self.mass * self.velocity.dot(self.velocity)
+    }
+}
+
+// 静止的矩形障碍物，(x, y) 是左下角，尺寸直接复用 Rectangle
+// 可以看作质量无穷大，碰撞时自己不动，只把圆弹回去
+#[derive(Debug)]
+pub struct Block {
+    pub x: f64,
+    pub y: f64,
+    pub rect: Rectangle,
+}
+
+impl Block {
+    pub fn new(x: f64, y: f64, rect: Rectangle) -> Self {
+        Block { x, y, rect }
+    }
+    // 矩形上离 point 最近的点
+    fn closest_point(&self, point: Vec2) -> Vec2 {
+        Vec2::new(
+            point.x.clamp(self.x, self.x + self.rect.width() as f64),
+            point.y.clamp(self.y, self.y + self.rect.height() as f64),
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct World {
+    pub bodies: Vec<Body>,
+    pub blocks: Vec<Block>,
+    pub gravity: Vec2,
+    // 固定时间步长，单位秒
+    pub timestep: f64,
+    // advance 传进来的时间不一定是步长的整数倍，剩下的零头留到下一次
+    accumulator: f64,
+}
+
+impl World {
+    pub fn new(gravity: Vec2, timestep: f64) -> Self {
+        assert!(timestep > 0.0, "timestep must be positive");
+        World {
+            bodies: Vec::new(),
+            blocks: Vec::new(),
+            gravity,
+            timestep,
+            accumulator: 0.0,
+        }
+    }
+
+    pub fn add_body(&mut self, body: Body) -> usize {
+        self.bodies.push(body);
+        self.bodies.len() - 1
+    }
+
+    pub fn add_block(&mut self, block: Block) {
+        self.blocks.push(block);
+    }
+
+    // 按固定步长推进 elapsed 秒，返回实际走了多少步
+    pub fn advance(&mut self, elapsed: f64) -> usize {
+        self.accumulator += elapsed;
+        let mut steps = 0;
+        while self.accumulator >= self.timestep {
+            self.step();
+            self.accumulator -= self.timestep;
+            steps += 1;
+        }
+        steps
+    }
+
+    // 前进一个时间步长：先积分（半隐式欧拉法，先更新速度再用新速度更新位置），再处理碰撞
+    pub fn step(&mut self) {
+        let dt = self.timestep;
+        for body in self.bodies.iter_mut() {
+            body.velocity += self.gravity * dt;
+            body.shape.move_by(body.velocity * dt);
+        }
+        for i in 0..self.bodies.len() {
+            for j in i + 1..self.bodies.len() {
+                let (left, right) = self.bodies.split_at_mut(j);
+                collide_bodies(&mut left[i], &mut right[0]);
+            }
+        }
+        for body in self.bodies.iter_mut() {
+            for block in self.blocks.iter() {
+                collide_block(body, block);
+            }
+        }
+    }
+
+    pub fn kinetic_energy(&self) -> f64 {
+        self.bodies.iter().map(Body::kinetic_energy).sum()
+    }
+
+    // 重力势能，以原点为零点：E = -m * (g · p)
+    pub fn potential_energy(&self) -> f64 {
+        self.bodies
+            .iter()
+            .map(|body| -body.mass * self.gravity.dot(body.shape.center()))
+            .sum()
+    }
+
+    pub fn total_energy(&self) -> f64 {
+        self.kinetic_energy() + self.potential_energy()
+    }
+}
+
+// 两个圆的完全弹性碰撞：只交换法线方向上的动量，切线方向不变
+fn collide_bodies(a: &mut Body, b: &mut Body) {
+    let delta = b.shape.center() - a.shape.center();
+    let distance = delta.length();
+    let overlap = a.shape.radius + b.shape.radius - distance;
+    if overlap <= 0.0 || distance == 0.0 {
+        return;
+    }
+    let normal = delta * (1.0 / distance);
+    let inv_a = 1.0 / a.mass;
+    let inv_b = 1.0 / b.mass;
+    // 先按质量反比把两个圆推开，避免下一步还粘在一起
+    a.shape
+        .move_by(-normal * (overlap * inv_a / (inv_a + inv_b)));
+    b.shape
+        .move_by(normal * (overlap * inv_b / (inv_a + inv_b)));
+    // 只有在相互靠近时才施加冲量
+    let approaching = (a.velocity - b.velocity).dot(normal);
+    if approaching <= 0.0 {
+        return;
+    }
+    let impulse = 2.0 * approaching / (inv_a + inv_b);
+    a.velocity -= normal * (impulse * inv_a);
+    b.velocity += normal * (impulse * inv_b);
+}
+
+// 圆撞上静止矩形：把圆推出矩形，并把法线方向的速度反向
+fn collide_block(body: &mut Body, block: &Block) {
+    let center = body.shape.center();
+    let closest = block.closest_point(center);
+    let delta = center - closest;
+    let distance = delta.length();
+    let (normal, overlap) = if distance > 0.0 {
+        (delta * (1.0 / distance), body.shape.radius - distance)
+    } else {
+        // 圆心已经进到矩形里面了，从最近的那条边推出去
+        let right = block.x + block.rect.width() as f64;
+        let top = block.y + block.rect.height() as f64;
+        let exits = [
+            (center.x - block.x, Vec2::new(-1.0, 0.0)),
+            (right - center.x, Vec2::new(1.0, 0.0)),
+            (center.y - block.y, Vec2::new(0.0, -1.0)),
+            (top - center.y, Vec2::new(0.0, 1.0)),
+        ];
+        let (depth, normal) = exits
+            .into_iter()
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .unwrap();
+        (normal, depth + body.shape.radius)
+    };
+    if overlap <= 0.0 {
+        return;
+    }
+    body.shape.move_by(normal * overlap);
+    let speed = body.velocity.dot(normal);
+    if speed < 0.0 {
+        body.velocity -= normal * (2.0 * speed);
+    }
+}
+
+// 四面墙围成的 20x20 盒子，里面放 12 个质量、速度各不相同的球，没有重力
+fn balls_in_box() -> World {
+    let mut world = World::new(Vec2::default(), 0.005);
+    world.add_block(Block::new(-1.0, -1.0, Rectangle::new(22, 1)));
+    world.add_block(Block::new(-1.0, 20.0, Rectangle::new(22, 1)));
+    world.add_block(Block::new(-1.0, 0.0, Rectangle::new(1, 20)));
+    world.add_block(Block::new(20.0, 0.0, Rectangle::new(1, 20)));
+    for i in 0..12 {
+        let x = 2.0 + (i % 4) as f64 * 5.0;
+        let y = 3.0 + (i / 4) as f64 * 6.0;
+        let velocity = Vec2::new((i as f64 * 1.7).sin() * 4.0, (i as f64 * 2.3).cos() * 4.0);
+        world.add_body(Body::new(
+            Circle::new(x, y, 0.8),
+            velocity,
+            1.0 + i as f64 * 0.5,
+        ));
+    }
+    world
+}
+
+// 从 10 米高处落到地面上的小球，有重力
+fn bouncing_ball() -> World {
+    let mut world = World::new(Vec2::new(0.0, -9.8), 0.0005);
+    world.add_block(Block::new(-10.0, -1.0, Rectangle::new(20, 1)));
+    world.add_body(Body::new(
+        Circle::new(0.0, 10.0, 0.5),
+        Vec2::new(0.0, 0.0),
+        2.0,
+    ));
+    world
+}
+
+// 逐步推进，返回第一次弹起之后到达的最高点
+fn rebound_height(world: &mut World, steps: usize) -> f64 {
+    let mut highest: f64 = 0.0;
+    let mut bounced = false;
+    for _ in 0..steps {
+        world.step();
+        let body = &world.bodies[0];
+        bounced |= body.velocity.y > 0.0;
+        if bounced {
+            highest = highest.max(body.shape.center().y);
+        }
+    }
+    highest
+}
+
+pub(crate) fn physics() {
+    // 一群球在盒子里来回弹，没有重力时总动能一直不变
+    let mut world = balls_in_box();
+    let before = world.kinetic_energy();
+    let steps = world.advance(20.0);
+    println!(
+        "{} steps, kinetic energy {:.6} -> {:.6}",
+        steps,
+        before,
+        world.kinetic_energy()
+    );
+
+    // 有重力时，小球落到地面上反复弹起，动能加势能近似守恒
+    let mut world = bouncing_ball();
+    let before = world.total_energy();
+    let height = rebound_height(&mut world, 6000);
+    println!(
+        "bouncing ball: energy {:.4} -> {:.4}, rebound height {:.3}",
+        before,
+        world.total_energy(),
+        height
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relative_error(before: f64, after: f64) -> f64 {
+        (after - before).abs() / before.abs()
+    }
+
+    fn momentum(world: &World) -> Vec2 {
+        world
+            .bodies
+            .iter()
+            .fold(Vec2::default(), |sum, body| sum + body.velocity * body.mass)
+    }
+
+    #[test]
+    fn equal_masses_swap_velocities() {
+        // 质量相同的两个球正面相撞，速度互换
+        let mut world = World::new(Vec2::default(), 0.01);
+        let a = world.add_body(Body::new(
+            Circle::new(0.0, 0.0, 1.0),
+            Vec2::new(1.0, 0.0),
+            1.0,
+        ));
+        let b = world.add_body(Body::new(
+            Circle::new(3.0, 0.0, 1.0),
+            Vec2::new(-1.0, 0.0),
+            1.0,
+        ));
+        let before = world.kinetic_energy();
+        world.advance(1.0);
+        assert!((world.bodies[a].velocity.x + 1.0).abs() < 1e-12);
+        assert!((world.bodies[b].velocity.x - 1.0).abs() < 1e-12);
+        assert!(relative_error(before, world.kinetic_energy()) < 1e-12);
+    }
+
+    #[test]
+    fn oblique_collision_conserves_momentum_and_energy() {
+        // 质量不同的斜碰：动量和动能都守恒
+        let mut world = World::new(Vec2::default(), 0.001);
+        world.add_body(Body::new(
+            Circle::new(0.0, 0.0, 1.0),
+            Vec2::new(2.0, 0.5),
+            3.0,
+        ));
+        world.add_body(Body::new(
+            Circle::new(4.0, 1.0, 0.5),
+            Vec2::new(-1.0, 0.0),
+            1.0,
+        ));
+        let (p0, e0) = (momentum(&world), world.kinetic_energy());
+        world.advance(2.0);
+        assert!((momentum(&world) - p0).length() < 1e-9);
+        assert!(relative_error(e0, world.kinetic_energy()) < 1e-12);
+    }
+
+    #[test]
+    fn balls_stay_in_box_without_losing_energy() {
+        let mut world = balls_in_box();
+        let before = world.kinetic_energy();
+        world.advance(20.0);
+        assert!(relative_error(before, world.kinetic_energy()) < 1e-9);
+        for body in &world.bodies {
+            let c = body.shape.center();
+            assert!(c.x > 0.0 && c.x < 20.0 && c.y > 0.0 && c.y < 20.0);
+        }
+    }
+
+    #[test]
+    fn bouncing_ball_returns_to_its_height() {
+        // 误差来自数值积分，随步长减小
+        let mut world = bouncing_ball();
+        let before = world.total_energy();
+        let height = rebound_height(&mut world, 6000);
+        assert!(relative_error(before, world.total_energy()) < 0.01);
+        assert!((height - 10.0).abs() < 0.1);
+    }
+}
@@ -0,0 +1,347 @@
+// 带单位的长度和面积
+// Circle::area 返回裸的 f64，Rectangle::area 返回裸的 u32，调用方根本不知道算出来的是平方毫米还是平方像素。
+// 这里给长度和面积都带上单位，单位之间只能显式转换；整数矩形还可以用分数精确计算，换算多少次都不丢精度。
+use std::fmt;
+use std::ops::{Add, Mul};
+
+use crate::{Circle, Rectangle};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Millimeter,
+    Centimeter,
+    Meter,
+    Inch,
+    // 像素的物理尺寸取决于 DPI（每英寸多少个像素）
+    Pixel { dpi: u32 },
+}
+
+impl Unit {
+    // 1 个该单位等于多少毫米，用分数表示才能精确换算：1 英寸 = 25.4 毫米 = 127/5 毫米
+    fn millimeters(&self) -> Ratio {
+        match *self {
+            Unit::Millimeter => Ratio::new(1, 1),
+            Unit::Centimeter => Ratio::new(10, 1),
+            Unit::Meter => Ratio::new(1000, 1),
+            Unit::Inch => Ratio::new(127, 5),
+            Unit::Pixel { dpi } => {
+                assert!(dpi > 0, "pixel unit needs a positive dpi");
+                Ratio::new(127, 5 * dpi as u128)
+            }
+        }
+    }
+    // 从 self 换算到 to 时长度要乘的系数
+    fn factor_to(&self, to: Unit) -> Ratio {
+        self.millimeters() * to.millimeters().recip()
+    }
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Unit::Millimeter => "mm",
+            Unit::Centimeter => "cm",
+            Unit::Meter => "m",
+            Unit::Inch => "in",
+            Unit::Pixel { .. } => "px",
+        }
+    }
+}
+
+// 非负的最简分数，分子分母都用 u128，整数矩形的面积换算不会溢出
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ratio {
+    numer: u128,
+    denom: u128,
+}
+
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+impl Ratio {
+    pub fn new(numer: u128, denom: u128) -> Self {
+        assert!(denom != 0, "denominator must not be zero");
+        let g = gcd(numer, denom);
+        Ratio {
+            numer: numer / g,
+            denom: denom / g,
+        }
+    }
+    pub fn numer(&self) -> u128 {
+        self.numer
+    }
+    pub fn denom(&self) -> u128 {
+        self.denom
+    }
+    pub fn is_integer(&self) -> bool {
+        self.denom == 1
+    }
+    // 倒数
+    pub fn recip(self) -> Ratio {
+        Ratio::new(self.denom, self.numer)
+    }
+    pub fn to_f64(self) -> f64 {
+        self.numer as f64 / self.denom as f64
+    }
+}
+
+impl Mul for Ratio {
+    type Output = Ratio;
+    fn mul(self, rhs: Ratio) -> Ratio {
+        // 先交叉约分再相乘，减少溢出的可能
+        let a = gcd(self.numer, rhs.denom);
+        let b = gcd(rhs.numer, self.denom);
+        Ratio::new(
+            (self.numer / a) * (rhs.numer / b),
+            (self.denom / b) * (rhs.denom / a),
+        )
+    }
+}
+
+impl fmt::Display for Ratio {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.numer)
+        } else {
+            write!(f, "{}/{}", self.numer, self.denom)
+        }
+    }
+}
+
+// 带单位的长度
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Length {
+    value: f64,
+    unit: Unit,
+}
+
+impl Length {
+    pub fn new(value: f64, unit: Unit) -> Self {
+        Length { value, unit }
+    }
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+    pub fn unit(&self) -> Unit {
+        self.unit
+    }
+    pub fn to(&self, unit: Unit) -> Length {
+        Length::new(self.value * self.unit.factor_to(unit).to_f64(), unit)
+    }
+}
+
+// 单位不同的长度相加时，结果沿用左边的单位
+impl Add for Length {
+    type Output = Length;
+    fn add(self, rhs: Length) -> Length {
+        Length::new(self.value + rhs.to(self.unit).value, self.unit)
+    }
+}
+
+// 长度乘长度得到面积
+impl Mul for Length {
+    type Output = Area;
+    fn mul(self, rhs: Length) -> Area {
+        Area::new(self.value * rhs.to(self.unit).value, self.unit)
+    }
+}
+
+impl fmt::Display for Length {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match f.precision() {
+            Some(p) => write!(f, "{:.*} {}", p, self.value, self.unit.symbol()),
+            None => write!(f, "{} {}", self.value, self.unit.symbol()),
+        }
+    }
+}
+
+// 带单位的面积，unit 表示“平方 unit”
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Area {
+    value: f64,
+    unit: Unit,
+}
+
+impl Area {
+    pub fn new(value: f64, unit: Unit) -> Self {
+        Area { value, unit }
+    }
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+    pub fn unit(&self) -> Unit {
+        self.unit
+    }
+    // 面积的换算系数是长度换算系数的平方
+    pub fn to(&self, unit: Unit) -> Area {
+        let factor = self.unit.factor_to(unit);
+        Area::new(self.value * (factor * factor).to_f64(), unit)
+    }
+}
+
+impl fmt::Display for Area {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match f.precision() {
+            Some(p) => write!(f, "{:.*} {}²", p, self.value, self.unit.symbol()),
+            None => write!(f, "{} {}²", self.value, self.unit.symbol()),
+        }
+    }
+}
+
+// 精确面积：数值是分数，换算只做分数乘法，不会出现 0.1 + 0.2 那样的误差
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExactArea {
+    value: Ratio,
+    unit: Unit,
+}
+
+impl ExactArea {
+    pub fn value(&self) -> Ratio {
+        self.value
+    }
+    pub fn unit(&self) -> Unit {
+        self.unit
+    }
+    pub fn to(&self, unit: Unit) -> ExactArea {
+        let factor = self.unit.factor_to(unit);
+        ExactArea {
+            value: self.value * factor * factor,
+            unit,
+        }
+    }
+    // 需要展示小数时再显式转成 f64
+    pub fn to_area(self) -> Area {
+        Area::new(self.value.to_f64(), self.unit)
+    }
+}
+
+impl fmt::Display for ExactArea {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}²", self.value, self.unit.symbol())
+    }
+}
+
+// 方法定义和结构体定义可以分开放，在这里给 Circle 和 Rectangle 加上带单位的版本
+impl Circle {
+    // 把半径当作以 unit 为单位来计算面积
+    pub fn area_in(&self, unit: Unit) -> Area {
+        Area::new(self.area(), unit)
+    }
+}
+
+impl Rectangle {
+    // 和 exact_area 一样先在 u64 里相乘，u32 的 area() 在大尺寸下会溢出
+    pub fn area_in(&self, unit: Unit) -> Area {
+        Area::new((self.width as u64 * self.height as u64) as f64, unit)
+    }
+    // 精确模式：宽高都是整数，面积是整数，换算到任何单位都用分数表示
+    pub fn exact_area(&self, unit: Unit) -> ExactArea {
+        ExactArea {
+            value: Ratio::new(self.width as u128 * self.height as u128, 1),
+            unit,
+        }
+    }
+}
+
+pub(crate) fn units() {
+    let rec = Rectangle::new(30, 10);
+    let area = rec.area_in(Unit::Centimeter);
+    println!(
+        "{} = {} = {:.4}",
+        area,
+        area.to(Unit::Millimeter),
+        area.to(Unit::Meter)
+    );
+
+    // 96 DPI 下，96 像素正好是 1 英寸
+    let inch = Length::new(96.0, Unit::Pixel { dpi: 96 }).to(Unit::Inch);
+    println!("96 px @96dpi = {}", inch);
+    let sum = Length::new(1.0, Unit::Inch) + Length::new(4.6, Unit::Millimeter);
+    println!(
+        "1 in + 4.6 mm = {:.3} (kept in {:?}, {:.4})",
+        sum.to(Unit::Centimeter),
+        sum.unit(),
+        sum.value()
+    );
+    let square = Length::new(2.0, Unit::Meter) * Length::new(50.0, Unit::Centimeter);
+    println!("2 m * 50 cm = {}", square);
+
+    let circle = Circle::new(0.0, 0.0, 1.0);
+    println!("{:.5}", circle.area_in(Unit::Inch).to(Unit::Centimeter));
+
+    // 精确模式：1 平方英寸 = 16129/25 平方毫米，来回换算之后仍然严格相等
+    let exact = Rectangle::new(1, 1).exact_area(Unit::Inch);
+    let mm = exact.to(Unit::Millimeter);
+    println!("{} = {} = {}", exact, mm, mm.to(Unit::Pixel { dpi: 300 }));
+    println!(
+        "numerator {} / denominator {} in {:?}, float {} {:?}",
+        mm.value().numer(),
+        mm.value().denom(),
+        mm.unit(),
+        area.value(),
+        area.unit()
+    );
+
+    // 浮点换算来回多次可能积累误差，分数保证严格相等
+    let exact = rec.exact_area(Unit::Pixel { dpi: 72 });
+    let mut float = exact.to_area();
+    let mut ratio = exact;
+    for unit in ROUND_TRIP {
+        float = float.to(unit);
+        ratio = ratio.to(unit);
+    }
+    println!("float round trip: {}, exact round trip: {}", float, ratio);
+}
+
+const ROUND_TRIP: [Unit; 4] = [
+    Unit::Centimeter,
+    Unit::Inch,
+    Unit::Meter,
+    Unit::Pixel { dpi: 72 },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_lengths_and_areas() {
+        let area = Rectangle::new(30, 10).area_in(Unit::Centimeter);
+        assert_eq!(area.to(Unit::Millimeter).value(), 30000.0);
+
+        // 96 DPI 下，96 像素正好是 1 英寸
+        let inch = Length::new(96.0, Unit::Pixel { dpi: 96 }).to(Unit::Inch);
+        assert_eq!(inch.value(), 1.0);
+        let sum = Length::new(1.0, Unit::Inch) + Length::new(4.6, Unit::Millimeter);
+        assert_eq!(sum.unit(), Unit::Inch);
+        let square = Length::new(2.0, Unit::Meter) * Length::new(50.0, Unit::Centimeter);
+        assert_eq!(square.unit(), Unit::Meter);
+        assert_eq!(square.value(), 1.0);
+    }
+
+    #[test]
+    fn area_in_does_not_overflow_u32() {
+        let area = Rectangle::new(100_000, 100_000).area_in(Unit::Millimeter);
+        assert_eq!(area.value(), 1e10);
+        let exact = Rectangle::new(100_000, 100_000).exact_area(Unit::Millimeter);
+        assert_eq!(exact.value().numer(), 10_000_000_000);
+    }
+
+    #[test]
+    fn exact_areas_round_trip() {
+        // 1 平方英寸 = 16129/25 平方毫米
+        let exact = Rectangle::new(1, 1).exact_area(Unit::Inch);
+        let mm = exact.to(Unit::Millimeter);
+        assert_eq!((mm.value().numer(), mm.value().denom()), (16129, 25));
+        assert_eq!(mm.to(Unit::Inch), exact);
+
+        let exact = Rectangle::new(30, 10).exact_area(Unit::Pixel { dpi: 72 });
+        let mut ratio = exact;
+        for unit in ROUND_TRIP {
+            ratio = ratio.to(unit);
+        }
+        assert_eq!(ratio, exact);
+        assert_eq!(ratio.unit(), exact.unit());
+    }
+}